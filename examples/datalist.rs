@@ -0,0 +1,41 @@
+//! An input with suggestions from a datalist
+
+use dioxus::prelude::*;
+
+fn main() {
+    mini_dxn::launch(app);
+}
+
+fn app() -> Element {
+    let mut value = use_signal(String::new);
+
+    rsx! {
+        style { {CSS} }
+        h1 { "Datalist suggestions" }
+        label {
+            r#for: "browser",
+            "Choose a browser: "
+        }
+        input {
+            id: "browser",
+            list: "browsers",
+            oninput: move |ev| value.set(ev.value()),
+        }
+        datalist {
+            id: "browsers",
+            option { value: "Chrome" }
+            option { value: "Edge" }
+            option { value: "Firefox" }
+            option { value: "Opera" }
+            option { value: "Safari" }
+            option { value: "Blitz" }
+        }
+        p { "Value: {value}" }
+    }
+}
+
+const CSS: &str = r#"
+input {
+    width: 200px;
+}
+"#;
@@ -0,0 +1,72 @@
+//! A modal `<dialog>` which is painted in the top layer above a dimmed `::backdrop`
+
+use std::sync::Arc;
+
+use anyrender_vello::VelloWindowRenderer;
+use blitz_dom::DocumentConfig;
+use blitz_html::{HtmlDocument, HtmlProvider};
+use blitz_shell::{BlitzApplication, BlitzShellEvent, WindowConfig, create_default_event_loop};
+
+pub fn main() {
+    // Parse the HTML into a Blitz document
+    let mut doc = HtmlDocument::from_html(
+        HTML,
+        DocumentConfig {
+            html_parser_provider: Some(Arc::new(HtmlProvider) as _),
+            ..Default::default()
+        },
+    );
+
+    // Open the dialog as a modal (as `dialog.showModal()` would)
+    let dialog_id = doc.query_selector("#dialog").unwrap().unwrap();
+    doc.show_modal(dialog_id);
+    doc.resolve(0.0);
+
+    // Create the Winit application and window
+    let event_loop = create_default_event_loop::<BlitzShellEvent>();
+    let mut application = BlitzApplication::new(event_loop.create_proxy());
+    let renderer = VelloWindowRenderer::new();
+    let window = WindowConfig::new(Box::new(doc), renderer);
+    application.add_window(window);
+
+    // Run event loop
+    event_loop.run_app(&mut application).unwrap()
+}
+
+static HTML: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head>
+        <style type="text/css">
+            body {
+                font-family: sans-serif;
+                margin: 24px;
+            }
+            .card {
+                background: #dbeafe;
+                padding: 12px;
+                margin-bottom: 12px;
+            }
+            dialog {
+                width: 320px;
+                border: 1px solid #9ca3af;
+                border-radius: 8px;
+                box-shadow: 0 8px 24px rgba(0, 0, 0, 0.3);
+            }
+            dialog::backdrop {
+                background: rgba(15, 23, 42, 0.6);
+            }
+        </style>
+    </head>
+    <body>
+        <h1>Modal dialog</h1>
+        <div class="card">This content is dimmed by the dialog's backdrop</div>
+        <div class="card">So is this</div>
+        <dialog id="dialog">
+            <h2>Hello from the top layer</h2>
+            <p>This dialog is painted above the rest of the page.</p>
+        </dialog>
+        <div class="card">Content after the dialog is painted beneath it too</div>
+    </body>
+    </html>
+"#;
@@ -0,0 +1,53 @@
+//! A multi-track grid for trying out the grid devtools overlays.
+//!
+//! Press Alt+L to toggle the grid line overlay and Alt+G to toggle the gap overlay.
+
+use dioxus::prelude::*;
+
+fn main() {
+    mini_dxn::launch(app);
+}
+
+fn app() -> Element {
+    rsx! {
+        style { {CSS} }
+        h1 { "Grid overlay" }
+        p { "Press Alt+L to show grid lines and Alt+G to show gaps" }
+        div { class: "grid",
+            header { "Header" }
+            nav { "Sidebar" }
+            main {
+                for idx in 1..=6 {
+                    div { class: "card", "Card {idx}" }
+                }
+            }
+            footer { "Footer" }
+        }
+    }
+}
+
+const CSS: &str = r#"
+.grid {
+    display: grid;
+    grid-template-columns: 160px 1fr 1fr;
+    grid-template-rows: 60px auto 40px;
+    grid-template-areas:
+        "header header header"
+        "sidebar main main"
+        "footer footer footer";
+    gap: 12px 16px;
+    padding: 16px;
+    max-width: 800px;
+}
+header { grid-area: header; background: #dbeafe; }
+nav { grid-area: sidebar; background: #dcfce7; }
+main {
+    grid-area: main;
+    display: grid;
+    grid-template-columns: repeat(3, 1fr);
+    grid-auto-rows: 80px;
+    gap: 8px;
+}
+footer { grid-area: footer; background: #fef3c7; }
+.card { background: #f3f4f6; border: 1px solid #d1d5db; }
+"#;
@@ -0,0 +1,27 @@
+//! A textarea which can be resized by dragging the grip in its bottom-right corner
+
+use dioxus::prelude::*;
+
+fn main() {
+    mini_dxn::launch(app);
+}
+
+fn app() -> Element {
+    rsx! {
+        style { {CSS} }
+        h1 { "Resizable textarea" }
+        p { "Drag the grip in the bottom-right corner of the textarea to resize it." }
+        textarea { "Some text to edit" }
+    }
+}
+
+const CSS: &str = r#"
+textarea {
+    width: 300px;
+    height: 100px;
+    min-width: 150px;
+    min-height: 50px;
+    max-width: 600px;
+    max-height: 400px;
+}
+"#;
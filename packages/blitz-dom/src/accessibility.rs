@@ -5,8 +5,17 @@ impl BaseDocument {
     pub fn build_accessibility_tree(&self) -> TreeUpdate {
         let mut nodes = std::collections::HashMap::new();
         let mut window = AccessKitNode::new(Role::Window);
+        let mut hidden = std::collections::HashSet::new();
 
         self.visit(|node_id, node| {
+            // Prune `aria-hidden` subtrees. Nodes are visited in tree order, so the parent
+            // has always been visited first.
+            let parent_hidden = node.parent.is_some_and(|id| hidden.contains(&id));
+            if parent_hidden || node.attr(local_name!("aria-hidden")) == Some("true") {
+                hidden.insert(node_id);
+                return;
+            }
+
             let parent = node
                 .parent
                 .and_then(|parent_id| nodes.get_mut(&parent_id))
@@ -76,3 +85,37 @@ impl BaseDocument {
         (id, builder)
     }
 }
+
+#[test]
+fn aria_hidden_subtrees_are_excluded() {
+    use crate::test_utils::doc_with_body;
+    use crate::{Attribute, qual_name};
+
+    let (doc, [button, icon, icon_text, label]) = doc_with_body(800, 600, "", |mutr, body| {
+        let button = mutr.create_element(qual_name!("button", html), vec![]);
+        let icon = mutr.create_element(
+            qual_name!("span", html),
+            vec![Attribute {
+                name: qual_name!("aria-hidden"),
+                value: "true".to_string(),
+            }],
+        );
+        let icon_text = mutr.create_text_node("\u{2715}");
+        let label = mutr.create_text_node("Close");
+        mutr.append_children(icon, &[icon_text]);
+        mutr.append_children(button, &[icon, label]);
+        mutr.append_children(body, &[button]);
+        [button, icon, icon_text, label]
+    });
+
+    let tree = doc.build_accessibility_tree();
+    let contains = |id: usize| {
+        tree.nodes
+            .iter()
+            .any(|(node_id, _)| *node_id == NodeId(id as u64))
+    };
+    assert!(contains(button));
+    assert!(contains(label));
+    assert!(!contains(icon));
+    assert!(!contains(icon_text));
+}
@@ -0,0 +1,236 @@
+//! Suggestion popups for `<input list="...">` elements bound to a `<datalist>`
+
+use markup5ever::local_name;
+
+use crate::node::TextBrush;
+use crate::traversal::TreeTraverser;
+use crate::{BaseDocument, stylo_to_parley};
+
+/// The height of each option in a datalist popup (in CSS pixels)
+pub const DATALIST_OPTION_HEIGHT: f32 = 24.0;
+
+/// An `<option>` from a `<datalist>` which matches the current value of the input
+pub struct DatalistOption {
+    /// The value that the input is set to when the option is accepted
+    pub value: String,
+    /// The laid out text of the option
+    pub layout: parley::Layout<TextBrush>,
+}
+
+/// The suggestion popup for the focussed input element
+pub struct DatalistPopup {
+    /// The input element which the popup belongs to
+    pub input_id: usize,
+    /// The options from the input's datalist which match the input's current value
+    pub options: Vec<DatalistOption>,
+    /// The option highlighted by keyboard navigation (if any)
+    pub selected: Option<usize>,
+}
+
+impl DatalistPopup {
+    /// The position of the top-left corner of the popup, and its width (in CSS pixels relative to
+    /// the document). The popup is placed directly below the input and matches its width.
+    pub fn origin_and_width(&self, doc: &BaseDocument) -> (taffy::Point<f32>, f32) {
+        let input = &doc.nodes[self.input_id];
+        let position = input.absolute_position(0.0, 0.0);
+        let size = input.final_layout.size;
+        let origin = taffy::Point {
+            x: position.x,
+            y: position.y + size.height,
+        };
+        (origin, size.width)
+    }
+
+    /// The index of the option at the specified point (in CSS pixels relative to the document)
+    pub fn option_at(&self, doc: &BaseDocument, x: f32, y: f32) -> Option<usize> {
+        let (origin, width) = self.origin_and_width(doc);
+        if x < origin.x || x >= origin.x + width || y < origin.y {
+            return None;
+        }
+        let index = ((y - origin.y) / DATALIST_OPTION_HEIGHT) as usize;
+        (index < self.options.len()).then_some(index)
+    }
+
+    /// Highlight the next (or previous if `forward` is false) option, wrapping around at the ends
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let count = self.options.len();
+        self.selected = Some(match (self.selected, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(idx), true) => (idx + 1) % count,
+            (Some(idx), false) => (idx + count - 1) % count,
+        });
+    }
+}
+
+impl BaseDocument {
+    pub fn datalist_popup(&self) -> Option<&DatalistPopup> {
+        self.datalist_popup.as_ref()
+    }
+
+    /// Show, update, or hide the suggestion popup for the focussed input based on the input's
+    /// current value. Options match if their value contains the input's value (case-insensitive).
+    pub(crate) fn update_datalist_popup(&mut self) {
+        let had_popup = self.datalist_popup.is_some();
+        self.datalist_popup = self
+            .focus_node_id
+            .and_then(|input_id| self.build_datalist_popup(input_id));
+        if had_popup || self.datalist_popup.is_some() {
+            self.shell_provider.request_redraw();
+        }
+    }
+
+    pub(crate) fn close_datalist_popup(&mut self) {
+        if self.datalist_popup.take().is_some() {
+            self.shell_provider.request_redraw();
+        }
+    }
+
+    /// Set the value of the popup's input to the option at `index` and close the popup.
+    /// Returns the input's id and new value if an option was accepted.
+    pub(crate) fn accept_datalist_option(&mut self, index: usize) -> Option<(usize, String)> {
+        let popup = self.datalist_popup.take()?;
+        let option = popup.options.into_iter().nth(index)?;
+
+        let input = self.nodes[popup.input_id].element_data_mut()?;
+        let input_data = input.text_input_data_mut()?;
+        input_data.set_text(
+            &mut self.font_ctx.lock().unwrap(),
+            &mut self.layout_ctx,
+            &option.value,
+        );
+        self.shell_provider.request_redraw();
+
+        Some((popup.input_id, option.value))
+    }
+
+    fn build_datalist_popup(&mut self, input_id: usize) -> Option<DatalistPopup> {
+        let input = self.nodes[input_id].element_data()?;
+        if input.name.local != local_name!("input") {
+            return None;
+        }
+        let datalist_id = *self.nodes_to_id.get(input.attr(local_name!("list"))?)?;
+        if !self.nodes[datalist_id]
+            .data
+            .is_element_with_tag_name(&local_name!("datalist"))
+        {
+            return None;
+        }
+        // Trimmed because empty inputs are seeded with a space
+        let query = input
+            .text_input_data()?
+            .editor
+            .raw_text()
+            .trim()
+            .to_lowercase();
+
+        let values: Vec<String> = TreeTraverser::new_with_root(self, datalist_id)
+            .filter_map(|node_id| {
+                let node = &self.nodes[node_id];
+                let option = node.element_data()?;
+                if option.name.local != local_name!("option")
+                    || option.attr(local_name!("disabled")).is_some()
+                {
+                    return None;
+                }
+                Some(
+                    option
+                        .attr(local_name!("value"))
+                        .map(str::to_string)
+                        .unwrap_or_else(|| node.text_content()),
+                )
+            })
+            .filter(|value| !value.is_empty() && value.to_lowercase().contains(&query))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        // Lay out the options using the input's text styles
+        let input = &self.nodes[input_id];
+        let styles = input.primary_styles()?;
+        let parley_style = stylo_to_parley::style(input_id, &styles, input.lang());
+        let mut font_ctx = self.font_ctx.lock().unwrap();
+        let options = values
+            .into_iter()
+            .map(|value| {
+                let mut builder = self.layout_ctx.tree_builder(
+                    &mut font_ctx,
+                    self.viewport.scale(),
+                    true,
+                    &parley_style,
+                );
+                builder.push_text(&value);
+                let mut layout = builder.build().0;
+                layout.break_all_lines(None);
+                DatalistOption { value, layout }
+            })
+            .collect();
+
+        Some(DatalistPopup {
+            input_id,
+            options,
+            selected: None,
+        })
+    }
+}
+
+#[test]
+fn typing_filters_datalist_options() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::events::{BlitzKeyEvent, KeyState};
+    use keyboard_types::{Code, Key, Location, Modifiers};
+
+    let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("list"), "fruits");
+        let datalist = mutr.create_element(qual_name!("datalist", html), vec![]);
+        mutr.set_attribute(datalist, qual_name!("id"), "fruits");
+        for fruit in ["Apple", "Banana", "Mango", "Pineapple"] {
+            let option = mutr.create_element(qual_name!("option", html), vec![]);
+            mutr.set_attribute(option, qual_name!("value"), fruit);
+            mutr.append_children(datalist, &[option]);
+        }
+        mutr.append_children(body, &[input, datalist]);
+        input
+    });
+
+    let option_values = |doc: &BaseDocument| -> Vec<String> {
+        doc.datalist_popup()
+            .map(|popup| popup.options.iter().map(|o| o.value.clone()).collect())
+            .unwrap_or_default()
+    };
+    let key_event = |key: Key| BlitzKeyEvent {
+        key,
+        code: Code::Unidentified,
+        modifiers: Modifiers::empty(),
+        location: Location::Standard,
+        is_auto_repeating: false,
+        is_composing: false,
+        state: KeyState::Pressed,
+        text: None,
+    };
+
+    // Focussing the input suggests every option
+    doc.set_focus_to(input);
+    assert_eq!(
+        option_values(&doc),
+        ["Apple", "Banana", "Mango", "Pineapple"]
+    );
+
+    // Typing filters the options (case-insensitively)
+    let event = key_event(Key::Character("AN".into()));
+    crate::events::handle_keypress(&mut doc, input, event, |_| {});
+    assert_eq!(option_values(&doc), ["Banana", "Mango"]);
+
+    // Arrow keys and Enter accept an option
+    crate::events::handle_keypress(&mut doc, input, key_event(Key::ArrowDown), |_| {});
+    crate::events::handle_keypress(&mut doc, input, key_event(Key::Enter), |_| {});
+    assert!(doc.datalist_popup().is_none());
+    let value = doc.nodes[input]
+        .element_data()
+        .and_then(|el| el.text_input_data())
+        .map(|data| data.editor.raw_text().to_string());
+    assert_eq!(value.as_deref(), Some("Banana"));
+}
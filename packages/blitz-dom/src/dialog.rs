@@ -0,0 +1,231 @@
+//! Modal `<dialog>` elements and the top layer (<https://fullscreen.spec.whatwg.org/#top-layer>)
+//!
+//! Elements in the top layer are painted above the rest of the document (in the order they were
+//! added), each with a `::backdrop` which covers the viewport beneath it.
+//!
+//! TODO:
+//!   - Non-modal dialogs (`show()`) and popovers
+//!   - Making content outside of a modal dialog inert (focus is trapped within it, but it doesn't
+//!     prevent pointer interaction with the rest of the document)
+
+use blitz_traits::events::{DomEvent, DomEventData};
+use markup5ever::local_name;
+use style::properties::ComputedValues;
+use style::selector_parser::PseudoElement;
+use style::servo_arc::Arc as ServoArc;
+use style::shared_lock::StylesheetGuards;
+use style::stylist::RuleInclusion;
+use style_dom::ElementState;
+
+use crate::{BaseDocument, qual_name};
+
+impl BaseDocument {
+    /// The elements in the top layer, in painting order (the last element is topmost)
+    pub fn top_layer(&self) -> &[usize] {
+        &self.top_layer
+    }
+
+    /// Whether the element is in the top layer (and so is painted above the rest of the document)
+    pub fn is_in_top_layer(&self, node_id: usize) -> bool {
+        self.top_layer.contains(&node_id)
+    }
+
+    /// The topmost modal dialog (if any). Focus is trapped within it and pressing Escape cancels it.
+    pub(crate) fn topmost_modal_dialog(&self) -> Option<usize> {
+        self.top_layer
+            .iter()
+            .rev()
+            .copied()
+            .find(|id| self.nodes[*id].element_state.contains(ElementState::MODAL))
+    }
+
+    /// Open a `<dialog>` element as a modal dialog, adding it to the top layer. This is equivalent to
+    /// the DOM's `HTMLDialogElement.showModal()`.
+    ///
+    /// Returns `false` (and does nothing) if the node is not a `<dialog>` element, or if it is already open.
+    pub fn show_modal(&mut self, node_id: usize) -> bool {
+        let Some(node) = self.get_node(node_id) else {
+            return false;
+        };
+        if !node.data.is_element_with_tag_name(&local_name!("dialog"))
+            || node.attr(local_name!("open")).is_some()
+            || !node.flags.is_in_document()
+        {
+            return false;
+        }
+
+        self.mutate().set_attribute(node_id, qual_name!("open"), "");
+        self.snapshot_node_and(node_id, |node| {
+            node.element_state.insert(ElementState::MODAL)
+        });
+        self.top_layer.push(node_id);
+        self.shell_provider.request_redraw();
+        true
+    }
+
+    /// Close an open `<dialog>` element, removing it from the top layer if it is modal, and fire a
+    /// `close` event at it. This is equivalent to the DOM's `HTMLDialogElement.close()`.
+    ///
+    /// Returns `false` (and does nothing) if the node is not an open `<dialog>` element.
+    pub fn close_dialog(&mut self, node_id: usize) -> bool {
+        let Some(node) = self.get_node(node_id) else {
+            return false;
+        };
+        if !node.data.is_element_with_tag_name(&local_name!("dialog"))
+            || node.attr(local_name!("open")).is_none()
+        {
+            return false;
+        }
+
+        self.mutate().clear_attribute(node_id, qual_name!("open"));
+        self.snapshot_node_and(node_id, |node| {
+            node.element_state.remove(ElementState::MODAL)
+        });
+        self.top_layer.retain(|id| *id != node_id);
+        self.pending_events
+            .push(DomEvent::new(node_id, DomEventData::Close));
+        self.shell_provider.request_redraw();
+        true
+    }
+
+    /// Compute the style of the `::backdrop` of an element in the top layer.
+    ///
+    /// Returns `None` if the element is not in the top layer or its styles have not been resolved.
+    pub fn backdrop_style(&self, node_id: usize) -> Option<ServoArc<ComputedValues>> {
+        if !self.is_in_top_layer(node_id) {
+            return None;
+        }
+        let node = &self.nodes[node_id];
+        let style = node.primary_styles()?;
+
+        let read_guard = self.guard.read();
+        let guards = StylesheetGuards::same(&read_guard);
+        self.stylist.lazily_compute_pseudo_element_style(
+            &guards,
+            node,
+            &PseudoElement::Backdrop,
+            RuleInclusion::All,
+            &style,
+            false,
+            None,
+        )
+    }
+}
+
+#[test]
+fn show_modal_adds_dialog_to_top_layer() {
+    use crate::test_utils::doc_with_body;
+    use style::color::AbsoluteColor;
+
+    let (mut doc, [dialog, div]) = doc_with_body(800, 600, "", |mutr, body| {
+        let dialog = mutr.create_element(qual_name!("dialog", html), vec![]);
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(body, &[dialog, div]);
+        [dialog, div]
+    });
+
+    // Only dialogs can be shown as modals
+    assert!(!doc.show_modal(div));
+    assert!(doc.top_layer().is_empty());
+
+    assert!(doc.show_modal(dialog));
+    assert!(!doc.show_modal(dialog));
+    doc.resolve(0.0);
+    assert_eq!(doc.top_layer(), [dialog]);
+
+    // The dialog matches `:modal` (and so is positioned by the UA stylesheet)
+    let styles = doc.get_node(dialog).unwrap().primary_styles().unwrap();
+    assert_eq!(
+        styles.clone_position(),
+        style::computed_values::position::T::Fixed
+    );
+    drop(styles);
+
+    // The UA stylesheet dims the viewport behind the dialog
+    let backdrop = doc.backdrop_style(dialog).unwrap();
+    let background = backdrop
+        .clone_background_color()
+        .resolve_to_absolute(&AbsoluteColor::BLACK);
+    assert!(background.alpha > 0.0);
+    assert!(doc.backdrop_style(div).is_none());
+
+    assert!(doc.close_dialog(dialog));
+    doc.resolve(0.0);
+    assert!(doc.top_layer().is_empty());
+    assert!(doc.backdrop_style(dialog).is_none());
+}
+
+#[test]
+fn tab_cycles_within_modal_and_escape_closes_it() {
+    use crate::test_utils::doc_with_body;
+    use crate::{EventDriver, NoopEventHandler};
+    use blitz_traits::events::{BlitzKeyEvent, EventPhase, UiEvent};
+    use keyboard_types::{Code, Key, KeyState, Location, Modifiers};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let (mut doc, [before, dialog, first, second, after]) =
+        doc_with_body(800, 600, "", |mutr, body| {
+            let before = mutr.create_element(qual_name!("button", html), vec![]);
+            let dialog = mutr.create_element(qual_name!("dialog", html), vec![]);
+            let first = mutr.create_element(qual_name!("button", html), vec![]);
+            let second = mutr.create_element(qual_name!("button", html), vec![]);
+            let after = mutr.create_element(qual_name!("button", html), vec![]);
+            mutr.append_children(dialog, &[first, second]);
+            mutr.append_children(body, &[before, dialog, after]);
+            [before, dialog, first, second, after]
+        });
+
+    doc.focus(before);
+    assert!(doc.show_modal(dialog));
+    doc.resolve(0.0);
+
+    // Tab and Shift+Tab cycle among the dialog's controls without reaching the rest of the document
+    assert_eq!(doc.focus_next_node(), Some(first));
+    assert_eq!(doc.focus_next_node(), Some(second));
+    assert_eq!(doc.focus_next_node(), Some(first));
+    assert_eq!(doc.focus_prev_node(), Some(second));
+    assert_eq!(doc.focus_prev_node(), Some(first));
+    doc.take_pending_events();
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let recorded = events.clone();
+    doc.set_event_trace_hook(move |event, current_target, phase| {
+        if phase == EventPhase::AtTarget {
+            recorded.borrow_mut().push((event.name(), current_target));
+        }
+    });
+
+    // Escape fires `cancel` at the dialog, which closes it (firing `close`)
+    let escape = BlitzKeyEvent {
+        key: Key::Escape,
+        code: Code::Escape,
+        modifiers: Modifiers::empty(),
+        location: Location::Standard,
+        is_auto_repeating: false,
+        is_composing: false,
+        state: KeyState::Down,
+        text: None,
+    };
+    let mut driver = EventDriver::new(doc.mutate(), NoopEventHandler);
+    driver.handle_ui_event(UiEvent::KeyDown(escape));
+    drop(driver);
+
+    assert_eq!(
+        *events.borrow(),
+        [("keydown", first), ("cancel", dialog), ("close", dialog)]
+    );
+    assert!(doc.top_layer().is_empty());
+    assert!(
+        doc.get_node(dialog)
+            .unwrap()
+            .attr(local_name!("open"))
+            .is_none()
+    );
+
+    // With the dialog closed, focus can leave it again
+    doc.focus(after);
+    assert_eq!(doc.focus_prev_node(), Some(second));
+    assert_eq!(doc.focus_prev_node(), Some(first));
+    assert_eq!(doc.focus_prev_node(), Some(before));
+}
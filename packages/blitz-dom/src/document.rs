@@ -1,10 +1,12 @@
-use crate::events::handle_dom_event;
+use crate::datalist::DatalistPopup;
+use crate::events::{ResizeDrag, SelectionDrag, handle_dom_event, update_selection_drag};
 use crate::font_metrics::BlitzFontMetricsProvider;
 use crate::layout::construct::ConstructionTask;
 use crate::layout::damage::ALL_DAMAGE;
 use crate::mutator::ViewportMut;
-use crate::net::{CssHandler, Resource, StylesheetLoader};
+use crate::net::{CssHandler, ImageHandler, Resource, StylesheetLoader};
 use crate::node::{ImageData, NodeFlags, RasterImageData, SpecialElementData, Status, TextBrush};
+use crate::scroll_into_view::{ScrollIntoViewOptions, SmoothScroll};
 use crate::stylo_to_cursor_icon::stylo_to_cursor_icon;
 use crate::traversal::TreeTraverser;
 use crate::url::DocumentUrl;
@@ -14,14 +16,20 @@ use crate::{
     EventDriver, HtmlParserProvider, Node, NodeData, NoopEventHandler, TextNodeData,
 };
 use blitz_traits::devtools::DevtoolSettings;
-use blitz_traits::events::{DomEvent, HitResult, UiEvent};
+use blitz_traits::events::{
+    BlitzFocusEvent, DomEvent, DomEventData, EventPhase, FocusOrigin, HitResult, UiEvent,
+};
 use blitz_traits::navigation::{DummyNavigationProvider, NavigationProvider};
-use blitz_traits::net::{DummyNetProvider, NetProvider, Request, SharedProvider};
-use blitz_traits::shell::{ColorScheme, DummyShellProvider, ShellProvider, Viewport};
+use blitz_traits::net::{Bytes, DummyNetProvider, NetProvider, Request, SharedProvider};
+use blitz_traits::shell::{
+    ColorScheme, CustomCursor, DummyShellProvider, MediaType as ViewportMediaType, ShellProvider,
+    Viewport,
+};
 use cursor_icon::CursorIcon;
 use linebender_resource_handle::Blob;
-use markup5ever::local_name;
+use markup5ever::{LocalName, local_name};
 use parley::FontContext;
+use parley::fontique::FontInfoOverride;
 use selectors::{Element, matching::QuirksMode};
 use slab::Slab;
 use std::any::Any;
@@ -35,14 +43,17 @@ use style::Atom;
 use style::animation::DocumentAnimationSet;
 use style::attr::{AttrIdentifier, AttrValue};
 use style::data::{ElementData as StyloElementData, ElementStyles};
+use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries::MediaType;
 use style::properties::ComputedValues;
 use style::properties::style_structs::Font;
 use style::queries::values::PrefersColorScheme;
 use style::selector_parser::ServoElementSnapshot;
+use style::servo::url::ComputedUrl;
 use style::servo_arc::Arc as ServoArc;
 use style::values::GenericAtomIdent;
 use style::values::computed::Overflow;
+use style::values::generics::image::Image as StyloImage;
 use style::{
     dom::{TDocument, TNode},
     media_queries::{Device, MediaList},
@@ -53,6 +64,28 @@ use style::{
 };
 use url::Url;
 
+/// How long (in seconds) the pointer must rest over an element before its `title` tooltip is shown
+pub(crate) const TOOLTIP_DELAY: f64 = 0.5;
+
+/// A `title` tooltip which will be shown once the pointer has rested for [`TOOLTIP_DELAY`]
+pub(crate) struct PendingTooltip {
+    text: String,
+    /// The pointer position (in document coordinates)
+    x: f32,
+    y: f32,
+    /// The animation time of the first tick after the pointer moved
+    hover_start: Option<f64>,
+}
+
+/// An image loaded for use by `cursor: url(...)`
+pub(crate) struct CursorImageData {
+    url: Url,
+    status: Status,
+    width: u32,
+    height: u32,
+    rgba: Arc<Vec<u8>>,
+}
+
 /// Abstraction over wrappers around [`BaseDocument`] to allow for them all to
 /// be driven by [`blitz-shell`](https://docs.rs/blitz-shell)
 pub trait Document: Deref<Target = BaseDocument> + DerefMut + 'static {
@@ -77,6 +110,9 @@ pub trait Document: Deref<Target = BaseDocument> + DerefMut + 'static {
     }
 }
 
+/// A hook which is invoked with each dispatched event, its current target, and its phase
+type EventTraceHook = dyn FnMut(&DomEvent, usize, EventPhase);
+
 pub struct BaseDocument {
     /// ID of the document
     id: usize,
@@ -84,6 +120,8 @@ pub struct BaseDocument {
     // Config
     /// Base url for resolving linked resources (stylesheets, images, fonts, etc)
     pub(crate) url: DocumentUrl,
+    /// The default browsing context for links (from the first `<base target>` element)
+    pub(crate) base_target: Option<String>,
     // Devtool settings. Currently used to render debug overlays
     pub(crate) devtool_settings: DevtoolSettings,
     // Viewport details such as the dimensions, HiDPI scale, and zoom factor,
@@ -116,10 +154,35 @@ pub struct BaseDocument {
     pub(crate) hover_node_id: Option<usize>,
     /// The node which is currently focussed (if any)
     pub(crate) focus_node_id: Option<usize>,
+    /// What caused the most recent change of focus (if focus has been set)
+    pub(crate) focus_origin: Option<FocusOrigin>,
+    /// Events fired by the document itself (e.g. `focus` and `blur`) which are waiting to be
+    /// dispatched by an [`EventDriver`](crate::EventDriver)
+    pub(crate) pending_events: Vec<DomEvent>,
     /// The node which is currently active (if any)
     pub(crate) active_node_id: Option<usize>,
+    /// A hook which is invoked for every event dispatched to the document (see [`BaseDocument::set_event_trace_hook`])
+    pub(crate) event_trace_hook: Option<Box<EventTraceHook>>,
+    /// The suggestion popup for the focussed `<input list="...">` element (if any)
+    pub(crate) datalist_popup: Option<DatalistPopup>,
+    /// The elements in the top layer (such as modal dialogs), in painting order
+    pub(crate) top_layer: Vec<usize>,
+    /// The node which is the target of the document URL's fragment (if any)
+    pub(crate) target_node_id: Option<usize>,
     /// The node which recieved a mousedown event (if any)
     pub(crate) mousedown_node_id: Option<usize>,
+    /// Images referenced by `cursor: url(...)` (indexed by `ImageType::Cursor`)
+    pub(crate) cursor_images: Vec<CursorImageData>,
+    /// The element whose resize grip is being dragged (if any)
+    pub(crate) resize_drag: Option<ResizeDrag>,
+    /// The text input whose text is being selected by dragging the pointer (if any)
+    pub(crate) selection_drag: Option<SelectionDrag>,
+    /// The `title` tooltip waiting to be shown (if any)
+    pub(crate) pending_tooltip: Option<PendingTooltip>,
+    /// Scroll offsets which are being animated by smooth scrolling
+    pub(crate) smooth_scrolls: Vec<SmoothScroll>,
+    /// Whether the shell is currently showing a tooltip
+    pub(crate) tooltip_visible: bool,
 
     /// Whether there are active CSS animations/transitions (so we should re-render every frame)
     pub(crate) has_active_animations: bool,
@@ -139,6 +202,8 @@ pub struct BaseDocument {
     pub(crate) changed_nodes: HashSet<usize>,
     /// Set of changed nodes for updating the accessibility tree
     pub(crate) deferred_construction_nodes: Vec<ConstructionTask>,
+    /// Nodes which were damaged during the most recent resolve (only tracked when paint flashing is enabled)
+    pub(crate) repainted_nodes: Vec<usize>,
 
     // Service providers
     /// Network provider. Can be used to fetch assets.
@@ -158,8 +223,13 @@ pub(crate) fn make_device(viewport: &Viewport, font_ctx: Arc<Mutex<FontContext>>
     let viewport_size = euclid::Size2D::new(width, height);
     let device_pixel_ratio = euclid::Scale::new(viewport.scale());
 
+    let media_type = match viewport.media_type {
+        ViewportMediaType::Screen => MediaType::screen(),
+        ViewportMediaType::Print => MediaType::print(),
+    };
+
     Device::new(
-        MediaType::screen(),
+        media_type,
         selectors::matching::QuirksMode::NoQuirks,
         viewport_size,
         device_pixel_ratio,
@@ -247,6 +317,7 @@ impl BaseDocument {
             devtool_settings: DevtoolSettings::default(),
             viewport_scroll: crate::Point::ZERO,
             url: base_url,
+            base_target: None,
             ua_stylesheets: HashMap::new(),
             nodes_to_stylesheet: BTreeMap::new(),
             font_ctx,
@@ -254,12 +325,25 @@ impl BaseDocument {
 
             hover_node_id: None,
             focus_node_id: None,
+            focus_origin: None,
+            pending_events: Vec::new(),
             active_node_id: None,
+            target_node_id: None,
+            datalist_popup: None,
+            top_layer: Vec::new(),
+            event_trace_hook: None,
             mousedown_node_id: None,
+            cursor_images: Vec::new(),
+            resize_drag: None,
+            selection_drag: None,
+            pending_tooltip: None,
+            smooth_scrolls: Vec::new(),
+            tooltip_visible: false,
             has_active_animations: false,
             has_canvas: false,
             changed_nodes: HashSet::new(),
             deferred_construction_nodes: Vec::new(),
+            repainted_nodes: Vec::new(),
             controls_to_form: HashMap::new(),
             net_provider,
             navigation_provider,
@@ -319,6 +403,33 @@ impl BaseDocument {
     /// Set base url for resolving linked resources (stylesheets, images, fonts, etc)
     pub fn set_base_url(&mut self, url: &str) {
         self.url = DocumentUrl::from(Url::parse(url).unwrap());
+        self.update_base_element();
+    }
+
+    /// Update the base URL and default link target from the first `<base>` elements in the
+    /// document with `href` and `target` attributes respectively
+    ///
+    /// <https://html.spec.whatwg.org/multipage/semantics.html#the-base-element>
+    pub(crate) fn update_base_element(&mut self) {
+        let mut base_href = None;
+        let mut base_target = None;
+        for node_id in TreeTraverser::new(self) {
+            let node = &self.nodes[node_id];
+            if !node.flags.is_in_document()
+                || !node.data.is_element_with_tag_name(&local_name!("base"))
+            {
+                continue;
+            }
+            if base_href.is_none() {
+                base_href = node.attr(local_name!("href")).map(str::to_string);
+            }
+            if base_target.is_none() {
+                base_target = node.attr(local_name!("target")).map(str::to_string);
+            }
+        }
+
+        self.url.set_base_element_href(base_href.as_deref());
+        self.base_target = base_target;
     }
 
     pub fn guard(&self) -> &SharedRwLock {
@@ -350,6 +461,39 @@ impl BaseDocument {
         DocumentMutator::new(self)
     }
 
+    /// Set a hook which is invoked for every event dispatched through an [`EventDriver`](crate::EventDriver),
+    /// once for each node the event propagates to (the current target), before the event is delivered to it.
+    /// Useful for logging the stream of events when debugging event handling.
+    pub fn set_event_trace_hook(
+        &mut self,
+        hook: impl FnMut(&DomEvent, usize, EventPhase) + 'static,
+    ) {
+        self.event_trace_hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_event_trace_hook(&mut self) {
+        self.event_trace_hook = None;
+    }
+
+    /// Take the events fired by the document which are waiting to be dispatched (skipping any whose
+    /// target has since been removed from the document)
+    pub(crate) fn take_pending_events(&mut self) -> Vec<DomEvent> {
+        let mut events = std::mem::take(&mut self.pending_events);
+        events.retain(|event| self.nodes.contains(event.target));
+        events
+    }
+
+    pub(crate) fn trace_event(&mut self, event: &DomEvent, current_target: usize) {
+        if let Some(hook) = &mut self.event_trace_hook {
+            let phase = if current_target == event.target {
+                EventPhase::AtTarget
+            } else {
+                EventPhase::Bubbling
+            };
+            hook(event, current_target, phase);
+        }
+    }
+
     pub fn handle_dom_event<F: FnMut(DomEvent)>(
         &mut self,
         event: &mut DomEvent,
@@ -425,18 +569,24 @@ impl BaseDocument {
         }
     }
 
+    /// Set a single declaration in an element's `style` attribute. Only the element is restyled (along
+    /// with its descendants if an inherited property changed).
     pub fn set_style_property(&mut self, node_id: usize, name: &str, value: &str) {
         self.nodes[node_id]
             .element_data_mut()
             .unwrap()
             .set_style_property(name, value, &self.guard, self.url.url_extra_data());
+        self.nodes[node_id].set_restyle_hint(RestyleHint::RESTYLE_STYLE_ATTRIBUTE);
     }
 
+    /// Remove a single declaration from an element's `style` attribute. Only the element is restyled
+    /// (along with its descendants if an inherited property changed).
     pub fn remove_style_property(&mut self, node_id: usize, name: &str) {
         self.nodes[node_id]
             .element_data_mut()
             .unwrap()
             .remove_style_property(name, &self.guard, self.url.url_extra_data());
+        self.nodes[node_id].set_restyle_hint(RestyleHint::RESTYLE_STYLE_ATTRIBUTE);
     }
 
     pub fn root_node(&self) -> &Node {
@@ -648,6 +798,15 @@ impl BaseDocument {
             Resource::Css(node_id, css) => {
                 self.add_stylesheet_for_node(css, node_id);
             }
+            Resource::Image(_, ImageType::Cursor(idx), width, height, image_data) => {
+                if let Some(cursor_image) = self.cursor_images.get_mut(idx) {
+                    cursor_image.status = Status::Ok;
+                    cursor_image.width = width;
+                    cursor_image.height = height;
+                    cursor_image.rgba = image_data;
+                }
+                self.update_cursor();
+            }
             Resource::Image(node_id, kind, width, height, image_data) => {
                 let node = self.get_node_mut(node_id).unwrap();
 
@@ -672,10 +831,19 @@ impl BaseDocument {
                                 ImageData::Raster(RasterImageData::new(width, height, image_data))
                         }
                     }
+                    ImageType::Cursor(_) => unreachable!("cursor images are handled above"),
+                }
+            }
+            // TODO: rasterize SVG cursor images
+            #[cfg(feature = "svg")]
+            Resource::Svg(_, ImageType::Cursor(idx), _, _) => {
+                if let Some(cursor_image) = self.cursor_images.get_mut(idx) {
+                    cursor_image.status = Status::Error;
                 }
+                self.update_cursor();
             }
             #[cfg(feature = "svg")]
-            Resource::Svg(node_id, kind, tree) => {
+            Resource::Svg(node_id, kind, tree, source) => {
                 let node = self.get_node_mut(node_id).unwrap();
 
                 match kind {
@@ -692,46 +860,14 @@ impl BaseDocument {
                             .element_data_mut()
                             .and_then(|el| el.background_images.get_mut(idx))
                         {
-                            bg_image.status = Status::Ok;
-                            bg_image.image = ImageData::Svg(tree);
+                            bg_image.set_svg(tree, source);
                         }
                     }
+                    ImageType::Cursor(_) => unreachable!("cursor images are handled above"),
                 }
             }
-            Resource::Font(bytes) => {
-                let font = Blob::new(Arc::new(bytes));
-
-                // TODO: Implement FontInfoOveride
-                // TODO: Investigate eliminating double-box
-                let mut font_ctx = self.font_ctx.lock().unwrap();
-                font_ctx.collection.register_fonts(font.clone(), None);
-
-                #[cfg(feature = "parallel-construct")]
-                {
-                    use crate::resolve::FONT_CTX;
-
-                    let doc_font_ctx = &*font_ctx;
-                    rayon::broadcast(|_ctx| {
-                        FONT_CTX.with_borrow_mut(|font_ctx| {
-                            match font_ctx {
-                                None => {
-                                    println!(
-                                        "Initialising FontContext for thread {:?}",
-                                        std::thread::current().id()
-                                    );
-                                    *font_ctx = Some(Box::new(doc_font_ctx.clone()));
-                                }
-                                Some(font_ctx) => {
-                                    font_ctx.collection.register_fonts(font.clone(), None);
-                                }
-                            };
-                        })
-                    });
-                }
-                drop(font_ctx);
-
-                // TODO: see if we can only invalidate if resolved fonts may have changed
-                self.invalidate_inline_contexts();
+            Resource::Font(family, bytes) => {
+                self.load_font(family.as_deref(), bytes);
             }
             Resource::None => {
                 // Do nothing
@@ -740,6 +876,60 @@ impl BaseDocument {
         }
     }
 
+    /// Register font data (e.g. a font embedded in the application) with the document. The font
+    /// can then be used with `font-family` using the family name specified in the font file itself.
+    pub fn register_font(&mut self, bytes: impl Into<Bytes>) {
+        self.load_font(None, bytes.into());
+    }
+
+    /// Register font data with the document under the specified family name (like a `@font-face` rule)
+    pub fn register_font_face(&mut self, family: &str, bytes: impl Into<Bytes>) {
+        self.load_font(Some(family), bytes.into());
+    }
+
+    fn load_font(&mut self, family: Option<&str>, bytes: Bytes) {
+        let font = Blob::new(Arc::new(bytes));
+        let info_override = family.map(|family_name| FontInfoOverride {
+            family_name: Some(family_name),
+            ..Default::default()
+        });
+
+        // TODO: Investigate eliminating double-box
+        let mut font_ctx = self.font_ctx.lock().unwrap();
+        font_ctx
+            .collection
+            .register_fonts(font.clone(), info_override);
+
+        #[cfg(feature = "parallel-construct")]
+        {
+            use crate::resolve::FONT_CTX;
+
+            let doc_font_ctx = &*font_ctx;
+            rayon::broadcast(|_ctx| {
+                FONT_CTX.with_borrow_mut(|font_ctx| {
+                    match font_ctx {
+                        None => {
+                            println!(
+                                "Initialising FontContext for thread {:?}",
+                                std::thread::current().id()
+                            );
+                            *font_ctx = Some(Box::new(doc_font_ctx.clone()));
+                        }
+                        Some(font_ctx) => {
+                            font_ctx
+                                .collection
+                                .register_fonts(font.clone(), info_override);
+                        }
+                    };
+                })
+            });
+        }
+        drop(font_ctx);
+
+        // TODO: see if we can only invalidate if resolved fonts may have changed
+        self.invalidate_inline_contexts();
+    }
+
     pub fn snapshot_node(&mut self, node_id: usize) {
         let node = &mut self.nodes[node_id];
         let opaque_node_id = TNode::opaque(&&*node);
@@ -800,6 +990,19 @@ impl BaseDocument {
         }
     }
 
+    /// Record that the attribute `name` of the node is changing in its snapshot, so that Stylo
+    /// invalidates elements matched by selectors which depend on it. The snapshot only lists the
+    /// attributes the node had when it was taken, so this covers attributes which are being added.
+    pub(crate) fn snapshot_attr_change(&mut self, node_id: usize, name: &LocalName) {
+        let opaque_node_id = TNode::opaque(&&self.nodes[node_id]);
+        if let Some(snapshot) = self.snapshots.get_mut(&opaque_node_id) {
+            let name = GenericAtomIdent(name.clone());
+            if !snapshot.changed_attrs.contains(&name) {
+                snapshot.changed_attrs.push(name);
+            }
+        }
+    }
+
     pub fn snapshot_node_and(&mut self, node_id: usize, cb: impl FnOnce(&mut Node)) {
         self.snapshot_node(node_id);
         cb(&mut self.nodes[node_id]);
@@ -818,26 +1021,124 @@ impl BaseDocument {
         self.root_element().hit(x, y)
     }
 
+    /// Returns all of the elements under the (x, y) co-ordinates in paint order (topmost first).
+    /// This is equivalent to the DOM's `elementsFromPoint` API.
+    pub fn elements_from_point(&self, x: f32, y: f32) -> Vec<usize> {
+        if TDocument::as_node(&&self.nodes[0])
+            .first_element_child()
+            .is_none()
+        {
+            return Vec::new();
+        }
+
+        let mut elements: Vec<usize> = Vec::new();
+        for hit in self.root_element().hit_all(x, y) {
+            let node = &self.nodes[hit.node_id];
+            if node.is_element() && !elements.contains(&node.id) {
+                elements.push(node.id);
+            }
+        }
+        elements
+    }
+
+    /// Move focus to the next sequentially focussable node in document order (or the first if there is no focussed node).
+    ///
+    /// Nodes with a negative `tabindex` and inert nodes are skipped. While a modal dialog is open focus
+    /// cycles within it.
     pub fn focus_next_node(&mut self) -> Option<usize> {
-        let focussed_node_id = self.get_focussed_node_id()?;
-        let id = self.next_node(&self.nodes[focussed_node_id], |node| node.is_focussable())?;
-        self.set_focus_to(id);
+        let (start_id, scope) = self.sequential_focus_start();
+        let id = self.next_node(&self.nodes[start_id], |node| {
+            node.is_sequentially_focussable()
+                && !node.is_inert()
+                && self.is_inclusive_descendant(node.id, scope)
+        })?;
+        self.set_focus_with_origin(id, FocusOrigin::Keyboard);
+        Some(id)
+    }
+
+    /// Move focus to the previous sequentially focussable node in document order (or the last if there is no focussed node).
+    ///
+    /// Nodes with a negative `tabindex` and inert nodes are skipped. While a modal dialog is open focus
+    /// cycles within it.
+    pub fn focus_prev_node(&mut self) -> Option<usize> {
+        let (start_id, scope) = self.sequential_focus_start();
+        let id = self.prev_node(&self.nodes[start_id], |node| {
+            node.is_sequentially_focussable()
+                && !node.is_inert()
+                && self.is_inclusive_descendant(node.id, scope)
+        })?;
+        self.set_focus_with_origin(id, FocusOrigin::Keyboard);
         Some(id)
     }
 
+    /// The node to start sequential focus navigation from, and the subtree which the navigation is
+    /// scoped to (the topmost modal dialog if one is open, otherwise the whole document)
+    fn sequential_focus_start(&self) -> (usize, usize) {
+        let scope = self.topmost_modal_dialog().unwrap_or(0);
+        let start_id = self
+            .get_focussed_node_id()
+            .filter(|id| self.is_inclusive_descendant(*id, scope))
+            .unwrap_or(scope);
+        (start_id, scope)
+    }
+
+    /// Focus a node, firing `blur` and `focus` events (which are dispatched by the next
+    /// [`EventDriver`](crate::EventDriver) to handle an event, or by [`EventDriver::dispatch_pending_events`](crate::EventDriver::dispatch_pending_events)).
+    ///
+    /// This is equivalent to the DOM's [`HTMLElement.focus()`](https://developer.mozilla.org/en-US/docs/Web/API/HTMLElement/focus).
+    /// Does nothing if the node is not focussable (or is inert). Returns whether the node is focussed.
+    pub fn focus(&mut self, node_id: usize) -> bool {
+        if !self
+            .get_node(node_id)
+            .is_some_and(|node| node.is_focussable() && !node.is_inert())
+        {
+            return false;
+        }
+        self.set_focus_with_origin(node_id, FocusOrigin::Programmatic);
+        true
+    }
+
+    /// Remove focus from the focussed node (if any), firing a `blur` event.
+    ///
+    /// This is equivalent to the DOM's [`HTMLElement.blur()`](https://developer.mozilla.org/en-US/docs/Web/API/HTMLElement/blur).
+    pub fn blur(&mut self) {
+        self.clear_focus();
+    }
+
+    /// What caused the focussed node to be focussed (or `None` if no node is focussed)
+    pub fn focus_origin(&self) -> Option<FocusOrigin> {
+        self.focus_node_id.and(self.focus_origin)
+    }
+
     /// Clear the focussed node
     pub fn clear_focus(&mut self) {
-        if let Some(id) = self.focus_node_id {
+        if let Some(id) = self.focus_node_id.take() {
             let shell_provider = self.shell_provider.clone();
             self.snapshot_node_and(id, |node| node.blur(shell_provider));
-            self.focus_node_id = None;
+            self.update_focus_within(Some(id), None);
+            self.pending_events.push(DomEvent::new(
+                id,
+                DomEventData::Blur(BlitzFocusEvent {
+                    related_target: None,
+                }),
+            ));
+            self.shell_provider.request_redraw();
         }
+        self.close_datalist_popup();
     }
 
     pub fn set_mousedown_node_id(&mut self, node_id: Option<usize>) {
         self.mousedown_node_id = node_id;
     }
+
+    /// Focus a node without checking whether it is focussable (see [`BaseDocument::focus`])
     pub fn set_focus_to(&mut self, focus_node_id: usize) -> bool {
+        self.set_focus_with_origin(focus_node_id, FocusOrigin::Programmatic)
+    }
+
+    /// Focus a node without checking whether it is focussable, recording what caused the change of
+    /// focus. Returns `false` if the node was already focussed.
+    pub fn set_focus_with_origin(&mut self, focus_node_id: usize, origin: FocusOrigin) -> bool {
         if Some(focus_node_id) == self.focus_node_id {
             return false;
         }
@@ -846,20 +1147,142 @@ impl BaseDocument {
         tracing::info!("Focussed node {focus_node_id}");
 
         let shell_provider = self.shell_provider.clone();
+        let old_focus_id = self.focus_node_id;
+
+        // `:focus-visible` matches when focus is moved using the keyboard, and for elements which
+        // accept text input. Programmatic focus matches unless focus was last moved by the pointer.
+        let is_text_input = self.nodes[focus_node_id]
+            .element_data()
+            .and_then(|el| el.text_input_data())
+            .is_some();
+        let focus_visible = match origin {
+            FocusOrigin::Keyboard => true,
+            FocusOrigin::Pointer => is_text_input,
+            FocusOrigin::Programmatic => {
+                is_text_input || self.focus_origin != Some(FocusOrigin::Pointer)
+            }
+        };
 
         // Remove focus from the old node
-        if let Some(id) = self.focus_node_id {
+        if let Some(id) = old_focus_id {
             self.snapshot_node_and(id, |node| node.blur(shell_provider.clone()));
         }
 
         // Focus the new node
-        self.snapshot_node_and(focus_node_id, |node| node.focus(shell_provider));
+        self.snapshot_node_and(focus_node_id, |node| {
+            node.focus(shell_provider, focus_visible)
+        });
+        self.update_focus_within(old_focus_id, Some(focus_node_id));
 
         self.focus_node_id = Some(focus_node_id);
+        self.focus_origin = Some(origin);
+
+        if let Some(id) = old_focus_id {
+            self.pending_events.push(DomEvent::new(
+                id,
+                DomEventData::Blur(BlitzFocusEvent {
+                    related_target: Some(focus_node_id),
+                }),
+            ));
+        }
+        self.pending_events.push(DomEvent::new(
+            focus_node_id,
+            DomEventData::Focus(BlitzFocusEvent {
+                related_target: old_focus_id,
+            }),
+        ));
+
+        self.update_datalist_popup();
+        self.shell_provider.request_redraw();
 
         true
     }
 
+    /// Move `:focus-within` from the DOM ancestors of the old focussed node to those of the new one
+    fn update_focus_within(&mut self, old_focus_id: Option<usize>, new_focus_id: Option<usize>) {
+        let old_node_path = self.maybe_node_ancestors(old_focus_id);
+        let new_node_path = self.maybe_node_ancestors(new_focus_id);
+        let same_count = old_node_path
+            .iter()
+            .zip(&new_node_path)
+            .take_while(|(o, n)| o == n)
+            .count();
+        for &id in old_node_path.iter().skip(same_count) {
+            self.snapshot_node_and(id, |node| node.set_focus_within(false));
+        }
+        for &id in new_node_path.iter().skip(same_count) {
+            self.snapshot_node_and(id, |node| node.set_focus_within(true));
+        }
+    }
+
+    /// Navigate to a fragment within this document without reloading it: update the fragment of
+    /// the document's URL, apply `:target` to the element it identifies, and scroll that element
+    /// into view.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-to-the-fragment-identifier>
+    pub fn navigate_to_fragment(&mut self, fragment: &str) {
+        self.url.set_fragment(Some(fragment));
+
+        let decoded = percent_encoding::percent_decode_str(fragment).decode_utf8_lossy();
+        let target_id = if decoded.is_empty() {
+            None
+        } else {
+            self.nodes_to_id.get(decoded.as_ref()).copied().or_else(|| {
+                TreeTraverser::new(self).find(|&node_id| {
+                    let node = &self.nodes[node_id];
+                    node.data.is_element_with_tag_name(&local_name!("a"))
+                        && node.attr(local_name!("name")) == Some(decoded.as_ref())
+                })
+            })
+        };
+
+        if let Some(old_id) = self.target_node_id.take() {
+            self.snapshot_node_and(old_id, |node| node.set_is_url_target(false));
+        }
+
+        match target_id {
+            Some(target_id) => {
+                self.reveal_hidden_until_found(target_id);
+                self.snapshot_node_and(target_id, |node| node.set_is_url_target(true));
+                self.target_node_id = Some(target_id);
+
+                self.scroll_to(target_id, ScrollIntoViewOptions::default());
+            }
+            None if decoded.is_empty() || decoded.eq_ignore_ascii_case("top") => {
+                self.viewport_scroll = crate::Point::ZERO;
+            }
+            None => {}
+        }
+
+        self.shell_provider.request_redraw();
+    }
+
+    /// Reveal a node which is hidden by a `hidden="until-found"` attribute on itself or any of its
+    /// ancestors (e.g. because it was navigated to), by removing those attributes.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#ancestor-hidden-until-found-revealing-algorithm>
+    ///
+    /// TODO: fire `beforematch` events at the revealed elements
+    pub fn reveal_hidden_until_found(&mut self, node_id: usize) {
+        let mut hidden_until_found = Vec::new();
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let node = &self.nodes[id];
+            if node
+                .attr(local_name!("hidden"))
+                .is_some_and(|value| value.eq_ignore_ascii_case("until-found"))
+            {
+                hidden_until_found.push(id);
+            }
+            current = node.parent;
+        }
+
+        let mut mutr = self.mutate();
+        for id in hidden_until_found {
+            mutr.clear_attribute(id, crate::qual_name!("hidden"));
+        }
+    }
+
     pub fn active_node(&mut self) -> bool {
         let Some(hover_node_id) = self.get_hover_node_id() else {
             return false;
@@ -901,13 +1324,26 @@ impl BaseDocument {
         let hit = self.hit(x, y);
         let hover_node_id = hit.map(|hit| hit.node_id);
 
+        // Any pointer movement hides the current tooltip and restarts the dwell timer
+        self.hide_tooltip();
+        self.pending_tooltip = hover_node_id
+            .and_then(|node_id| self.title_text(node_id))
+            .map(|text| PendingTooltip {
+                text,
+                x,
+                y,
+                hover_start: None,
+            });
+
         // Return early if the new node is the same as the already-hovered node
         if hover_node_id == self.hover_node_id {
             return false;
         }
 
-        let old_node_path = self.maybe_node_layout_ancestors(self.hover_node_id);
-        let new_node_path = self.maybe_node_layout_ancestors(hover_node_id);
+        // `:hover` applies to the hovered node's DOM ancestors (which may not be layout ancestors,
+        // e.g. inline elements containing an inline-block)
+        let old_node_path = self.maybe_node_ancestors(self.hover_node_id);
+        let new_node_path = self.maybe_node_ancestors(hover_node_id);
         let same_count = old_node_path
             .iter()
             .zip(&new_node_path)
@@ -923,8 +1359,7 @@ impl BaseDocument {
         self.hover_node_id = hover_node_id;
 
         // Update the cursor
-        let cursor = self.get_cursor().unwrap_or_default();
-        self.shell_provider.set_cursor(cursor);
+        self.update_cursor();
 
         // Request redraw
         self.shell_provider.request_redraw();
@@ -936,6 +1371,46 @@ impl BaseDocument {
         self.hover_node_id
     }
 
+    /// The `title` text of a node or its nearest ancestor with a `title` attribute.
+    /// An empty `title` suppresses the tooltips of its ancestors.
+    fn title_text(&self, node_id: usize) -> Option<String> {
+        let mut node = self.get_node(node_id);
+        while let Some(current) = node {
+            if let Some(title) = current.attr(local_name!("title")) {
+                return (!title.is_empty()).then(|| title.to_string());
+            }
+            node = current.parent.and_then(|id| self.get_node(id));
+        }
+        None
+    }
+
+    /// Show the pending `title` tooltip if the pointer has rested for long enough
+    pub(crate) fn update_tooltip(&mut self, current_time: f64) {
+        let Some(pending) = &mut self.pending_tooltip else {
+            return;
+        };
+        let hover_start = *pending.hover_start.get_or_insert(current_time);
+        if current_time - hover_start < TOOLTIP_DELAY {
+            return;
+        }
+
+        let pending = self.pending_tooltip.take().unwrap();
+        let zoom = self.viewport.zoom();
+        let x = pending.x - self.viewport_scroll.x as f32 / zoom;
+        let y = pending.y - self.viewport_scroll.y as f32 / zoom;
+        self.shell_provider.show_tooltip(pending.text, x, y);
+        self.tooltip_visible = true;
+    }
+
+    /// Hide the current tooltip (if any) and cancel any pending tooltip
+    pub fn hide_tooltip(&mut self) {
+        self.pending_tooltip = None;
+        if self.tooltip_visible {
+            self.tooltip_visible = false;
+            self.shell_provider.hide_tooltip();
+        }
+    }
+
     pub fn set_viewport(&mut self, viewport: Viewport) {
         let scale_has_changed = viewport.scale_f64() != self.viewport.scale_f64();
         self.viewport = viewport;
@@ -977,12 +1452,32 @@ impl BaseDocument {
         &mut self.devtool_settings
     }
 
+    /// The nodes which were restyled or relaid out during the most recent resolve.
+    ///
+    /// This is only tracked when [`DevtoolSettings::paint_flash`] is enabled (and requires the
+    /// `incremental` feature), otherwise it will be empty.
+    pub fn repainted_nodes(&self) -> &[usize] {
+        &self.repainted_nodes
+    }
+
     pub fn is_animating(&self) -> bool {
-        self.has_canvas | self.has_active_animations
+        // A pending tooltip needs the document to keep ticking until it is shown
+        self.has_canvas
+            | self.has_active_animations
+            | self.pending_tooltip.is_some()
+            | !self.smooth_scrolls.is_empty()
     }
 
     /// Update the device and reset the stylist to process the new size
+    ///
+    /// Stylesheets whose `@media` queries are affected by the change are invalidated, and if the
+    /// viewport size or resolution has changed the whole document is recascaded (so that styles
+    /// using viewport-relative units are recomputed).
     pub fn set_stylist_device(&mut self, device: Device) {
+        let old_device = self.stylist.device();
+        let viewport_has_changed = old_device.au_viewport_size() != device.au_viewport_size()
+            || old_device.device_pixel_ratio() != device.device_pixel_ratio();
+
         let origins = {
             let guard = &self.guard;
             let guards = StylesheetGuards {
@@ -992,12 +1487,86 @@ impl BaseDocument {
             self.stylist.set_device(device, &guards)
         };
         self.stylist.force_stylesheet_origins_dirty(origins);
+
+        if viewport_has_changed {
+            if let Some(root) = self.try_root_element() {
+                root.set_restyle_hint(RestyleHint::recascade_subtree());
+            }
+        }
     }
 
     pub fn stylist_device(&mut self) -> &Device {
         self.stylist.device()
     }
 
+    /// Set the shell's cursor to match the hovered node, preferring a custom cursor image if it has one
+    fn update_cursor(&mut self) {
+        if let Some(cursor) = self.get_custom_cursor() {
+            if self.shell_provider.set_custom_cursor(cursor) {
+                return;
+            }
+        }
+        let cursor = self.get_cursor().unwrap_or_default();
+        self.shell_provider.set_cursor(cursor);
+    }
+
+    /// Resolve the first loaded `url(...)` image in the hovered node's `cursor` property, starting
+    /// to load any images that haven't been requested yet.
+    ///
+    /// Returns `None` while an image is still loading, or if none of the images could be loaded,
+    /// in which case the cursor keyword should be used.
+    fn get_custom_cursor(&mut self) -> Option<CustomCursor> {
+        let node_id = self.get_hover_node_id()?;
+        let cursor = self.nodes[node_id].primary_styles()?.clone_cursor();
+
+        for cursor_image in cursor.images.iter() {
+            let StyloImage::Url(ComputedUrl::Valid(url)) = &cursor_image.image else {
+                continue;
+            };
+
+            let idx = match self.cursor_images.iter().position(|img| img.url == **url) {
+                Some(idx) => idx,
+                None => {
+                    let idx = self.cursor_images.len();
+                    self.cursor_images.push(CursorImageData {
+                        url: (**url).clone(),
+                        status: Status::Loading,
+                        width: 0,
+                        height: 0,
+                        rgba: Arc::new(Vec::new()),
+                    });
+                    self.net_provider.fetch(
+                        self.id,
+                        Request::get((**url).clone()),
+                        Box::new(ImageHandler::new(node_id, ImageType::Cursor(idx))),
+                    );
+                    idx
+                }
+            };
+
+            let image = &self.cursor_images[idx];
+            match image.status {
+                Status::Ok => {
+                    let (hotspot_x, hotspot_y) = match cursor_image.has_hotspot {
+                        true => (cursor_image.hotspot_x, cursor_image.hotspot_y),
+                        false => (0.0, 0.0),
+                    };
+                    return Some(CustomCursor {
+                        rgba: image.rgba.clone(),
+                        width: image.width,
+                        height: image.height,
+                        hotspot_x: hotspot_x.max(0.0) as u32,
+                        hotspot_y: hotspot_y.max(0.0) as u32,
+                    });
+                }
+                Status::Loading => return None,
+                Status::Error => continue,
+            }
+        }
+
+        None
+    }
+
     pub fn get_cursor(&self) -> Option<CursorIcon> {
         // todo: cache this on the node itself
         let node = &self.nodes[self.get_hover_node_id()?];
@@ -1097,9 +1666,15 @@ impl BaseDocument {
         }
 
         let has_changed = node.scroll_offset != initial;
+        let parent = node.parent;
+
+        // Scrolling moves different content beneath the pointer, so any drag selection follows it
+        if has_changed {
+            update_selection_drag(self);
+        }
 
         if bubble_x != 0.0 || bubble_y != 0.0 {
-            if let Some(parent) = node.parent {
+            if let Some(parent) = parent {
                 return self.scroll_node_by_has_changed(parent, bubble_x, bubble_y) | has_changed;
             } else {
                 return self.scroll_viewport_by_has_changed(bubble_x, bubble_y) | has_changed;
@@ -1130,7 +1705,11 @@ impl BaseDocument {
             f64::min(new_scroll.1, content_size.height as f64 - window_height),
         );
 
-        self.viewport_scroll != initial
+        let has_changed = self.viewport_scroll != initial;
+        if has_changed {
+            update_selection_drag(self);
+        }
+        has_changed
     }
 
     pub fn viewport_scroll(&self) -> crate::Point<f64> {
@@ -1177,3 +1756,435 @@ impl AsMut<BaseDocument> for BaseDocument {
         self
     }
 }
+
+#[cfg(test)]
+use crate::test_utils::doc_with_body;
+
+#[test]
+fn hovering_title_shows_tooltip_after_delay() {
+    use crate::qual_name;
+
+    #[derive(Default)]
+    struct TooltipRecorder(Mutex<Vec<String>>);
+    impl ShellProvider for TooltipRecorder {
+        fn show_tooltip(&self, text: String, _x: f32, _y: f32) {
+            self.0.lock().unwrap().push(text);
+        }
+    }
+
+    let (mut doc, ()) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(div, qual_name!("title"), "Tooltip text");
+        mutr.set_attribute(div, qual_name!("style"), "width: 100px; height: 100px");
+        mutr.append_children(body, &[div]);
+    });
+    let shell = Arc::new(TooltipRecorder::default());
+    doc.set_shell_provider(shell.clone());
+
+    doc.set_hover_to(10.0, 10.0);
+    doc.resolve(1.0);
+    assert!(shell.0.lock().unwrap().is_empty());
+    assert!(doc.is_animating());
+
+    doc.resolve(1.0 + TOOLTIP_DELAY);
+    assert_eq!(*shell.0.lock().unwrap(), vec!["Tooltip text".to_string()]);
+    assert!(!doc.is_animating());
+}
+
+#[test]
+fn registered_font_faces_resolve_by_family_name() {
+    let mut doc = BaseDocument::new(DocumentConfig::default());
+    doc.register_font_face("Embedded Bullets", Bytes::from_static(crate::BULLET_FONT));
+
+    let mut font_ctx = doc.font_ctx.lock().unwrap();
+    assert!(
+        font_ctx
+            .collection
+            .family_by_name("Embedded Bullets")
+            .is_some()
+    );
+}
+
+#[test]
+fn elements_from_point_returns_overlapping_elements_topmost_first() {
+    use crate::qual_name;
+
+    let css = "html { height: 300px } body { margin: 0; height: 100% }";
+    let (doc, [body, back, front]) = doc_with_body(800, 600, css, |mutr, body| {
+        let back = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            back,
+            qual_name!("style"),
+            "position: absolute; left: 0; top: 0; width: 100px; height: 100px",
+        );
+        let front = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            front,
+            qual_name!("style"),
+            "position: absolute; left: 50px; top: 50px; width: 100px; height: 100px",
+        );
+        mutr.append_children(body, &[back, front]);
+        [body, back, front]
+    });
+    let html = doc.root_element().id;
+
+    assert_eq!(
+        doc.elements_from_point(75.0, 75.0),
+        vec![front, back, body, html]
+    );
+    assert_eq!(doc.elements_from_point(10.0, 10.0), vec![back, body, html]);
+    assert_eq!(doc.elements_from_point(200.0, 200.0), vec![body, html]);
+    assert_eq!(doc.hit(75.0, 75.0).map(|hit| hit.node_id), Some(front));
+}
+
+#[test]
+fn hit_testing_accounts_for_transforms() {
+    use crate::qual_name;
+
+    let css = "body { margin: 0 }";
+    let (doc, [button, rotated]) = doc_with_body(800, 600, css, |mutr, body| {
+        let button = mutr.create_element(qual_name!("button", html), vec![]);
+        mutr.set_attribute(
+            button,
+            qual_name!("style"),
+            "display: block; width: 50px; height: 20px; margin: 0; transform: translate(100px, 0)",
+        );
+        let rotated = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            rotated,
+            qual_name!("style"),
+            "width: 100px; height: 20px; margin-top: 80px; transform: rotate(90deg)",
+        );
+        mutr.append_children(body, &[button, rotated]);
+        [button, rotated]
+    });
+
+    // The button is hit where it is painted rather than where it is laid out
+    assert_eq!(doc.hit(120.0, 10.0).map(|hit| hit.node_id), Some(button));
+    assert_ne!(doc.hit(20.0, 10.0).map(|hit| hit.node_id), Some(button));
+
+    // The rotated box (laid out at y = 100..120) stands upright around its center (50, 110)
+    assert_eq!(doc.hit(50.0, 70.0).map(|hit| hit.node_id), Some(rotated));
+    assert_ne!(doc.hit(90.0, 110.0).map(|hit| hit.node_id), Some(rotated));
+}
+
+#[test]
+fn hit_testing_applies_parent_perspective() {
+    use crate::qual_name;
+
+    let css = "body { margin: 0 } \
+               div { width: 100px; height: 100px } \
+               #container { margin: 100px; perspective: 100px } \
+               #child { transform: translateZ(-100px) }";
+    let (doc, [container, child]) = doc_with_body(800, 600, css, |mutr, body| {
+        let container = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(container, qual_name!("id"), "container");
+        let child = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(child, qual_name!("id"), "child");
+        mutr.append_children(container, &[child]);
+        mutr.append_children(body, &[container]);
+        [container, child]
+    });
+
+    // Moving away from the viewer halves the child's size around its center (as it is painted), so
+    // it only covers 125..175
+    assert_eq!(doc.hit(130.0, 130.0).map(|hit| hit.node_id), Some(child));
+    assert_eq!(doc.hit(170.0, 170.0).map(|hit| hit.node_id), Some(child));
+    assert_eq!(
+        doc.hit(110.0, 110.0).map(|hit| hit.node_id),
+        Some(container)
+    );
+}
+
+#[test]
+fn hit_testing_applies_clip_path_without_painting() {
+    use crate::qual_name;
+
+    let css = "body { margin: 0 } \
+               div { width: 100px; height: 100px; clip-path: circle(50%) }";
+    let (doc, [body, div]) = doc_with_body(800, 600, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(body, &[div]);
+        [body, div]
+    });
+
+    // The clip path is resolved on demand, so the document doesn't need to have been painted
+    assert_eq!(doc.hit(50.0, 50.0).map(|hit| hit.node_id), Some(div));
+    assert_eq!(doc.hit(5.0, 5.0).map(|hit| hit.node_id), Some(body));
+    let node = doc.get_node(div).unwrap();
+    assert!(node.clip_path_cache.is_valid_for(node));
+}
+
+#[test]
+fn inert_subtrees_cannot_be_clicked_or_focussed() {
+    use crate::qual_name;
+    use blitz_traits::events::{BlitzMouseButtonEvent, MouseEventButton, MouseEventButtons};
+    use keyboard_types::Modifiers;
+
+    let css = "body { margin: 0 }";
+    let (mut doc, [body, inert_input, input]) = doc_with_body(800, 600, css, |mutr, body| {
+        let inert = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(inert, qual_name!("inert"), "");
+        let inert_input = mutr.create_element(qual_name!("input", html), vec![]);
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        for id in [inert_input, input] {
+            let style = "display: block; margin: 0; width: 100px; height: 20px";
+            mutr.set_attribute(id, qual_name!("style"), style);
+        }
+        mutr.append_children(inert, &[inert_input]);
+        mutr.append_children(body, &[inert, input]);
+        [body, inert_input, input]
+    });
+
+    // The inert input is still laid out (and so painted), but positions over it hit the body behind it
+    assert_eq!(doc.nodes[inert_input].final_layout.size.height, 20.0);
+    assert!(doc.nodes[inert_input].is_inert());
+    assert_eq!(doc.hit(10.0, 10.0).map(|hit| hit.node_id), Some(body));
+    assert_eq!(doc.hit(10.0, 30.0).map(|hit| hit.node_id), Some(input));
+
+    // Clicking the inert input doesn't focus it
+    let mouse_event = |buttons| BlitzMouseButtonEvent {
+        x: 10.0,
+        y: 10.0,
+        mods: Modifiers::empty(),
+        button: MouseEventButton::Main,
+        buttons,
+    };
+    let mut driver = EventDriver::new(doc.mutate(), NoopEventHandler);
+    driver.handle_ui_event(UiEvent::MouseDown(mouse_event(MouseEventButtons::Primary)));
+    driver.handle_ui_event(UiEvent::MouseUp(mouse_event(MouseEventButtons::None)));
+    drop(driver);
+    assert_eq!(doc.get_focussed_node_id(), None);
+
+    // Nor can it be focussed programmatically or with the keyboard
+    assert!(!doc.focus(inert_input));
+    assert_eq!(doc.focus_next_node(), Some(input));
+    assert_eq!(doc.focus_next_node(), Some(input));
+    assert_eq!(doc.focus_prev_node(), Some(input));
+}
+
+#[test]
+fn positioned_descendants_of_inert_elements_cannot_be_hit() {
+    use crate::qual_name;
+
+    let css = "body { margin: 0; height: 300px } \
+               #positioned { position: absolute; top: 100px; width: 100px; height: 100px }";
+    let (doc, [body, positioned]) = doc_with_body(800, 600, css, |mutr, body| {
+        let inert = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(inert, qual_name!("inert"), "");
+        let positioned = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(positioned, qual_name!("id"), "positioned");
+        mutr.append_children(inert, &[positioned]);
+        mutr.append_children(body, &[inert]);
+        [body, positioned]
+    });
+
+    // The positioned element is hoisted into the root stacking context (rather than being visited
+    // through its inert parent), but it is still inert
+    assert!(doc.nodes[positioned].is_inert());
+    assert_eq!(doc.nodes[positioned].final_layout.size.height, 100.0);
+    assert_eq!(doc.hit(50.0, 150.0).map(|hit| hit.node_id), Some(body));
+}
+
+#[test]
+fn hovering_custom_cursor_requests_cursor_image() {
+    use crate::qual_name;
+
+    #[derive(Default)]
+    struct CursorRecorder(Mutex<Vec<CustomCursor>>);
+    impl ShellProvider for CursorRecorder {
+        fn set_custom_cursor(&self, cursor: CustomCursor) -> bool {
+            self.0.lock().unwrap().push(cursor);
+            true
+        }
+    }
+
+    let (mut doc, div) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 100px; height: 100px; cursor: url(https://example.com/cursor.png) 3 4, pointer",
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+    let shell = Arc::new(CursorRecorder::default());
+    doc.set_shell_provider(shell.clone());
+
+    // The keyword is used while the image loads
+    doc.set_hover_to(10.0, 10.0);
+    assert!(shell.0.lock().unwrap().is_empty());
+    assert_eq!(doc.cursor_images.len(), 1);
+
+    let rgba = Arc::new(vec![255; 8 * 8 * 4]);
+    doc.load_resource(Resource::Image(div, ImageType::Cursor(0), 8, 8, rgba));
+    let cursors = shell.0.lock().unwrap();
+    assert_eq!(cursors.len(), 1);
+    assert_eq!((cursors[0].width, cursors[0].height), (8, 8));
+    assert_eq!((cursors[0].hotspot_x, cursors[0].hotspot_y), (3, 4));
+}
+
+#[test]
+fn tab_order_includes_links_and_buttons() {
+    use crate::qual_name;
+
+    let (mut doc, [link, button, input]) = doc_with_body(800, 600, "", |mutr, body| {
+        let link = mutr.create_element(qual_name!("a", html), vec![]);
+        mutr.set_attribute(link, qual_name!("href"), "#top");
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        let button = mutr.create_element(qual_name!("button", html), vec![]);
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.append_children(body, &[link, div, button, input]);
+        [link, button, input]
+    });
+
+    assert_eq!(doc.focus_next_node(), Some(link));
+    assert_eq!(doc.focus_next_node(), Some(button));
+    assert_eq!(doc.focus_next_node(), Some(input));
+    assert_eq!(doc.focus_next_node(), Some(link));
+    assert_eq!(doc.focus_prev_node(), Some(input));
+    assert_eq!(doc.focus_prev_node(), Some(button));
+}
+
+#[test]
+fn hover_applies_to_dom_ancestors() {
+    use crate::qual_name;
+    use style_dom::ElementState;
+
+    // The inline-block's layout parent is the div, but `:hover` must also apply to the span
+    let css = "body { margin: 0 }";
+    let (mut doc, [body, div, span, inner]) = doc_with_body(800, 600, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        let span = mutr.create_element(qual_name!("span", html), vec![]);
+        let inner = mutr.create_element(qual_name!("span", html), vec![]);
+        mutr.set_attribute(
+            inner,
+            qual_name!("style"),
+            "display: inline-block; width: 50px; height: 50px",
+        );
+        mutr.append_children(span, &[inner]);
+        mutr.append_children(div, &[span]);
+        mutr.append_children(body, &[div]);
+        [body, div, span, inner]
+    });
+
+    let is_hovered =
+        |doc: &BaseDocument, id: usize| doc.nodes[id].element_state.contains(ElementState::HOVER);
+
+    doc.set_hover_to(10.0, 10.0);
+    assert_eq!(doc.get_hover_node_id(), Some(inner));
+    for id in [inner, span, div, body] {
+        assert!(is_hovered(&doc, id), "node {id} should be hovered");
+    }
+
+    doc.set_hover_to(700.0, 500.0);
+    for id in [inner, span, div] {
+        assert!(!is_hovered(&doc, id), "node {id} should not be hovered");
+    }
+}
+
+#[test]
+fn resizing_viewport_reevaluates_media_queries() {
+    use crate::qual_name;
+
+    let css = "div { width: 100px; height: 10px } \
+               @media (max-width: 600px) { div { width: 50px } }";
+    let (mut doc, div) = doc_with_body(800, 600, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(body, &[div]);
+        div
+    });
+    assert_eq!(doc.nodes[div].final_layout.size.width, 100.0);
+
+    // Shrinking the viewport past the breakpoint applies the rule
+    doc.viewport_mut().window_size = (500, 600);
+    doc.resolve(0.0);
+    assert_eq!(doc.nodes[div].final_layout.size.width, 50.0);
+
+    // Growing it again removes it
+    doc.set_viewport(Viewport::new(800, 600, 1.0, ColorScheme::Light));
+    doc.resolve(0.0);
+    assert_eq!(doc.nodes[div].final_layout.size.width, 100.0);
+}
+
+#[test]
+fn focus_and_blur_fire_events() {
+    use crate::qual_name;
+    use style_dom::ElementState;
+
+    let (mut doc, [form, input, div]) = doc_with_body(800, 600, "", |mutr, body| {
+        let form = mutr.create_element(qual_name!("form", html), vec![]);
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(form, &[input]);
+        mutr.append_children(body, &[form, div]);
+        [form, input, div]
+    });
+    let html = doc.root_element().id;
+
+    let event_names = |doc: &mut BaseDocument| -> Vec<(&'static str, usize)> {
+        doc.take_pending_events()
+            .iter()
+            .map(|event| (event.name(), event.target))
+            .collect()
+    };
+    let has_state =
+        |doc: &BaseDocument, id: usize, state| doc.nodes[id].element_state.contains(state);
+
+    // Focussing a div (which isn't focussable) does nothing
+    assert!(!doc.focus(div));
+    assert_eq!(doc.get_focussed_node_id(), Some(html));
+    assert!(event_names(&mut doc).is_empty());
+
+    // Focussing an input fires a focus event and applies :focus, :focus-within and :focus-visible
+    assert!(doc.focus(input));
+    assert_eq!(doc.get_focussed_node_id(), Some(input));
+    assert_eq!(doc.focus_origin(), Some(FocusOrigin::Programmatic));
+    assert_eq!(event_names(&mut doc), [("focus", input)]);
+    assert!(has_state(&doc, input, ElementState::FOCUS));
+    assert!(has_state(&doc, input, ElementState::FOCUSRING));
+    for id in [input, form, html] {
+        assert!(has_state(&doc, id, ElementState::FOCUS_WITHIN));
+    }
+
+    // Blurring fires a blur event and removes the focus states
+    doc.blur();
+    assert_eq!(doc.focus_origin(), None);
+    assert_eq!(event_names(&mut doc), [("blur", input)]);
+    assert!(!has_state(&doc, input, ElementState::FOCUS));
+    for id in [input, form, html] {
+        assert!(!has_state(&doc, id, ElementState::FOCUS_WITHIN));
+    }
+}
+
+#[test]
+fn negative_tabindex_is_skipped_by_tab_but_focussable() {
+    use crate::qual_name;
+    use keyboard_types::Modifiers;
+
+    let (mut doc, [first, div, last]) = doc_with_body(800, 600, "", |mutr, body| {
+        let first = mutr.create_element(qual_name!("button", html), vec![]);
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(div, qual_name!("tabindex", html), "-1");
+        let last = mutr.create_element(qual_name!("button", html), vec![]);
+        mutr.append_children(body, &[first, div, last]);
+        [first, div, last]
+    });
+
+    // Tab and Shift+Tab skip the div
+    assert_eq!(doc.focus_next_node(), Some(first));
+    assert_eq!(doc.focus_next_node(), Some(last));
+    assert_eq!(doc.focus_prev_node(), Some(first));
+
+    // But it can be focussed programmatically
+    assert!(doc.focus(div));
+    assert_eq!(doc.get_focussed_node_id(), Some(div));
+
+    // And by clicking it
+    doc.focus(first);
+    let click = doc.nodes[div].synthetic_click_event_data(Modifiers::empty());
+    crate::events::handle_click(&mut doc, div, &click, |_| {});
+    assert_eq!(doc.get_focussed_node_id(), Some(div));
+    assert_eq!(doc.focus_origin(), Some(FocusOrigin::Pointer));
+}
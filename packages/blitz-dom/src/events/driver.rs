@@ -59,6 +59,7 @@ impl<'doc, Handler: EventHandler> EventDriver<'doc, Handler> {
                 hover_node_id = self.doc().hover_node_id;
             }
             UiEvent::MouseDown(_) => {
+                self.doc_mut().hide_tooltip();
                 self.doc_mut().active_node();
                 self.doc_mut().set_mousedown_node_id(hover_node_id);
             }
@@ -106,8 +107,19 @@ impl<'doc, Handler: EventHandler> EventDriver<'doc, Handler> {
 
     pub fn handle_dom_event(&mut self, event: DomEvent) {
         let mut queue = VecDeque::with_capacity(4);
+        queue.extend(self.doc_mut().take_pending_events());
         queue.push_back(event);
+        self.dispatch_queue(queue);
+    }
+
+    /// Dispatch the events fired by the document itself which haven't been dispatched yet
+    /// (e.g. the `focus` and `blur` events fired by [`BaseDocument::focus`])
+    pub fn dispatch_pending_events(&mut self) {
+        let queue = VecDeque::from(self.doc_mut().take_pending_events());
+        self.dispatch_queue(queue);
+    }
 
+    fn dispatch_queue(&mut self, mut queue: VecDeque<DomEvent>) {
         while let Some(mut event) = queue.pop_front() {
             let chain = if event.bubbles {
                 self.doc().node_chain(event.target)
@@ -115,6 +127,10 @@ impl<'doc, Handler: EventHandler> EventDriver<'doc, Handler> {
                 vec![event.target]
             };
 
+            for &node_id in &chain {
+                self.doc_mut().trace_event(&event, node_id);
+            }
+
             let mut event_state = EventState::default();
             self.handler
                 .handle_event(&chain, &mut event, &mut self.mutr, &mut event_state);
@@ -123,6 +139,71 @@ impl<'doc, Handler: EventHandler> EventDriver<'doc, Handler> {
                 self.doc_mut()
                     .handle_dom_event(&mut event, |new_evt| queue.push_back(new_evt));
             }
+
+            queue.extend(self.doc_mut().take_pending_events());
         }
     }
 }
+
+#[test]
+fn event_trace_hook_records_checkbox_click() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::events::{EventPhase, MouseEventButton, MouseEventButtons};
+    use keyboard_types::Modifiers;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let (mut doc, checkbox) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let checkbox = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(checkbox, qual_name!("type"), "checkbox");
+        mutr.set_attribute(
+            checkbox,
+            qual_name!("style"),
+            "display: block; margin: 0; width: 20px; height: 20px",
+        );
+        mutr.append_children(body, &[checkbox]);
+        checkbox
+    });
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let recorded = events.clone();
+    doc.set_event_trace_hook(move |event, current_target, phase| {
+        // Focus isn't dispatched in the same order as in browsers (where it follows `mousedown`), so
+        // it is left out of the trace
+        if phase == EventPhase::AtTarget && event.name() != "focus" {
+            recorded.borrow_mut().push((event.name(), current_target));
+        }
+    });
+
+    let mouse_event = |buttons| BlitzMouseButtonEvent {
+        x: 10.0,
+        y: 10.0,
+        mods: Modifiers::empty(),
+        button: MouseEventButton::Main,
+        buttons,
+    };
+    let mut driver = EventDriver::new(doc.mutate(), NoopEventHandler);
+    driver.handle_ui_event(UiEvent::MouseMove(mouse_event(MouseEventButtons::None)));
+    driver.handle_ui_event(UiEvent::MouseDown(mouse_event(MouseEventButtons::Primary)));
+    driver.handle_ui_event(UiEvent::MouseUp(mouse_event(MouseEventButtons::None)));
+    drop(driver);
+
+    assert_eq!(
+        *events.borrow(),
+        [
+            ("mousemove", checkbox),
+            ("mousedown", checkbox),
+            ("mouseup", checkbox),
+            ("click", checkbox),
+            ("input", checkbox),
+        ]
+    );
+    assert_eq!(doc.get_focussed_node_id(), Some(checkbox));
+    assert_eq!(
+        doc.nodes[checkbox]
+            .element_data()
+            .and_then(|el| el.checkbox_input_checked()),
+        Some(true)
+    );
+}
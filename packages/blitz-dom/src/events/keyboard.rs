@@ -23,15 +23,43 @@ pub(crate) fn handle_keypress<F: FnMut(DomEvent)>(
     mut dispatch_event: F,
 ) {
     if event.key == Key::Tab {
-        doc.focus_next_node();
+        if event.modifiers.shift() {
+            doc.focus_prev_node();
+        } else {
+            doc.focus_next_node();
+        }
         return;
     }
 
+    // Escape cancels the topmost modal dialog (unless it is dismissing a datalist popup). The default
+    // action of the `cancel` event closes the dialog.
+    if event.key == Key::Escape && doc.datalist_popup.is_none() {
+        if let Some(dialog_id) = doc.topmost_modal_dialog() {
+            dispatch_event(DomEvent::new(dialog_id, DomEventData::Cancel));
+            return;
+        }
+    }
+
     if let Some(node_id) = doc.focus_node_id {
         if target != node_id {
             return;
         }
 
+        if handle_datalist_keypress(doc, node_id, &event, &mut dispatch_event) {
+            return;
+        }
+
+        // Arrow keys step the value of date inputs
+        if event.state.is_pressed() && matches!(event.key, Key::ArrowUp | Key::ArrowDown) {
+            if let Some(value) = doc.step_date_input(node_id, event.key == Key::ArrowUp) {
+                dispatch_event(DomEvent::new(
+                    node_id,
+                    DomEventData::Input(BlitzInputEvent { value }),
+                ));
+                return;
+            }
+        }
+
         let node = &mut doc.nodes[node_id];
         let Some(element_data) = node.element_data_mut() else {
             return;
@@ -54,6 +82,8 @@ pub(crate) fn handle_keypress<F: FnMut(DomEvent)>(
                             node_id,
                             DomEventData::Input(BlitzInputEvent { value }),
                         ));
+                        doc.update_datalist_popup();
+                        doc.snapshot_node_and(node_id, |node| node.update_validity());
                     }
                     GeneratedEvent::Submit => {
                         // TODO: Generate submit event that can be handled by script
@@ -65,6 +95,47 @@ pub(crate) fn handle_keypress<F: FnMut(DomEvent)>(
     }
 }
 
+/// Navigate, accept, or dismiss the datalist suggestion popup for the focussed input.
+/// Returns `true` if the key was consumed by the popup.
+fn handle_datalist_keypress<F: FnMut(DomEvent)>(
+    doc: &mut BaseDocument,
+    node_id: usize,
+    event: &BlitzKeyEvent,
+    dispatch_event: &mut F,
+) -> bool {
+    if !event.state.is_pressed() {
+        return false;
+    }
+    let Some(popup) = doc.datalist_popup.as_mut() else {
+        return false;
+    };
+    if popup.input_id != node_id {
+        return false;
+    }
+
+    match event.key {
+        Key::ArrowDown | Key::ArrowUp => {
+            popup.move_selection(event.key == Key::ArrowDown);
+            doc.shell_provider.request_redraw();
+        }
+        Key::Enter => {
+            let Some(selected) = popup.selected else {
+                return false;
+            };
+            if let Some((input_id, value)) = doc.accept_datalist_option(selected) {
+                dispatch_event(DomEvent::new(
+                    input_id,
+                    DomEventData::Input(BlitzInputEvent { value }),
+                ));
+            }
+        }
+        Key::Escape => doc.close_datalist_popup(),
+        _ => return false,
+    }
+
+    true
+}
+
 #[cfg(target_os = "macos")]
 const ACTION_MOD: Modifiers = Modifiers::SUPER;
 #[cfg(not(target_os = "macos"))]
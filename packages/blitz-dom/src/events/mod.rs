@@ -8,7 +8,10 @@ pub use driver::{EventDriver, EventHandler, NoopEventHandler};
 pub(crate) use ime::handle_ime_event;
 pub(crate) use keyboard::handle_keypress;
 use mouse::handle_mouseup;
-pub(crate) use mouse::{handle_click, handle_mousedown, handle_mousemove};
+pub(crate) use mouse::{
+    ResizeDrag, SelectionDrag, handle_click, handle_mousedown, handle_mousemove,
+    update_selection_drag,
+};
 
 use crate::BaseDocument;
 
@@ -56,5 +59,11 @@ pub(crate) fn handle_dom_event<F: FnMut(DomEvent)>(
         DomEventData::Input(_) => {
             // Do nothing (no default action)
         }
+        DomEventData::Focus(_) | DomEventData::Blur(_) | DomEventData::Close => {
+            // Do nothing (no default action)
+        }
+        DomEventData::Cancel => {
+            doc.close_dialog(target_node_id);
+        }
     }
 }
@@ -1,66 +1,157 @@
 use blitz_traits::{
     events::{
-        BlitzInputEvent, BlitzMouseButtonEvent, DomEvent, DomEventData, MouseEventButton,
-        MouseEventButtons,
+        BlitzInputEvent, BlitzMouseButtonEvent, DomEvent, DomEventData, FocusOrigin,
+        MouseEventButton, MouseEventButtons,
     },
     navigation::NavigationOptions,
 };
 use markup5ever::local_name;
+use style::properties::generated::longhands::box_sizing::computed_value::T as BoxSizing;
+use url::Position;
 
 use crate::{BaseDocument, node::SpecialElementData};
 
-pub(crate) fn handle_mousemove(
-    doc: &mut BaseDocument,
-    target: usize,
-    x: f32,
-    y: f32,
-    buttons: MouseEventButtons,
-) -> bool {
-    let mut changed = doc.set_hover_to(x, y);
+/// An in-progress drag of an element's resize grip
+pub(crate) struct ResizeDrag {
+    node_id: usize,
+    /// The position of the pointer when the drag started
+    start_x: f32,
+    start_y: f32,
+    /// The used `width` and `height` of the element when the drag started
+    start_width: f32,
+    start_height: f32,
+}
 
-    let Some(hit) = doc.hit(x, y) else {
-        return changed;
+/// An in-progress drag which is selecting the text of a text input
+pub(crate) struct SelectionDrag {
+    node_id: usize,
+    /// The position of the pointer relative to the viewport. Scrolling while dragging moves the
+    /// pointer over different content, which extends the selection.
+    client_x: f32,
+    client_y: f32,
+}
+
+impl SelectionDrag {
+    fn new(doc: &BaseDocument, node_id: usize, x: f32, y: f32) -> Self {
+        let scroll = doc.viewport_scroll();
+        let zoom = doc.viewport.zoom();
+        SelectionDrag {
+            node_id,
+            client_x: x - scroll.x as f32 / zoom,
+            client_y: y - scroll.y as f32 / zoom,
+        }
+    }
+}
+
+/// Extend the selection of the text input being dragged over to the content now under the pointer.
+/// This is called when the document is scrolled, as the pointer's position in the document changes
+/// without the pointer moving.
+pub(crate) fn update_selection_drag(doc: &mut BaseDocument) {
+    let Some(drag) = &doc.selection_drag else {
+        return;
     };
+    let scroll = doc.viewport_scroll();
+    let zoom = doc.viewport.zoom();
+    let node_id = drag.node_id;
+    let x = drag.client_x + scroll.x as f32 / zoom;
+    let y = drag.client_y + scroll.y as f32 / zoom;
+    extend_text_selection(doc, node_id, x, y);
+}
 
+/// Extend the selection of a text input to the (document) position `x`, `y`. Returns whether the
+/// selection was extended (which requires the position to be over the text input).
+fn extend_text_selection(doc: &mut BaseDocument, target: usize, x: f32, y: f32) -> bool {
+    let Some(hit) = doc.hit(x, y) else {
+        return false;
+    };
     if hit.node_id != target {
-        return changed;
+        return false;
     }
 
     let node = &mut doc.nodes[target];
     let Some(el) = node.data.downcast_element_mut() else {
-        return changed;
+        return false;
     };
-
-    let disabled = el.attr(local_name!("disabled")).is_some();
-    if disabled {
-        return changed;
+    if el.attr(local_name!("disabled")).is_some() {
+        return false;
     }
+    let SpecialElementData::TextInput(ref mut text_input_data) = el.special_data else {
+        return false;
+    };
 
-    if let SpecialElementData::TextInput(ref mut text_input_data) = el.special_data {
+    let content_box_offset = taffy::Point {
+        x: node.final_layout.padding.left + node.final_layout.border.left,
+        y: node.final_layout.padding.top + node.final_layout.border.top,
+    };
+    let x = (hit.x - content_box_offset.x) as f64 * doc.viewport.scale_f64();
+    let y = (hit.y - content_box_offset.y) as f64 * doc.viewport.scale_f64();
+
+    text_input_data
+        .editor
+        .driver(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx)
+        .extend_selection_to_point(x as f32, y as f32);
+    true
+}
+
+/// Resize the element being dragged so that its bottom-right corner follows the pointer.
+///
+/// The new size is applied as inline `width` and `height` styles, so `min-*` and `max-*`
+/// constraints are still respected by layout.
+fn update_resize_drag(doc: &mut BaseDocument, x: f32, y: f32) {
+    let Some(drag) = &doc.resize_drag else {
+        return;
+    };
+    let node_id = drag.node_id;
+    let width = (drag.start_width + x - drag.start_x).max(0.0);
+    let height = (drag.start_height + y - drag.start_y).max(0.0);
+
+    doc.set_style_property(node_id, "width", &format!("{width}px"));
+    doc.set_style_property(node_id, "height", &format!("{height}px"));
+}
+
+pub(crate) fn handle_mousemove(
+    doc: &mut BaseDocument,
+    target: usize,
+    x: f32,
+    y: f32,
+    buttons: MouseEventButtons,
+) -> bool {
+    if doc.resize_drag.is_some() {
         if buttons == MouseEventButtons::None {
-            return changed;
+            doc.resize_drag = None;
+        } else {
+            update_resize_drag(doc, x, y);
+            return true;
         }
+    }
 
-        let content_box_offset = taffy::Point {
-            x: node.final_layout.padding.left + node.final_layout.border.left,
-            y: node.final_layout.padding.top + node.final_layout.border.top,
-        };
-
-        let x = (hit.x - content_box_offset.x) as f64 * doc.viewport.scale_f64();
-        let y = (hit.y - content_box_offset.y) as f64 * doc.viewport.scale_f64();
+    let changed = doc.set_hover_to(x, y);
 
-        text_input_data
-            .editor
-            .driver(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx)
-            .extend_selection_to_point(x as f32, y as f32);
+    if buttons == MouseEventButtons::None {
+        doc.selection_drag = None;
+        return changed;
+    }
 
-        changed = true;
+    // Keep track of the pointer so that scrolling can extend the selection
+    if let Some(node_id) = doc.selection_drag.as_ref().map(|drag| drag.node_id) {
+        doc.selection_drag = Some(SelectionDrag::new(doc, node_id, x, y));
     }
 
-    changed
+    extend_text_selection(doc, target, x, y) || changed
 }
 
 pub(crate) fn handle_mousedown(doc: &mut BaseDocument, target: usize, x: f32, y: f32) {
+    // Presses on the datalist popup are handled on mouseup. Presses elsewhere (other than on the
+    // popup's input) dismiss it.
+    if let Some(popup) = &doc.datalist_popup {
+        if popup.option_at(doc, x, y).is_some() {
+            return;
+        }
+        if popup.input_id != target {
+            doc.close_datalist_popup();
+        }
+    }
+
     let Some(hit) = doc.hit(x, y) else {
         return;
     };
@@ -68,6 +159,29 @@ pub(crate) fn handle_mousedown(doc: &mut BaseDocument, target: usize, x: f32, y:
         return;
     }
 
+    // Start resizing the element if the resize grip was pressed
+    let node = &doc.nodes[target];
+    if node.is_within_resize_grip(hit.x, hit.y) {
+        let layout = node.final_layout;
+        let mut start_size = layout.size;
+        let is_content_box = node
+            .primary_styles()
+            .is_some_and(|style| style.clone_box_sizing() == BoxSizing::ContentBox);
+        if is_content_box {
+            start_size = start_size - (layout.padding + layout.border).sum_axes();
+        }
+
+        doc.resize_drag = Some(ResizeDrag {
+            node_id: target,
+            start_x: x,
+            start_y: y,
+            start_width: start_size.width,
+            start_height: start_size.height,
+        });
+        return;
+    }
+
+    let selection_drag = SelectionDrag::new(doc, target, x, y);
     let node = &mut doc.nodes[target];
     let Some(el) = node.data.downcast_element_mut() else {
         return;
@@ -91,7 +205,8 @@ pub(crate) fn handle_mousedown(doc: &mut BaseDocument, target: usize, x: f32, y:
             .driver(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx)
             .move_to_point(x as f32, y as f32);
 
-        doc.set_focus_to(hit.node_id);
+        doc.selection_drag = Some(selection_drag);
+        doc.set_focus_with_origin(hit.node_id, FocusOrigin::Pointer);
     }
 }
 
@@ -101,6 +216,28 @@ pub(crate) fn handle_mouseup<F: FnMut(DomEvent)>(
     event: &BlitzMouseButtonEvent,
     mut dispatch_event: F,
 ) {
+    doc.selection_drag = None;
+
+    // Releasing the resize grip doesn't click the element
+    if doc.resize_drag.take().is_some() {
+        return;
+    }
+
+    // Releasing on a datalist option accepts it instead of clicking the element beneath it
+    let option_idx = doc
+        .datalist_popup
+        .as_ref()
+        .and_then(|popup| popup.option_at(doc, event.x, event.y));
+    if let Some(option_idx) = option_idx {
+        if let Some((input_id, value)) = doc.accept_datalist_option(option_idx) {
+            dispatch_event(DomEvent::new(
+                input_id,
+                DomEventData::Input(BlitzInputEvent { value }),
+            ));
+        }
+        return;
+    }
+
     if doc.devtools().highlight_hover {
         let mut node = doc.get_node(target).unwrap();
         if event.button == MouseEventButton::Secondary {
@@ -163,7 +300,7 @@ pub(crate) fn handle_click<F: FnMut(DomEvent)>(
                     node_id,
                     DomEventData::Input(BlitzInputEvent { value }),
                 ));
-                doc.set_focus_to(node_id);
+                doc.set_focus_with_origin(node_id, FocusOrigin::Pointer);
                 return;
             }
             local_name!("input") if el.attr(local_name!("type")) == Some("radio") => {
@@ -177,7 +314,7 @@ pub(crate) fn handle_click<F: FnMut(DomEvent)>(
                     DomEventData::Input(BlitzInputEvent { value }),
                 ));
 
-                BaseDocument::set_focus_to(doc, node_id);
+                doc.set_focus_with_origin(node_id, FocusOrigin::Pointer);
 
                 return;
             }
@@ -194,11 +331,45 @@ pub(crate) fn handle_click<F: FnMut(DomEvent)>(
             local_name!("a") => {
                 if let Some(href) = el.attr(local_name!("href")) {
                     if let Some(url) = doc.url.resolve_relative(href) {
-                        doc.navigation_provider.navigate_to(NavigationOptions::new(
-                            url,
-                            String::from("text/plain"),
-                            doc.id(),
-                        ));
+                        if let Some(filename) = el.attr(local_name!("download")) {
+                            let filename = (!filename.is_empty()).then(|| filename.to_string());
+                            doc.navigation_provider.download(url, filename);
+                            return;
+                        }
+
+                        let options = NavigationOptions::new(url, String::new(), doc.id())
+                            .set_rel(el.attr(local_name!("rel")).unwrap_or(""));
+
+                        // There are no nested browsing contexts, so `_parent` and `_top` are
+                        // equivalent to `_self`. Any other target opens a new window.
+                        let target = el
+                            .attr(local_name!("target"))
+                            .or(doc.base_target.as_deref())
+                            .unwrap_or("");
+                        match target {
+                            target
+                                if target.is_empty()
+                                    || target.eq_ignore_ascii_case("_self")
+                                    || target.eq_ignore_ascii_case("_parent")
+                                    || target.eq_ignore_ascii_case("_top") =>
+                            {
+                                // Navigations to a fragment of the current document don't reload it
+                                let same_document = options.url.fragment().is_some()
+                                    && options.url[..Position::AfterQuery]
+                                        == doc.url[..Position::AfterQuery];
+                                if same_document {
+                                    doc.navigate_to_fragment(
+                                        options.url.fragment().unwrap_or_default(),
+                                    );
+                                } else {
+                                    doc.navigation_provider.navigate_to(options);
+                                }
+                            }
+                            target => {
+                                let options = options.set_target(Some(target.to_string()));
+                                doc.shell_provider.open_new_window(options);
+                            }
+                        }
                     } else {
                         println!("{href} is not parseable as a url. : {:?}", *doc.url)
                     }
@@ -252,6 +423,12 @@ pub(crate) fn handle_click<F: FnMut(DomEvent)>(
             _ => {}
         }
 
+        // Clicking any focussable element (including those with a negative tabindex) focusses it
+        if doc.nodes[node_id].is_focussable() {
+            doc.set_focus_with_origin(node_id, FocusOrigin::Pointer);
+            return;
+        }
+
         // No match. Recurse up to parent.
         maybe_node_id = doc.nodes[node_id].parent;
     }
@@ -259,3 +436,270 @@ pub(crate) fn handle_click<F: FnMut(DomEvent)>(
     // If nothing is matched then clear focus
     doc.clear_focus();
 }
+
+#[test]
+fn dragging_resize_grip_grows_textarea() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (mut doc, textarea) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let textarea = mutr.create_element(qual_name!("textarea", html), vec![]);
+        mutr.set_attribute(
+            textarea,
+            qual_name!("style"),
+            "display: block; box-sizing: border-box; width: 100px; height: 50px; max-height: 60px",
+        );
+        mutr.append_children(body, &[textarea]);
+        textarea
+    });
+
+    let node = &doc.nodes[textarea];
+    let origin = node.absolute_position(0.0, 0.0);
+    let x = origin.x + node.final_layout.size.width - 2.0;
+    let y = origin.y + node.final_layout.size.height - 2.0;
+
+    handle_mousedown(&mut doc, textarea, x, y);
+    handle_mousemove(
+        &mut doc,
+        textarea,
+        x + 30.0,
+        y + 20.0,
+        MouseEventButtons::Primary,
+    );
+    doc.resize_drag = None;
+    doc.resolve(0.0);
+
+    // The height is clamped by `max-height`
+    let size = doc.nodes[textarea].final_layout.size;
+    assert_eq!((size.width, size.height), (130.0, 60.0));
+}
+
+#[test]
+fn scrolling_while_drag_selecting_extends_the_selection() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let css = "body { margin: 0 }";
+    let (mut doc, [container, textarea]) = doc_with_body(800, 600, css, |mutr, body| {
+        let container = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            container,
+            qual_name!("style"),
+            "height: 100px; overflow: auto",
+        );
+        let textarea = mutr.create_element(qual_name!("textarea", html), vec![]);
+        mutr.set_attribute(
+            textarea,
+            qual_name!("style"),
+            "display: block; margin: 0; width: 200px; height: 300px; font-size: 10px; line-height: 20px",
+        );
+        let text: Vec<String> = (0..15).map(|line| format!("line {line}")).collect();
+        mutr.set_attribute(textarea, qual_name!("value"), &text.join("\n"));
+        mutr.append_children(container, &[textarea]);
+        mutr.append_children(body, &[container]);
+        [container, textarea]
+    });
+
+    let selected_text = |doc: &BaseDocument| {
+        let el = doc.nodes[textarea].element_data().unwrap();
+        let editor = &el.text_input_data().unwrap().editor;
+        editor.selected_text().unwrap_or_default().to_string()
+    };
+
+    // Select from the first line down to the third
+    handle_mousedown(&mut doc, textarea, 10.0, 10.0);
+    handle_mousemove(&mut doc, textarea, 10.0, 50.0, MouseEventButtons::Primary);
+    let selected = selected_text(&doc);
+    assert!(selected.contains("line 1") && !selected.contains("line 6"));
+
+    // Scrolling moves the pointer over later lines, so the selection grows to follow it
+    doc.scroll_node_by(container, 0.0, -100.0);
+    let selected = selected_text(&doc);
+    assert!(selected.contains("line 6") && !selected.contains("line 8"));
+
+    // Once the button is released scrolling no longer changes the selection
+    handle_mouseup(
+        &mut doc,
+        textarea,
+        &BlitzMouseButtonEvent {
+            x: 10.0,
+            y: 50.0,
+            mods: keyboard_types::Modifiers::empty(),
+            button: MouseEventButton::Main,
+            buttons: MouseEventButtons::None,
+        },
+        |_| {},
+    );
+    doc.scroll_node_by(container, 0.0, -100.0);
+    assert_eq!(selected_text(&doc), selected);
+}
+
+#[test]
+fn clicking_download_link_downloads_instead_of_navigating() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::navigation::NavigationProvider;
+    use keyboard_types::Modifiers;
+    use std::sync::{Arc, Mutex};
+    use url::Url;
+
+    #[derive(Default)]
+    struct NavigationRecorder {
+        navigations: Mutex<Vec<Url>>,
+        downloads: Mutex<Vec<(Url, Option<String>)>>,
+    }
+    impl NavigationProvider for NavigationRecorder {
+        fn navigate_to(&self, options: NavigationOptions) {
+            self.navigations.lock().unwrap().push(options.url);
+        }
+        fn download(&self, url: Url, filename: Option<String>) {
+            self.downloads.lock().unwrap().push((url, filename));
+        }
+    }
+
+    let (mut doc, link) = doc_with_body(800, 600, "", |mutr, body| {
+        let link = mutr.create_element(qual_name!("a", html), vec![]);
+        mutr.set_attribute(link, qual_name!("href"), "https://example.com/data.csv");
+        mutr.set_attribute(link, qual_name!("download"), "report.csv");
+        mutr.append_children(body, &[link]);
+        link
+    });
+    let provider = Arc::new(NavigationRecorder::default());
+    doc.set_navigation_provider(provider.clone());
+
+    let event = doc.nodes[link].synthetic_click_event_data(Modifiers::empty());
+    handle_click(&mut doc, link, &event, |_| {});
+
+    assert!(provider.navigations.lock().unwrap().is_empty());
+    assert_eq!(
+        *provider.downloads.lock().unwrap(),
+        vec![(
+            Url::parse("https://example.com/data.csv").unwrap(),
+            Some("report.csv".to_string())
+        )]
+    );
+}
+
+#[test]
+fn clicking_blank_target_link_opens_new_window() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::navigation::NavigationProvider;
+    use blitz_traits::shell::ShellProvider;
+    use keyboard_types::Modifiers;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct NavigationRecorder(Mutex<Vec<NavigationOptions>>);
+    impl NavigationProvider for NavigationRecorder {
+        fn navigate_to(&self, options: NavigationOptions) {
+            self.0.lock().unwrap().push(options);
+        }
+    }
+    impl ShellProvider for NavigationRecorder {
+        fn open_new_window(&self, options: NavigationOptions) {
+            self.0.lock().unwrap().push(options);
+        }
+    }
+
+    let (mut doc, link) = doc_with_body(800, 600, "", |mutr, body| {
+        let link = mutr.create_element(qual_name!("a", html), vec![]);
+        mutr.set_attribute(link, qual_name!("href"), "https://example.com/");
+        mutr.set_attribute(link, qual_name!("target"), "_blank");
+        mutr.set_attribute(link, qual_name!("rel"), "noreferrer");
+        mutr.append_children(body, &[link]);
+        link
+    });
+    let navigations = Arc::new(NavigationRecorder::default());
+    let windows = Arc::new(NavigationRecorder::default());
+    doc.set_navigation_provider(navigations.clone());
+    doc.set_shell_provider(windows.clone());
+
+    let event = doc.nodes[link].synthetic_click_event_data(Modifiers::empty());
+    handle_click(&mut doc, link, &event, |_| {});
+
+    assert!(navigations.0.lock().unwrap().is_empty());
+    let windows = windows.0.lock().unwrap();
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0].url.as_str(), "https://example.com/");
+    assert_eq!(windows[0].target.as_deref(), Some("_blank"));
+    assert!(windows[0].noopener && windows[0].noreferrer);
+}
+
+#[test]
+fn links_resolve_against_base_href() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::navigation::NavigationProvider;
+    use keyboard_types::Modifiers;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct NavigationRecorder(Mutex<Vec<NavigationOptions>>);
+    impl NavigationProvider for NavigationRecorder {
+        fn navigate_to(&self, options: NavigationOptions) {
+            self.0.lock().unwrap().push(options);
+        }
+    }
+
+    let (mut doc, link) = doc_with_body(800, 600, "", |mutr, body| {
+        let base = mutr.create_element(qual_name!("base", html), vec![]);
+        mutr.set_attribute(base, qual_name!("href"), "/app/");
+        let link = mutr.create_element(qual_name!("a", html), vec![]);
+        mutr.set_attribute(link, qual_name!("href"), "next.html");
+        mutr.append_children(body, &[base, link]);
+        link
+    });
+    doc.set_base_url("https://example.com/docs/page.html");
+    let provider = Arc::new(NavigationRecorder::default());
+    doc.set_navigation_provider(provider.clone());
+
+    let event = doc.nodes[link].synthetic_click_event_data(Modifiers::empty());
+    handle_click(&mut doc, link, &event, |_| {});
+
+    let navigations = provider.0.lock().unwrap();
+    assert_eq!(navigations.len(), 1);
+    assert_eq!(
+        navigations[0].url.as_str(),
+        "https://example.com/app/next.html"
+    );
+}
+
+#[test]
+fn clicking_fragment_link_sets_target() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::navigation::NavigationProvider;
+    use keyboard_types::Modifiers;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct NavigationRecorder(Mutex<Vec<NavigationOptions>>);
+    impl NavigationProvider for NavigationRecorder {
+        fn navigate_to(&self, options: NavigationOptions) {
+            self.0.lock().unwrap().push(options);
+        }
+    }
+
+    let css = "div { width: 100px; height: 100px } #section:target { width: 200px }";
+    let (mut doc, (link, section)) = doc_with_body(800, 600, css, |mutr, body| {
+        let link = mutr.create_element(qual_name!("a", html), vec![]);
+        mutr.set_attribute(link, qual_name!("href"), "#section");
+        let section = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(section, qual_name!("id"), "section");
+        mutr.append_children(body, &[link, section]);
+        (link, section)
+    });
+    doc.set_base_url("https://example.com/page.html");
+    let provider = Arc::new(NavigationRecorder::default());
+    doc.set_navigation_provider(provider.clone());
+    assert_eq!(doc.nodes[section].final_layout.size.width, 100.0);
+
+    let event = doc.nodes[link].synthetic_click_event_data(Modifiers::empty());
+    handle_click(&mut doc, link, &event, |_| {});
+    doc.resolve(0.0);
+
+    assert!(provider.0.lock().unwrap().is_empty());
+    assert_eq!(doc.url.fragment(), Some("section"));
+    assert_eq!(doc.nodes[section].final_layout.size.width, 200.0);
+}
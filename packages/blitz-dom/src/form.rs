@@ -159,12 +159,16 @@ impl BaseDocument {
             }
         }
 
+        // Only POST submissions have a request body that the encoding type applies to
+        let content_type = match post_resource {
+            Body::Empty => String::new(),
+            _ => enctype.to_string(),
+        };
         let method = method.try_into().unwrap_or_default();
 
-        let navigation_options =
-            NavigationOptions::new(parsed_action, enctype.to_string(), self.id())
-                .set_document_resource(post_resource)
-                .set_method(method);
+        let navigation_options = NavigationOptions::new(parsed_action, content_type, self.id())
+            .set_document_resource(post_resource)
+            .set_method(method);
 
         self.navigation_provider.navigate_to(navigation_options)
     }
@@ -449,6 +453,231 @@ fn normalize_line_endings(input: &str) -> String {
     result
 }
 
+/// A date in the proleptic Gregorian calendar, as used by `<input type="date">`
+///
+/// <https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#dates>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct InputDate {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl InputDate {
+    fn days_in_month(year: i64, month: u32) -> u32 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// The number of days since 1970-01-01
+    ///
+    /// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+    fn to_days(self) -> i64 {
+        let year = if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// The date a number of days after 1970-01-01
+    ///
+    /// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+}
+
+impl FromStr for InputDate {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(());
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if year.len() < 4 || month.len() != 2 || day.len() != 2 {
+            return Err(());
+        }
+        if !is_digits(year) || !is_digits(month) || !is_digits(day) {
+            return Err(());
+        }
+
+        let year: i64 = year.parse().map_err(|_| ())?;
+        let month: u32 = month.parse().map_err(|_| ())?;
+        let day: u32 = day.parse().map_err(|_| ())?;
+        if year < 1 || !(1..=12).contains(&month) {
+            return Err(());
+        }
+        if day < 1 || day > Self::days_in_month(year, month) {
+            return Err(());
+        }
+        Ok(Self { year, month, day })
+    }
+}
+
+impl Display for InputDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl ElementData {
+    fn is_date_input(&self) -> bool {
+        self.name.local == local_name!("input") && self.attr(local_name!("type")) == Some("date")
+    }
+
+    /// The current value of an input (from its editor if it has one, otherwise from its `value` attribute)
+    fn input_value(&self) -> String {
+        match self.text_input_data() {
+            Some(input_data) => input_data.editor.raw_text().trim().to_string(),
+            None => self
+                .attr(local_name!("value"))
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    fn date_attr(&self, name: LocalName) -> Option<InputDate> {
+        self.attr(name)?.parse().ok()
+    }
+
+    /// The `step` of a date input in days (`None` if any value is allowed)
+    fn date_step(&self) -> Option<i64> {
+        match self.attr(local_name!("step")) {
+            Some(step) if step.eq_ignore_ascii_case("any") => None,
+            Some(step) => Some(
+                step.parse::<f64>()
+                    .ok()
+                    .filter(|step| *step > 0.0)
+                    .map_or(1, |step| (step.round() as i64).max(1)),
+            ),
+            None => Some(1),
+        }
+    }
+
+    /// Whether the element's value satisfies its constraints (which determines whether it matches
+    /// `:valid` or `:invalid`). Returns `None` for elements whose validity isn't computed, which is
+    /// currently everything other than `<input type="date">`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#the-constraint-validation-api>
+    pub fn validity(&self) -> Option<bool> {
+        if !self.is_date_input() {
+            return None;
+        }
+
+        let value = self.input_value();
+        if value.is_empty() {
+            return Some(true);
+        }
+        let Ok(date) = value.parse::<InputDate>() else {
+            return Some(false);
+        };
+
+        let min = self.date_attr(local_name!("min"));
+        let max = self.date_attr(local_name!("max"));
+        if min.is_some_and(|min| date < min) || max.is_some_and(|max| date > max) {
+            return Some(false);
+        }
+        if let Some(step) = self.date_step() {
+            let step_base = min.map_or(0, InputDate::to_days);
+            if (date.to_days() - step_base).rem_euclid(step) != 0 {
+                return Some(false);
+            }
+        }
+
+        Some(true)
+    }
+}
+
+impl BaseDocument {
+    /// Step the value of an `<input type="date">` up or down by its `step` (in days), keeping it
+    /// within its `min` and `max`. Returns the new value, or `None` if the node isn't a date input.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#dom-input-stepup>
+    // TODO: step the focussed component (day, month, or year) once date inputs are edited as segmented fields
+    pub(crate) fn step_date_input(&mut self, node_id: usize, up: bool) -> Option<String> {
+        let element = self.nodes[node_id].element_data()?;
+        if !element.is_date_input() || element.text_input_data().is_none() {
+            return None;
+        }
+
+        let min = element
+            .date_attr(local_name!("min"))
+            .map(InputDate::to_days);
+        let max = element
+            .date_attr(local_name!("max"))
+            .map(InputDate::to_days);
+        let step = element.date_step().unwrap_or(1);
+        let step_base = min.unwrap_or(0);
+
+        let mut days = match element.input_value().parse::<InputDate>() {
+            Ok(date) => {
+                // Step from the nearest value on the step grid in the stepping direction
+                let days = date.to_days();
+                let offset = (days - step_base).rem_euclid(step);
+                match (offset, up) {
+                    (0, true) => days + step,
+                    (0, false) => days - step,
+                    (offset, true) => days - offset + step,
+                    (offset, false) => days - offset,
+                }
+            }
+            Err(_) => {
+                let today = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |duration| (duration.as_secs() / 86400) as i64);
+                let start = if up { min } else { max };
+                start.unwrap_or(today)
+            }
+        };
+        if let Some(max) = max {
+            if days > max {
+                days = max - (max - step_base).rem_euclid(step);
+            }
+        }
+        if let Some(min) = min {
+            days = days.max(min);
+        }
+
+        let value = InputDate::from_days(days).to_string();
+        let input_data = self.nodes[node_id]
+            .element_data_mut()
+            .and_then(|element| element.text_input_data_mut())?;
+        input_data.set_text(
+            &mut self.font_ctx.lock().unwrap(),
+            &mut self.layout_ctx,
+            &value,
+        );
+        self.snapshot_node_and(node_id, |node| node.update_validity());
+
+        Some(value)
+    }
+}
+
 /// Encodes form data as text/plain according to HTML spec given an slice of name-value pairs
 /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#text/plain-encoding-algorithm
 fn encode_text_plain<T: AsRef<str>, U: AsRef<str>>(input: &[(T, U)]) -> String {
@@ -461,3 +690,136 @@ fn encode_text_plain<T: AsRef<str>, U: AsRef<str>>(input: &[(T, U)]) -> String {
     }
     out
 }
+
+#[test]
+fn form_post_navigation_carries_encoding() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::navigation::NavigationProvider;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct NavigationRecorder(Mutex<Vec<NavigationOptions>>);
+    impl NavigationProvider for NavigationRecorder {
+        fn navigate_to(&self, options: NavigationOptions) {
+            self.0.lock().unwrap().push(options);
+        }
+    }
+
+    let (mut doc, (form, button)) = doc_with_body(800, 600, "", |mutr, body| {
+        let form = mutr.create_element(qual_name!("form", html), vec![]);
+        mutr.set_attribute(form, qual_name!("method"), "post");
+        mutr.set_attribute(form, qual_name!("action"), "https://example.com/submit");
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("name"), "q");
+        mutr.set_attribute(input, qual_name!("value"), "blitz");
+        let button = mutr.create_element(qual_name!("button", html), vec![]);
+        mutr.append_children(form, &[input, button]);
+        mutr.append_children(body, &[form]);
+        (form, button)
+    });
+    let provider = Arc::new(NavigationRecorder::default());
+    doc.set_navigation_provider(provider.clone());
+
+    doc.submit_form(form, button);
+
+    let navigations = provider.0.lock().unwrap();
+    assert_eq!(navigations.len(), 1);
+    assert_eq!(navigations[0].method, Method::POST);
+    assert_eq!(
+        navigations[0].content_type,
+        "application/x-www-form-urlencoded"
+    );
+    assert!(matches!(navigations[0].document_resource, Body::Form(_)));
+}
+
+#[test]
+fn input_date_round_trips_through_days() {
+    for date in [
+        "1970-01-01",
+        "2000-02-29",
+        "2024-12-31",
+        "0001-01-01",
+        "1969-07-20",
+    ] {
+        let parsed: InputDate = date.parse().unwrap();
+        assert_eq!(InputDate::from_days(parsed.to_days()).to_string(), date);
+    }
+    assert_eq!(InputDate::from_str("1970-01-02").unwrap().to_days(), 1);
+    assert!(InputDate::from_str("2023-02-29").is_err());
+    assert!(InputDate::from_str("2023-1-01").is_err());
+}
+
+#[test]
+fn stepping_date_input_clamps_to_max() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("type"), "date");
+        mutr.set_attribute(input, qual_name!("value"), "2024-02-28");
+        mutr.set_attribute(input, qual_name!("max"), "2024-03-01");
+        mutr.append_children(body, &[input]);
+        input
+    });
+
+    assert_eq!(
+        doc.step_date_input(input, true).as_deref(),
+        Some("2024-02-29")
+    );
+    assert_eq!(
+        doc.step_date_input(input, true).as_deref(),
+        Some("2024-03-01")
+    );
+    assert_eq!(
+        doc.step_date_input(input, true).as_deref(),
+        Some("2024-03-01")
+    );
+    assert_eq!(
+        doc.step_date_input(input, false).as_deref(),
+        Some("2024-02-29")
+    );
+}
+
+#[test]
+fn out_of_range_dates_are_invalid() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use style_dom::ElementState;
+
+    let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("type"), "date");
+        mutr.set_attribute(input, qual_name!("min"), "2024-01-01");
+        mutr.set_attribute(input, qual_name!("max"), "2024-12-31");
+        mutr.set_attribute(input, qual_name!("step"), "7");
+        mutr.set_attribute(input, qual_name!("value"), "2023-12-31");
+        mutr.append_children(body, &[input]);
+        input
+    });
+
+    let validity = |doc: &BaseDocument| doc.nodes[input].element_data().unwrap().validity();
+    assert_eq!(validity(&doc), Some(false));
+    assert!(
+        doc.nodes[input]
+            .element_state
+            .contains(ElementState::INVALID)
+    );
+
+    // Dates must also be a whole number of steps after `min`
+    for (value, valid) in [
+        ("2024-01-08", true),
+        ("2024-01-09", false),
+        ("2025-01-06", false),
+    ] {
+        doc.mutate()
+            .set_attribute(input, qual_name!("value"), value);
+        assert_eq!(validity(&doc), Some(valid), "{value}");
+    }
+    assert!(
+        doc.nodes[input]
+            .element_state
+            .contains(ElementState::INVALID)
+    );
+}
@@ -38,6 +38,9 @@ pub struct CssBox {
     pub padding_width: Insets,
     pub border_width: Insets,
     pub outline_width: f64,
+    /// The gap between the border box and the outline (`outline-offset`). Negative offsets move the
+    /// outline inside the border box.
+    pub outline_offset: f64,
 
     pub border_radii: NonUniformRoundedRectRadii,
 }
@@ -56,23 +59,7 @@ impl CssBox {
 
         // Correct the border radii if they are too big if two border radii would intersect, then we need to shrink
         // ALL border radii by the same factor such that they do not
-        let top_overlap_factor =
-            border_box.width() / (border_radii.top_left.x + border_radii.top_right.x);
-        let bottom_overlap_factor =
-            border_box.width() / (border_radii.bottom_left.x + border_radii.bottom_right.x);
-        let left_overlap_factor =
-            border_box.height() / (border_radii.top_left.y + border_radii.bottom_left.y);
-        let right_overlap_factor =
-            border_box.height() / (border_radii.top_right.y + border_radii.bottom_right.y);
-
-        let min_factor = top_overlap_factor
-            .min(bottom_overlap_factor)
-            .min(left_overlap_factor)
-            .min(right_overlap_factor)
-            .min(1.0);
-        if min_factor < 1.0 {
-            border_radii *= min_factor
-        }
+        border_radii.scale_to_fit(border_box.size());
 
         Self {
             padding_box,
@@ -80,12 +67,36 @@ impl CssBox {
             content_box,
             outline_box,
             outline_width,
+            outline_offset: 0.0,
             padding_width: padding,
             border_width: border,
             border_radii,
         }
     }
 
+    /// The same box with a different border width. The border box and border radii are kept, so the
+    /// border is resized inwards (and the padding box and content box shrink or grow to match).
+    pub fn with_border_width(&self, border_width: Insets) -> Self {
+        Self::new(
+            self.border_box,
+            border_width,
+            self.padding_width,
+            self.outline_width,
+            self.border_radii,
+        )
+    }
+
+    /// The same box with its outline moved away from the border box by `outline_offset`
+    pub fn with_outline_offset(mut self, outline_offset: f64) -> Self {
+        // The outline can't be moved inwards past the middle of the box
+        let min_offset = -self.border_box.width().min(self.border_box.height()) / 2.0;
+        self.outline_offset = outline_offset.max(min_offset);
+        self.outline_box = self
+            .border_box
+            .inset(self.outline_width + self.outline_offset);
+        self
+    }
+
     /// Construct a BezPath representing one edge of a box's border.
     /// Takes into account border-radius and the possibility that the edges
     /// are different colors.
@@ -140,15 +151,47 @@ impl CssBox {
     }
 
     /// Construct a bezpath drawing the outline
+    ///
+    /// The outer edge follows the border radii enlarged by the outline width, and the
+    /// inner edge follows the (rounded) border box. Both edges are moved outwards by the outline offset.
     pub fn outline(&self) -> BezPath {
-        let mut path = BezPath::new();
+        // The inner edge of the outline is the border box grown by the offset (with its radii grown to match)
+        let offset_box;
+        let outline_box = if self.outline_offset == 0.0 {
+            self
+        } else {
+            let offset = self.outline_offset;
+            let grow = |radii: Vec2| match radii.x == 0.0 || radii.y == 0.0 {
+                // Square corners stay square
+                true => radii,
+                false => Vec2::new((radii.x + offset).max(0.0), (radii.y + offset).max(0.0)),
+            };
+            let radii = self.border_radii;
+            offset_box = CssBox::new(
+                self.border_box.inset(offset),
+                Insets::ZERO,
+                Insets::ZERO,
+                self.outline_width,
+                NonUniformRoundedRectRadii {
+                    top_left: grow(radii.top_left),
+                    top_right: grow(radii.top_right),
+                    bottom_right: grow(radii.bottom_right),
+                    bottom_left: grow(radii.bottom_left),
+                },
+            );
+            &offset_box
+        };
 
-        // TODO: this has been known to produce quirky outputs with hugely rounded edges
-        self.shape(&mut path, CssBoxKind::OutlineBox, Direction::Clockwise);
-        path.move_to(self.corner(Corner::TopLeft, CssBoxKind::BorderBox));
+        let mut path = BezPath::new();
+        outline_box.shape(&mut path, CssBoxKind::OutlineBox, Direction::Clockwise);
+        path.close_path();
 
-        self.shape(&mut path, CssBoxKind::BorderBox, Direction::Anticlockwise);
-        path.move_to(self.corner(Corner::TopLeft, CssBoxKind::BorderBox));
+        // Build the inner edge as a separate subpath. Appending it to the outer path directly
+        // would join the two with a stray line segment, which shows up as a wedge on rounded corners.
+        let mut inner = BezPath::new();
+        outline_box.shape(&mut inner, CssBoxKind::BorderBox, Direction::Anticlockwise);
+        inner.close_path();
+        path.extend(inner);
 
         path
     }
@@ -546,3 +589,106 @@ fn should_solve_properly() {
     // 0.643501
     dbg!(start_angle(4.0, 1.0, Vec2 { x: 1.0, y: 2.0 }));
 }
+
+#[test]
+fn border_radius_100_percent_is_scaled_to_fit() {
+    // `border-radius: 100%` on a 200x100 box resolves to a 200x100 radius for every corner
+    let radius = Vec2::new(200.0, 100.0);
+    let css_box = CssBox::new(
+        Rect::new(0.0, 0.0, 200.0, 100.0),
+        Insets::ZERO,
+        Insets::ZERO,
+        0.0,
+        NonUniformRoundedRectRadii {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        },
+    );
+
+    // All radii are halved, producing an ellipse
+    let expected = Vec2::new(100.0, 50.0);
+    assert_eq!(css_box.border_radii.top_left, expected);
+    assert_eq!(css_box.border_radii.top_right, expected);
+    assert_eq!(css_box.border_radii.bottom_right, expected);
+    assert_eq!(css_box.border_radii.bottom_left, expected);
+}
+
+#[test]
+fn asymmetric_border_radii_stay_within_box() {
+    let border_box = Rect::new(0.0, 0.0, 100.0, 50.0);
+    let css_box = CssBox::new(
+        border_box,
+        Insets::uniform(4.0),
+        Insets::ZERO,
+        0.0,
+        NonUniformRoundedRectRadii {
+            top_left: Vec2::new(80.0, 40.0),
+            top_right: Vec2::new(60.0, 10.0),
+            bottom_right: Vec2::new(10.0, 45.0),
+            bottom_left: Vec2::new(30.0, 30.0),
+        },
+    );
+
+    // Every side is constrained by the same (smallest) factor
+    let radii = css_box.border_radii;
+    let factor = 100.0 / 140.0;
+    assert!((radii.top_left.x - 80.0 * factor).abs() < 1e-9);
+    assert!((radii.bottom_right.y - 45.0 * factor).abs() < 1e-9);
+    assert!(radii.top_left.x + radii.top_right.x <= border_box.width() + 1e-9);
+    assert!(radii.bottom_left.x + radii.bottom_right.x <= border_box.width() + 1e-9);
+    assert!(radii.top_left.y + radii.bottom_left.y <= border_box.height() + 1e-9);
+    assert!(radii.top_right.y + radii.bottom_right.y <= border_box.height() + 1e-9);
+
+    let bounds = border_box.inflate(1e-3, 1e-3);
+    let paths = [
+        css_box.border_box_path(),
+        css_box.padding_box_path(),
+        css_box.border_edge_shape(Edge::Top),
+        css_box.border_edge_shape(Edge::Right),
+        css_box.border_edge_shape(Edge::Bottom),
+        css_box.border_edge_shape(Edge::Left),
+    ];
+    for path in paths {
+        let bbox = path.bounding_box();
+        assert_eq!(
+            bounds.union(bbox),
+            bounds,
+            "{bbox:?} escapes {border_box:?}"
+        );
+    }
+}
+
+#[test]
+fn outline_follows_border_radius() {
+    let css_box = CssBox::new(
+        Rect::new(0.0, 0.0, 100.0, 100.0),
+        Insets::ZERO,
+        Insets::ZERO,
+        5.0,
+        NonUniformRoundedRectRadii {
+            top_left: Vec2::new(10.0, 10.0),
+            top_right: Vec2::new(10.0, 10.0),
+            bottom_right: Vec2::new(10.0, 10.0),
+            bottom_left: Vec2::new(10.0, 10.0),
+        },
+    );
+
+    // The outline corners share the centre of the border corners but have radii enlarged by the outline width
+    let border = css_box.ellipse(Corner::TopLeft, CssBoxKind::BorderBox);
+    let outline = css_box.ellipse(Corner::TopLeft, CssBoxKind::OutlineBox);
+    assert_eq!(outline.center(), border.center());
+    assert_eq!(outline.radii(), border.radii() + Vec2::new(5.0, 5.0));
+
+    let path = css_box.outline();
+    let bbox = path.bounding_box();
+    assert!((bbox.x0 + 5.0).abs() < 1e-3 && (bbox.y1 - 105.0).abs() < 1e-3);
+
+    // The corner of the outline box is cut off by the rounded outline...
+    assert!(!path.contains(Point::new(-4.0, -4.0)));
+    // ...but the straight edges are still painted...
+    assert!(path.contains(Point::new(-2.5, 50.0)));
+    // ...and the border box itself is not
+    assert!(!path.contains(Point::new(50.0, 50.0)));
+}
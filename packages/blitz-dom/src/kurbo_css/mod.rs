@@ -0,0 +1,123 @@
+//! A rounded rect closer to the browser
+//! Implemented in such a way that splits the border into 4 parts at the midway of each radius
+//!
+//! This object is meant to be updated only when the data changes - BezPaths are expensive!
+//!
+//! Can I just say, this is a lot of work for a border
+//! HTML/css is annoyingly wild
+
+use kurbo::{Insets, Rect, Vec2};
+use style::properties::ComputedValues;
+use style::values::computed::{BorderCornerRadius, CSSPixelLength};
+use taffy::Layout;
+
+mod css_box;
+mod non_uniform_radii;
+
+pub use css_box::CssBox;
+pub use non_uniform_radii::NonUniformRoundedRectRadii;
+
+/// Convert Stylo and Taffy types into Kurbo types
+pub fn create_css_rect(style: &ComputedValues, layout: &Layout, scale: f64) -> CssBox {
+    // Resolve and rescale
+    // We have to scale since document pixels are not same same as rendered pixels
+    let width: f64 = layout.size.width as f64;
+    let height: f64 = layout.size.height as f64;
+    let border_box = Rect::new(0.0, 0.0, width * scale, height * scale);
+    let border = insets_from_taffy_rect(layout.border.map(|p| p as f64 * scale));
+    let padding = insets_from_taffy_rect(layout.padding.map(|p| p as f64 * scale));
+    let outline = style.get_outline();
+    let outline_width = outline.outline_width.to_f64_px() * scale;
+    let outline_offset = outline.outline_offset.px() as f64 * scale;
+
+    // Resolve the radii to a length. need to downscale since the radii are in document pixels
+    let resolve_w = CSSPixelLength::new(width as _);
+    let resolve_h = CSSPixelLength::new(height as _);
+    let resolve_radii = |radius: &BorderCornerRadius| -> Vec2 {
+        Vec2 {
+            x: scale * radius.0.width.0.resolve(resolve_w).px() as f64,
+            y: scale * radius.0.height.0.resolve(resolve_h).px() as f64,
+        }
+    };
+    let s_border = style.get_border();
+    let border_radii = NonUniformRoundedRectRadii {
+        top_left: resolve_radii(&s_border.border_top_left_radius),
+        top_right: resolve_radii(&s_border.border_top_right_radius),
+        bottom_right: resolve_radii(&s_border.border_bottom_right_radius),
+        bottom_left: resolve_radii(&s_border.border_bottom_left_radius),
+    };
+
+    CssBox::new(border_box, border, padding, outline_width, border_radii)
+        .with_outline_offset(outline_offset)
+}
+
+fn insets_from_taffy_rect(input: taffy::Rect<f64>) -> Insets {
+    Insets {
+        x0: input.left,
+        y0: input.top,
+        x1: input.right,
+        y1: input.bottom,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Edge {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::enum_variant_names, reason = "Use CSS standard terminology")]
+pub(crate) enum CssBoxKind {
+    OutlineBox,
+    BorderBox,
+    PaddingBox,
+    ContentBox,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+    Clockwise,
+    Anticlockwise,
+}
+
+fn add_insets(a: Insets, b: Insets) -> Insets {
+    Insets {
+        x0: a.x0 + b.x0,
+        y0: a.y0 + b.y0,
+        x1: a.x1 + b.x1,
+        y1: a.y1 + b.y1,
+    }
+}
+
+#[inline(always)]
+fn get_corner_insets(insets: Insets, corner: Corner) -> Vec2 {
+    match corner {
+        Corner::TopLeft => Vec2 {
+            x: insets.x0,
+            y: insets.y0,
+        },
+        Corner::TopRight => Vec2 {
+            x: insets.x1,
+            y: insets.y0,
+        },
+        Corner::BottomLeft => Vec2 {
+            x: insets.x0,
+            y: insets.y1,
+        },
+        Corner::BottomRight => Vec2 {
+            x: insets.x1,
+            y: insets.y1,
+        },
+    }
+}
@@ -1,4 +1,4 @@
-use kurbo::Vec2;
+use kurbo::{Size, Vec2};
 use std::ops::{Mul, MulAssign};
 
 /// Radii for each corner of a non-uniform rounded rectangle.
@@ -35,6 +35,25 @@ impl NonUniformRoundedRectRadii {
             + self.bottom_right.y)
             / 8.0
     }
+
+    /// Scale all radii by a common factor such that the radii of two adjacent corners
+    /// never sum to more than the length of the side they share.
+    ///
+    /// See <https://www.w3.org/TR/css-backgrounds-3/#corner-overlap>
+    pub fn scale_to_fit(&mut self, size: Size) {
+        let factor = [
+            size.width / (self.top_left.x + self.top_right.x),
+            size.width / (self.bottom_left.x + self.bottom_right.x),
+            size.height / (self.top_left.y + self.bottom_left.y),
+            size.height / (self.top_right.y + self.bottom_right.y),
+        ]
+        .into_iter()
+        .fold(1.0, f64::min);
+
+        if factor < 1.0 {
+            *self *= factor;
+        }
+    }
 }
 
 impl Mul<f64> for NonUniformRoundedRectRadii {
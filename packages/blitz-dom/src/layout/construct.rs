@@ -3,15 +3,17 @@ use std::sync::Arc;
 
 use markup5ever::{QualName, local_name, ns};
 use parley::{
-    FontContext, InlineBox, InlineBoxKind, LayoutContext, StyleProperty, TreeBuilder,
-    WhiteSpaceCollapse,
+    FontContext, InlineBox, InlineBoxKind, LayoutContext, TreeBuilder, WhiteSpaceCollapse,
 };
 use slab::Slab;
 use style::{
     computed_values::position::T as PositionProperty,
     data::ElementData as StyloElementData,
-    selector_parser::RestyleDamage,
+    properties::ComputedValues,
+    selector_parser::{PseudoElement, RestyleDamage},
+    servo_arc::Arc as ServoArc,
     shared_lock::StylesheetGuards,
+    stylist::RuleInclusion,
     values::{
         computed::{Content, ContentItem, Display, Float},
         specified::box_::{DisplayInside, DisplayOutside},
@@ -26,6 +28,7 @@ use crate::{
         TextBrush, TextInputData, TextLayout,
     },
     qual_name, stylo_to_parley,
+    stylo_to_parley::stylo::WhiteSpaceCollapse as StyloWhiteSpaceCollapse,
 };
 
 use super::{damage::ALL_DAMAGE, list::collect_list_item_children, table::build_table_context};
@@ -116,7 +119,9 @@ pub(crate) fn collect_layout_children(
                 return;
             } else if matches!(
                 type_attr,
-                None | Some("text" | "password" | "email" | "number" | "search" | "tel" | "url")
+                None | Some(
+                    "text" | "password" | "email" | "number" | "search" | "tel" | "url" | "date"
+                )
             ) {
                 create_text_editor(doc, container_node_id, false);
                 return;
@@ -126,6 +131,11 @@ pub(crate) fn collect_layout_children(
             }
         }
 
+        if tag_name == "img" {
+            create_image_alt_text(doc, container_node_id);
+            return;
+        }
+
         #[cfg(feature = "svg")]
         if matches!(tag_name, "svg") {
             let mut outer_html = doc.get_node(container_node_id).unwrap().outer_html();
@@ -518,8 +528,6 @@ fn collect_complex_layout_children(
         // Push nodes that need wrapping into the current "anonymous block container".
         // If there is not an open one then we create one.
         else if needs_wrap(child_node_kind, display_outside) {
-            use style::selector_parser::PseudoElement;
-
             if anonymous_block_id.is_none() {
                 const NAME: QualName = QualName {
                     prefix: None,
@@ -587,13 +595,27 @@ fn collect_complex_layout_children(
 }
 
 fn create_text_editor(doc: &mut BaseDocument, input_element_id: usize, is_multiline: bool) {
-    let node = &mut doc.nodes[input_element_id];
+    let node = &doc.nodes[input_element_id];
     let parley_style = node
         .primary_styles()
         .as_ref()
-        .map(|s| stylo_to_parley::style(node.id, s))
+        .map(|s| stylo_to_parley::style(node.id, s, node.lang()))
         .unwrap_or_default();
 
+    // The placeholder is laid out with the styles of the `::placeholder` pseudo-element
+    let placeholder = node
+        .attr(local_name!("placeholder"))
+        .filter(|text| !text.is_empty())
+        .map(str::to_owned);
+    let placeholder_style = placeholder
+        .as_ref()
+        .and_then(|_| placeholder_style(doc, node));
+    let placeholder_parley_style = placeholder_style
+        .as_ref()
+        .map(|s| stylo_to_parley::style(node.id, s, node.lang()))
+        .unwrap_or_else(|| parley_style.clone());
+
+    let node = &mut doc.nodes[input_element_id];
     let element = &mut node.data.downcast_element_mut().unwrap();
     if !matches!(element.special_data, SpecialElementData::TextInput(_)) {
         let mut text_input_data = TextInputData::new(is_multiline);
@@ -606,17 +628,76 @@ fn create_text_editor(doc: &mut BaseDocument, input_element_id: usize, is_multil
         unreachable!();
     };
 
+    text_input_data.set_text_style(&parley_style);
+    text_input_data.placeholder_style = placeholder_style;
+
     let editor = &mut text_input_data.editor;
     editor.set_scale(doc.viewport.scale_f64() as f32);
     editor.set_width(None);
+    editor.refresh_layout(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx);
 
-    let styles = editor.edit_styles();
-    styles.retain(|_| false);
-    styles.insert(StyleProperty::FontSize(parley_style.font_size));
-    styles.insert(StyleProperty::LineHeight(parley_style.line_height));
-    styles.insert(StyleProperty::Brush(parley_style.brush));
+    text_input_data.placeholder = placeholder.map(|text| {
+        let mut font_ctx = doc.font_ctx.lock().unwrap();
+        let mut builder = doc.layout_ctx.tree_builder(
+            &mut font_ctx,
+            doc.viewport.scale(),
+            true,
+            &placeholder_parley_style,
+        );
+        builder.push_text(&text);
 
-    editor.refresh_layout(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx);
+        let mut layout = builder.build().0;
+        layout.break_all_lines(None);
+        Box::new(layout)
+    });
+}
+
+/// Compute the styles of the `::placeholder` pseudo-element of a text input
+fn placeholder_style(doc: &BaseDocument, node: &Node) -> Option<ServoArc<ComputedValues>> {
+    let style = node.primary_styles()?;
+    let read_guard = doc.guard.read();
+    let guards = StylesheetGuards::same(&read_guard);
+    doc.stylist.lazily_compute_pseudo_element_style(
+        &guards,
+        node,
+        &PseudoElement::Placeholder,
+        RuleInclusion::All,
+        &style,
+        false,
+        None,
+    )
+}
+
+/// Lay out the `alt` text of an `<img>` element which has no image to display (because it has no
+/// `src` or its image failed to load), so that the text can be painted in place of the image
+fn create_image_alt_text(doc: &mut BaseDocument, img_element_id: usize) {
+    let node = &doc.nodes[img_element_id];
+    let element = node.element_data().unwrap();
+    let alt = element
+        .attr(local_name!("alt"))
+        .filter(|alt| !alt.is_empty() && element.has_missing_image())
+        .map(str::to_owned);
+    let parley_style = node
+        .primary_styles()
+        .as_ref()
+        .map(|s| stylo_to_parley::style(node.id, s, node.lang()))
+        .unwrap_or_default();
+
+    let alt_text_layout = alt.map(|text| {
+        let mut font_ctx = doc.font_ctx.lock().unwrap();
+        let mut builder =
+            doc.layout_ctx
+                .tree_builder(&mut font_ctx, doc.viewport.scale(), true, &parley_style);
+        builder.push_text(&text);
+
+        let mut layout = builder.build().0;
+        layout.break_all_lines(None);
+        Box::new(layout)
+    });
+    doc.nodes[img_element_id]
+        .element_data_mut()
+        .unwrap()
+        .alt_text_layout = alt_text_layout;
 }
 
 fn create_checkbox_input(doc: &mut BaseDocument, input_element_id: usize) {
@@ -792,7 +873,7 @@ pub(crate) fn build_inline_layout_into(
 
     let parley_style = root_node_style
         .as_ref()
-        .map(|s| stylo_to_parley::style(inline_context_root_node_id, s))
+        .map(|s| stylo_to_parley::style(inline_context_root_node_id, s, root_node.lang()))
         .unwrap_or_default();
 
     let root_line_height = resolve_line_height(parley_style.line_height, parley_style.font_size);
@@ -803,9 +884,8 @@ pub(crate) fn build_inline_layout_into(
     // Set whitespace collapsing mode
     let collapse_mode = root_node_style
         .map(|s| s.get_inherited_text().white_space_collapse)
-        .map(stylo_to_parley::white_space_collapse)
-        .unwrap_or(WhiteSpaceCollapse::Collapse);
-    builder.set_white_space_mode(collapse_mode);
+        .unwrap_or(StyloWhiteSpaceCollapse::Collapse);
+    builder.set_white_space_mode(stylo_to_parley::white_space_collapse(collapse_mode));
 
     // Render position-inside list items
     if let Some(ListItemLayout {
@@ -860,7 +940,7 @@ pub(crate) fn build_inline_layout_into(
         nodes: &Slab<Node>,
         parent_id: usize,
         node_id: usize,
-        collapse_mode: WhiteSpaceCollapse,
+        collapse_mode: StyloWhiteSpaceCollapse,
         root_line_height: f32,
     ) {
         let node = &nodes[node_id];
@@ -874,9 +954,8 @@ pub(crate) fn build_inline_layout_into(
         // Set whitespace collapsing mode
         let collapse_mode = style
             .map(|s| s.clone_white_space_collapse())
-            .map(stylo_to_parley::white_space_collapse)
             .unwrap_or(collapse_mode);
-        builder.set_white_space_mode(collapse_mode);
+        builder.set_white_space_mode(stylo_to_parley::white_space_collapse(collapse_mode));
 
         match &node.data {
             NodeData::Element(element_data) | NodeData::AnonymousBlock(element_data) => {
@@ -942,12 +1021,14 @@ pub(crate) fn build_inline_layout_into(
                             builder.set_white_space_mode(WhiteSpaceCollapse::Preserve);
                             builder.push_text("\n");
                             builder.pop_style_span();
-                            builder.set_white_space_mode(collapse_mode);
+                            builder.set_white_space_mode(stylo_to_parley::white_space_collapse(
+                                collapse_mode,
+                            ));
                         } else {
                             // node.remove_damage(CONSTRUCT_DESCENDENT | CONSTRUCT_FC | CONSTRUCT_BOX);
                             let mut style = node
                                 .primary_styles()
-                                .map(|s| stylo_to_parley::style(node.id, &s))
+                                .map(|s| stylo_to_parley::style(node.id, &s, node.lang()))
                                 .unwrap_or_default();
 
                             // dbg!(&style);
@@ -1018,7 +1099,11 @@ pub(crate) fn build_inline_layout_into(
             NodeData::Text(data) => {
                 // node.remove_damage(CONSTRUCT_DESCENDENT | CONSTRUCT_FC | CONSTRUCT_BOX);
                 // dbg!(&data.content);
-                builder.push_text(&data.content);
+                if collapse_mode == StyloWhiteSpaceCollapse::BreakSpaces {
+                    builder.push_text(&break_spaces_text(&data.content));
+                } else {
+                    builder.push_text(&data.content);
+                }
             }
             NodeData::Comment => {
                 // node.remove_damage(CONSTRUCT_DESCENDENT | CONSTRUCT_FC | CONSTRUCT_BOX);
@@ -1027,3 +1112,121 @@ pub(crate) fn build_inline_layout_into(
         }
     }
 }
+
+/// Parley doesn't support `white-space: break-spaces`, which preserves spaces (and tabs), allows a line
+/// break after every one of them, and doesn't let them hang at the end of a line.
+///
+/// So (on top of parley's `Preserve` mode) we insert a zero width space after each space. This provides the
+/// break opportunity, and means that spaces at the end of a line are no longer "trailing whitespace" so they
+/// count towards the line's width when aligning and sizing it.
+fn break_spaces_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        output.push(c);
+        if c == ' ' || c == '\t' {
+            output.push('\u{200B}');
+        }
+    }
+    output
+}
+
+#[test]
+fn break_spaces_preserves_and_wraps_trailing_spaces() {
+    use crate::test_utils::doc_with_body;
+
+    let css = "div { font-size: 10px; width: 300px; text-align: right }
+               .pre-wrap { white-space: pre-wrap }
+               .break-spaces { white-space: break-spaces }
+               .narrow { width: 30px }";
+    let (doc, [pre_wrap, break_spaces, narrow]) = doc_with_body(800, 600, css, |mutr, body| {
+        let mut create_div = |class: &str, text: &str| {
+            let div = mutr.create_element(qual_name!("div", html), vec![]);
+            mutr.set_attribute(div, qual_name!("class", html), class);
+            let text = mutr.create_text_node(text);
+            mutr.append_children(div, &[text]);
+            div
+        };
+        let pre_wrap = create_div("pre-wrap", "ab   ");
+        let break_spaces = create_div("break-spaces", "ab   ");
+        let narrow = create_div("break-spaces narrow", &format!("ab{}", " ".repeat(20)));
+        mutr.append_children(body, &[pre_wrap, break_spaces, narrow]);
+        [pre_wrap, break_spaces, narrow]
+    });
+
+    let layout = |id: usize| {
+        &doc.nodes[id]
+            .element_data()
+            .unwrap()
+            .inline_layout_data
+            .as_ref()
+            .unwrap()
+            .layout
+    };
+    let trailing_whitespace = |id: usize| {
+        layout(id)
+            .lines()
+            .next()
+            .unwrap()
+            .metrics()
+            .trailing_whitespace
+    };
+
+    // Trailing spaces hang under pre-wrap, but take up space under break-spaces
+    assert!(trailing_whitespace(pre_wrap) > 0.0);
+    assert_eq!(trailing_whitespace(break_spaces), 0.0);
+
+    // Spaces which don't fit on a line wrap onto the next one
+    assert!(layout(narrow).len() > 1);
+}
+
+#[test]
+fn lang_selects_regional_glyphs_for_han_characters() {
+    use crate::test_utils::doc_with_body;
+    use parley::PositionedLayoutItem;
+    use parley::fontique::{FallbackKey, Script};
+
+    let (doc, [ja, zh]) = doc_with_body(800, 600, "", |mutr, body| {
+        let mut create_div = |lang: &str| {
+            let div = mutr.create_element(qual_name!("div", html), vec![]);
+            mutr.set_attribute(div, qual_name!("lang", html), lang);
+            // U+76F4 is drawn differently in Japanese and Simplified Chinese
+            let text = mutr.create_text_node("直");
+            mutr.append_children(div, &[text]);
+            div
+        };
+        let divs = [create_div("ja"), create_div("zh")];
+        mutr.append_children(body, &divs);
+        divs
+    });
+
+    // The glyphs can only differ if the system has distinct Japanese and Chinese fallback fonts
+    let fallback_family = |lang: &str| {
+        let lang = stylo_to_parley::locale(lang);
+        let key = FallbackKey::new(Script::from_bytes(*b"Hani"), lang.as_ref());
+        let mut font_ctx = doc.font_ctx.lock().unwrap();
+        font_ctx.collection.fallback_families(key).next()
+    };
+    let (ja_family, zh_family) = (fallback_family("ja"), fallback_family("zh"));
+    if ja_family.is_none() || ja_family == zh_family {
+        return;
+    }
+
+    let glyph = |id: usize| {
+        let layout = &doc.nodes[id]
+            .element_data()
+            .unwrap()
+            .inline_layout_data
+            .as_ref()
+            .unwrap()
+            .layout;
+        let line = layout.lines().next().unwrap();
+        let Some(PositionedLayoutItem::GlyphRun(glyph_run)) = line.items().next() else {
+            panic!("expected a glyph run");
+        };
+        let font = glyph_run.run().font();
+        let glyph_id = glyph_run.glyphs().next().unwrap().id;
+        (font.data.id(), font.index, glyph_id)
+    };
+
+    assert_ne!(glyph(ja), glyph(zh));
+}
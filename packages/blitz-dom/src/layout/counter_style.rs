@@ -0,0 +1,249 @@
+//! Counter styles (<https://drafts.csswg.org/css-counter-styles-3/>), which determine how the index of
+//! a list item is represented in its marker
+//!
+//! TODO:
+//!   - `@counter-style` rules. Stylo only parses them (and `list-style-type: <custom-ident>`) for the
+//!     Gecko engine, so only the predefined styles below can be used.
+//!   - The `cyclic`, `fixed` and `symbolic` systems, and the `pad` and `negative` descriptors
+
+use std::ops::RangeInclusive;
+
+/// The algorithm used to turn a counter value into a string, along with its symbols
+#[derive(Debug)]
+pub(crate) enum CounterSystem {
+    /// A positional numeral system, where the number of symbols is the base
+    Numeric(&'static [&'static str]),
+    /// Like `Numeric`, but without a symbol for zero (a, b, ..., z, aa, ab, ...)
+    Alphabetic(&'static [&'static str]),
+    /// The sum of the weights of the symbols (largest first), as with roman numerals
+    Additive(&'static [(u32, &'static str)]),
+}
+
+#[derive(Debug)]
+pub(crate) struct CounterStyle {
+    pub(crate) system: CounterSystem,
+    /// Appended to the representation of the counter in the marker
+    pub(crate) suffix: &'static str,
+    /// The values which the style can represent. Other values are represented with [`DECIMAL`].
+    pub(crate) range: RangeInclusive<i64>,
+}
+
+impl CounterStyle {
+    /// The text of the marker for the counter value `value` (including the suffix)
+    pub(crate) fn marker(&self, value: i64) -> String {
+        let mut marker = self
+            .representation(value)
+            .or_else(|| DECIMAL.representation(value))
+            .unwrap_or_default();
+        marker.push_str(self.suffix);
+        marker
+    }
+
+    /// The representation of `value` in this style, or `None` if the style can't represent it
+    fn representation(&self, value: i64) -> Option<String> {
+        if !self.range.contains(&value) {
+            return None;
+        }
+
+        // Negative values are represented as a negative sign followed by the absolute value
+        if value < 0 {
+            return Some(format!("-{}", self.representation(-value)?));
+        }
+        let mut value = value as u64;
+
+        match self.system {
+            CounterSystem::Numeric(symbols) => {
+                let base = symbols.len() as u64;
+                let mut digits = vec![symbols[(value % base) as usize]];
+                value /= base;
+                while value > 0 {
+                    digits.push(symbols[(value % base) as usize]);
+                    value /= base;
+                }
+                Some(digits.into_iter().rev().collect())
+            }
+            CounterSystem::Alphabetic(symbols) => {
+                if value == 0 {
+                    return None;
+                }
+                let base = symbols.len() as u64;
+                let mut letters = Vec::new();
+                while value > 0 {
+                    value -= 1;
+                    letters.push(symbols[(value % base) as usize]);
+                    value /= base;
+                }
+                Some(letters.into_iter().rev().collect())
+            }
+            CounterSystem::Additive(symbols) => {
+                if value == 0 {
+                    return symbols
+                        .iter()
+                        .find(|(weight, _)| *weight == 0)
+                        .map(|(_, symbol)| symbol.to_string());
+                }
+                let mut representation = String::new();
+                for (weight, symbol) in symbols.iter().filter(|(weight, _)| *weight > 0) {
+                    let weight = *weight as u64;
+                    while value >= weight {
+                        representation.push_str(symbol);
+                        value -= weight;
+                    }
+                }
+                (value == 0).then_some(representation)
+            }
+        }
+    }
+}
+
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+const LATIN: [&str; 26] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s",
+    "t", "u", "v", "w", "x", "y", "z",
+];
+const UPPER_LATIN: [&str; 26] = [
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S",
+    "T", "U", "V", "W", "X", "Y", "Z",
+];
+
+pub(crate) static DECIMAL: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&DIGITS),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static LOWER_ALPHA: CounterStyle = CounterStyle {
+    system: CounterSystem::Alphabetic(&LATIN),
+    suffix: ". ",
+    range: 1..=i64::MAX,
+};
+
+pub(crate) static UPPER_ALPHA: CounterStyle = CounterStyle {
+    system: CounterSystem::Alphabetic(&UPPER_LATIN),
+    suffix: ". ",
+    range: 1..=i64::MAX,
+};
+
+pub(crate) static LOWER_ROMAN: CounterStyle = CounterStyle {
+    system: CounterSystem::Additive(&[
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ]),
+    suffix: ". ",
+    range: 1..=3999,
+};
+
+pub(crate) static UPPER_ROMAN: CounterStyle = CounterStyle {
+    system: CounterSystem::Additive(&[
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ]),
+    suffix: ". ",
+    range: 1..=3999,
+};
+
+pub(crate) static LOWER_GREEK: CounterStyle = CounterStyle {
+    system: CounterSystem::Alphabetic(&[
+        "α", "β", "γ", "δ", "ε", "ζ", "η", "θ", "ι", "κ", "λ", "μ", "ν", "ξ", "ο", "π", "ρ", "σ",
+        "τ", "υ", "φ", "χ", "ψ", "ω",
+    ]),
+    suffix: ". ",
+    range: 1..=i64::MAX,
+};
+
+pub(crate) static ARABIC_INDIC: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["٠", "١", "٢", "٣", "٤", "٥", "٦", "٧", "٨", "٩"]),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static PERSIAN: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["۰", "۱", "۲", "۳", "۴", "۵", "۶", "۷", "۸", "۹"]),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static DEVANAGARI: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["०", "१", "२", "३", "४", "५", "६", "७", "८", "९"]),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static BENGALI: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["০", "১", "২", "৩", "৪", "৫", "৬", "৭", "৮", "৯"]),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static THAI: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["๐", "๑", "๒", "๓", "๔", "๕", "๖", "๗", "๘", "๙"]),
+    suffix: ". ",
+    range: i64::MIN..=i64::MAX,
+};
+
+pub(crate) static CJK_DECIMAL: CounterStyle = CounterStyle {
+    system: CounterSystem::Numeric(&["〇", "一", "二", "三", "四", "五", "六", "七", "八", "九"]),
+    suffix: "、",
+    range: 0..=i64::MAX,
+};
+
+pub(crate) static CJK_EARTHLY_BRANCH: CounterStyle = CounterStyle {
+    system: CounterSystem::Alphabetic(&[
+        "子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥",
+    ]),
+    suffix: "、",
+    range: 1..=i64::MAX,
+};
+
+pub(crate) static CJK_HEAVENLY_STEM: CounterStyle = CounterStyle {
+    system: CounterSystem::Alphabetic(&[
+        "甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸",
+    ]),
+    suffix: "、",
+    range: 1..=i64::MAX,
+};
+
+#[test]
+fn roman_numerals() {
+    let markers: Vec<String> = (1..=4).map(|value| LOWER_ROMAN.marker(value)).collect();
+    assert_eq!(markers, ["i. ", "ii. ", "iii. ", "iv. "]);
+    assert_eq!(UPPER_ROMAN.marker(1994), "MCMXCIV. ");
+
+    // Values outside of the range of roman numerals fall back to decimal
+    assert_eq!(LOWER_ROMAN.marker(0), "0. ");
+    assert_eq!(UPPER_ROMAN.marker(4000), "4000. ");
+}
+
+#[test]
+fn numeric_and_alphabetic_systems() {
+    assert_eq!(DECIMAL.marker(-12), "-12. ");
+    assert_eq!(ARABIC_INDIC.marker(105), "١٠٥. ");
+    assert_eq!(CJK_DECIMAL.marker(0), "〇、");
+
+    assert_eq!(LOWER_GREEK.marker(25), "αα. ");
+    assert_eq!(CJK_HEAVENLY_STEM.marker(11), "甲甲、");
+    // Alphabetic systems have no representation for zero
+    assert_eq!(LOWER_ALPHA.marker(0), "0. ");
+}
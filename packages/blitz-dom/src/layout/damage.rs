@@ -1,8 +1,9 @@
 use std::ops::Range;
 
 use crate::node::NodeFlags;
-use crate::{BaseDocument, net::ImageHandler, node::BackgroundImageData, util::ImageType};
-use crate::{NON_INCREMENTAL, Node};
+use crate::util::{ImageType, select_image_set_candidate};
+use crate::{BaseDocument, net::ImageHandler, node::BackgroundImageData};
+use crate::{NON_INCREMENTAL, Node, stylo_to_parley};
 use blitz_traits::net::Request;
 use style::properties::ComputedValues;
 use style::properties::generated::longhands::position::computed_value::T as Position;
@@ -343,7 +344,12 @@ impl BaseDocument {
                 input.editor.set_scale(scale);
                 let mut font_ctx = font_ctx.lock().unwrap();
                 input.editor.refresh_layout(&mut font_ctx, layout_ctx);
-                node.insert_damage(ONLY_RELAYOUT);
+
+                // The placeholder layout is only built during box construction
+                match input.placeholder.is_some() {
+                    true => node.insert_damage(ALL_DAMAGE),
+                    false => node.insert_damage(ONLY_RELAYOUT),
+                }
             }
         }
     }
@@ -365,7 +371,7 @@ impl BaseDocument {
 
         let display = {
             let node = self.nodes.get_mut(node_id).unwrap();
-            let _damage = node.damage().unwrap_or(ALL_DAMAGE);
+            let damage = node.damage().unwrap_or(ALL_DAMAGE);
             let stylo_element_data = node.stylo_element_data.borrow();
             let primary_styles = stylo_element_data
                 .as_ref()
@@ -390,13 +396,15 @@ impl BaseDocument {
                 elem_bgs.resize_with(len, || None);
 
                 for idx in 0..len {
-                    let background_image = &style_bgs[idx];
+                    // The candidate of an `image-set()` is selected for the device pixel ratio when
+                    // styles are computed (and so changes when the scale changes)
+                    let (background_image, _) = select_image_set_candidate(&style_bgs[idx]);
                     let new_bg_image = match background_image {
                         StyloImage::Url(ComputedUrl::Valid(new_url)) => {
                             let old_bg_image = elem_bgs[idx].as_ref();
                             let old_bg_image_url = old_bg_image.map(|data| &data.url);
                             if old_bg_image_url.is_some_and(|old_url| **new_url == **old_url) {
-                                break;
+                                continue;
                             }
 
                             self.net_provider.fetch(
@@ -416,6 +424,23 @@ impl BaseDocument {
                 }
             }
 
+            // Flush text styles to text inputs so that changes to font-size and line-height are
+            // reflected in the editor's layout (and thus the caret and selection geometry)
+            if damage.contains(RestyleDamage::RELAYOUT) {
+                if let Some(input) = node
+                    .data
+                    .downcast_element_mut()
+                    .and_then(|el| el.text_input_data_mut())
+                {
+                    // The locale is not needed as only the font size, line height, and brush are applied
+                    input.set_text_style(&stylo_to_parley::style(node_id, style, None));
+                    let mut font_ctx = self.font_ctx.lock().unwrap();
+                    input
+                        .editor
+                        .refresh_layout(&mut font_ctx, &mut self.layout_ctx);
+                }
+            }
+
             // In non-incremental mode we unconditionally clear the Taffy cache.
             // In incremental mode this is handled as part of damage propagation.
             if NON_INCREMENTAL {
@@ -549,3 +574,83 @@ fn node_to_paint_order(node: &Node, is_flex_or_grid: bool) -> i32 {
         position_to_order(style.clone_position()) + float_to_order(style.clone_float())
     }
 }
+
+#[test]
+fn image_set_selects_candidate_for_scale() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::shell::{ColorScheme, Viewport};
+
+    let (mut doc, div) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 10px; height: 10px; background-image: image-set(\
+             url(https://example.com/icon.png) 1x, url(https://example.com/icon@2x.png) 2x)",
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+    doc.set_viewport(Viewport::new(800, 600, 2.0, ColorScheme::Light));
+    doc.resolve(0.0);
+
+    let background_url = |doc: &BaseDocument| {
+        let element = doc.get_node(div).unwrap().element_data().unwrap();
+        let background = element.background_images[0].as_ref().unwrap();
+        background.url.as_str().to_string()
+    };
+    assert_eq!(background_url(&doc), "https://example.com/icon@2x.png");
+
+    // The candidate is selected again when the scale changes
+    doc.set_viewport(Viewport::new(800, 600, 1.0, ColorScheme::Light));
+    doc.resolve(0.0);
+    assert_eq!(background_url(&doc), "https://example.com/icon.png");
+}
+
+#[test]
+fn line_height_changes_text_input_selection_height() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("value"), "Hello");
+        mutr.set_attribute(
+            input,
+            qual_name!("style"),
+            "font-size: 16px; line-height: 20px",
+        );
+        mutr.append_children(body, &[input]);
+        input
+    });
+
+    let selection_height = |doc: &mut BaseDocument| {
+        let input_data = doc.nodes[input]
+            .data
+            .downcast_element_mut()
+            .and_then(|el| el.text_input_data_mut())
+            .unwrap();
+        input_data
+            .editor
+            .driver(&mut doc.font_ctx.lock().unwrap(), &mut doc.layout_ctx)
+            .select_all();
+        let geometry = input_data.editor.selection_geometry();
+        let (rect, _line_idx) = &geometry[0];
+        rect.y1 - rect.y0
+    };
+    let short = selection_height(&mut doc);
+
+    // Restyling the input with a larger line-height makes the selection of the same text taller
+    let mut mutr = doc.mutate();
+    mutr.set_attribute(
+        input,
+        qual_name!("style"),
+        "font-size: 16px; line-height: 40px",
+    );
+    drop(mutr);
+    doc.resolve(0.0);
+    let tall = selection_height(&mut doc);
+
+    assert!(tall > short, "{tall} should be taller than {short}");
+}
@@ -1,4 +1,6 @@
-use parley::AlignmentOptions;
+use parley::{AlignmentOptions, LineMetrics};
+use style::values::computed::VerticalAlign;
+use style::values::generics::box_::VerticalAlignKeyword;
 use taffy::{
     AvailableSpace, BlockContext, BlockFormattingContext, BoxSizing, CollapsibleMarginSet,
     CoreStyle as _, LayoutInput, LayoutOutput, LayoutPartialTree as _, MaybeMath as _,
@@ -577,11 +579,21 @@ impl BaseDocument {
             height: height / scale,
         });
 
+        // Approximate the x-height of the container's font as 0.5em (used for `vertical-align: middle`)
+        let x_height = self.nodes[node_id]
+            .primary_styles()
+            .map(|s| s.get_font().font_size.used_size.0.px() * 0.5 * scale)
+            .unwrap_or(0.0);
+
         // Store sizes and positions of inline boxes
         for line in inline_layout.layout.lines() {
             for item in line.items() {
                 if let parley::layout::PositionedLayoutItem::InlineBox(ibox) = item {
                     let node = &mut self.nodes[ibox.id as usize];
+                    let vertical_align = node
+                        .primary_styles()
+                        .map(|s| s.clone_vertical_align())
+                        .unwrap_or(VerticalAlign::Keyword(VerticalAlignKeyword::Baseline));
                     let padding = node
                         .style
                         .padding
@@ -653,11 +665,26 @@ impl BaseDocument {
                         layout.padding = padding; //.map(|p| p / scale);
                         layout.border = border; //.map(|p| p / scale);
                     } else {
+                        let y = vertical_align_inline_box(
+                            &vertical_align,
+                            line.metrics(),
+                            ibox.y,
+                            ibox.height,
+                            x_height,
+                        );
+
+                        // Relatively positioned boxes are offset from where they would otherwise be
+                        // (`left` and `top` win over `right` and `bottom`). The insets of static boxes
+                        // are always `auto`.
+                        let offset_x = left.or(right.map(|right| -right)).unwrap_or(0.0);
+                        let offset_y = top.or(bottom.map(|bottom| -bottom)).unwrap_or(0.0);
+
                         let layout = &mut node.unrounded_layout;
                         layout.size.width = (ibox.width / scale) - margin.left - margin.right;
                         layout.size.height = (ibox.height / scale) - margin.top - margin.bottom;
-                        layout.location.x = (ibox.x / scale) + margin.left + container_pb.left;
-                        layout.location.y = (ibox.y / scale) + margin.top + container_pb.top;
+                        layout.location.x =
+                            (ibox.x / scale) + margin.left + container_pb.left + offset_x;
+                        layout.location.y = (y / scale) + margin.top + container_pb.top + offset_y;
                         layout.padding = padding; //.map(|p| p / scale);
                         layout.border = border; //.map(|p| p / scale);
                     }
@@ -713,3 +740,53 @@ impl BaseDocument {
 fn f32_max(a: f32, b: f32) -> f32 {
     a.max(b)
 }
+
+/// Compute the y position of an inline box within its line according to its `vertical-align` style.
+///
+/// Parley always places inline boxes on the baseline, so `box_y` is the baseline-aligned position.
+fn vertical_align_inline_box(
+    vertical_align: &VerticalAlign,
+    line: &LineMetrics,
+    box_y: f32,
+    box_height: f32,
+    x_height: f32,
+) -> f32 {
+    let half_leading = (line.line_height - (line.ascent + line.descent)) / 2.0;
+    match vertical_align {
+        VerticalAlign::Keyword(VerticalAlignKeyword::Top) => {
+            line.baseline - line.ascent - half_leading
+        }
+        VerticalAlign::Keyword(VerticalAlignKeyword::Bottom) => {
+            line.baseline + line.descent + half_leading - box_height
+        }
+        VerticalAlign::Keyword(VerticalAlignKeyword::Middle) => {
+            line.baseline - (x_height / 2.0) - (box_height / 2.0)
+        }
+        // TODO: sub, super, text-top, text-bottom and length/percentage values
+        _ => box_y,
+    }
+}
+
+#[test]
+fn vertical_align_middle_centers_box_on_x_height() {
+    let line = LineMetrics {
+        ascent: 16.0,
+        descent: 4.0,
+        line_height: 24.0,
+        baseline: 18.0,
+        ..Default::default()
+    };
+    let box_height = 10.0;
+    let box_y = line.baseline - box_height;
+
+    let align = |keyword| {
+        let vertical_align = VerticalAlign::Keyword(keyword);
+        vertical_align_inline_box(&vertical_align, &line, box_y, box_height, 8.0)
+    };
+
+    assert_eq!(align(VerticalAlignKeyword::Baseline), 8.0);
+    // Box centre (y + 5) sits half an x-height (4) above the baseline (18)
+    assert_eq!(align(VerticalAlignKeyword::Middle), 9.0);
+    assert_eq!(align(VerticalAlignKeyword::Top), 0.0);
+    assert_eq!(align(VerticalAlignKeyword::Bottom), 14.0);
+}
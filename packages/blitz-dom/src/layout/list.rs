@@ -3,6 +3,7 @@ use parley::FontStack;
 use style::computed_values::list_style_position::T as ListStylePosition;
 use style::computed_values::list_style_type::T as ListStyleType;
 
+use super::counter_style::{self, CounterStyle};
 use crate::{
     BaseDocument,
     node::{ListItemLayout, ListItemLayoutPosition, Marker},
@@ -68,12 +69,17 @@ fn node_list_item_child(
     let styles = node.primary_styles().unwrap();
     let list_style_type = styles.clone_list_style_type();
     let list_style_position = styles.clone_list_style_position();
-    let marker = marker_for_style(list_style_type, index)?;
+    let marker = match counter_style_for_type_attr(doc, child_id)
+        .filter(|_| list_style_type == ListStyleType::Decimal)
+    {
+        Some(counter_style) => Marker::String(counter_style.marker(index as i64 + 1)),
+        None => marker_for_style(list_style_type, index)?,
+    };
 
     let position = match list_style_position {
         ListStylePosition::Inside => ListItemLayoutPosition::Inside,
         ListStylePosition::Outside => {
-            let mut parley_style = stylo_to_parley::style(child_id, &styles);
+            let mut parley_style = stylo_to_parley::style(child_id, &styles, node.lang());
 
             if let Some(font_stack) = font_for_bullet_style(list_style_type) {
                 parley_style.font_stack = font_stack;
@@ -113,18 +119,11 @@ fn marker_for_style(list_style_type: ListStyleType, index: usize) -> Option<Mark
         return None;
     }
 
+    if let Some(counter_style) = counter_style(list_style_type) {
+        return Some(Marker::String(counter_style.marker(index as i64 + 1)));
+    }
+
     Some(match list_style_type {
-        ListStyleType::LowerAlpha => {
-            let mut marker = String::new();
-            build_alpha_marker(index, &mut marker);
-            Marker::String(format!("{marker}. "))
-        }
-        ListStyleType::UpperAlpha => {
-            let mut marker = String::new();
-            build_alpha_marker(index, &mut marker);
-            Marker::String(format!("{}. ", marker.to_ascii_uppercase()))
-        }
-        ListStyleType::Decimal => Marker::String(format!("{}. ", index + 1)),
         ListStyleType::Disc => Marker::Char('•'),
         ListStyleType::Circle => Marker::Char('◦'),
         ListStyleType::Square => Marker::Char('▪'),
@@ -134,6 +133,53 @@ fn marker_for_style(list_style_type: ListStyleType, index: usize) -> Option<Mark
     })
 }
 
+// The counter style used for the markers of a list style type (if it isn't a bullet)
+fn counter_style(list_style_type: ListStyleType) -> Option<&'static CounterStyle> {
+    Some(match list_style_type {
+        ListStyleType::Decimal => &counter_style::DECIMAL,
+        ListStyleType::LowerAlpha => &counter_style::LOWER_ALPHA,
+        ListStyleType::UpperAlpha => &counter_style::UPPER_ALPHA,
+        ListStyleType::LowerGreek => &counter_style::LOWER_GREEK,
+        ListStyleType::ArabicIndic => &counter_style::ARABIC_INDIC,
+        ListStyleType::Persian => &counter_style::PERSIAN,
+        ListStyleType::Devanagari => &counter_style::DEVANAGARI,
+        ListStyleType::Bengali => &counter_style::BENGALI,
+        ListStyleType::Thai => &counter_style::THAI,
+        ListStyleType::CjkDecimal => &counter_style::CJK_DECIMAL,
+        ListStyleType::CjkEarthlyBranch => &counter_style::CJK_EARTHLY_BRANCH,
+        ListStyleType::CjkHeavenlyStem => &counter_style::CJK_HEAVENLY_STEM,
+        _ => return None,
+    })
+}
+
+// The counter style selected by the `type` attribute of a list item (or of its `<ol>`).
+//
+// Stylo only supports the `list-style-type` values which Servo implements, which don't include roman
+// numerals, so the attribute is applied here rather than as a presentational hint. Like a
+// presentational hint, it doesn't override a `list-style-type` set by the author (which would
+// no longer compute to the default `decimal`).
+fn counter_style_for_type_attr(
+    doc: &BaseDocument,
+    node_id: usize,
+) -> Option<&'static CounterStyle> {
+    let node = &doc.nodes[node_id];
+    let type_attr = node.attr(local_name!("type")).or_else(|| {
+        let parent = node.parent.map(|id| &doc.nodes[id])?;
+        if !parent.data.is_element_with_tag_name(&local_name!("ol")) {
+            return None;
+        }
+        parent.attr(local_name!("type"))
+    })?;
+    Some(match type_attr {
+        "1" => &counter_style::DECIMAL,
+        "a" => &counter_style::LOWER_ALPHA,
+        "A" => &counter_style::UPPER_ALPHA,
+        "i" => &counter_style::LOWER_ROMAN,
+        "I" => &counter_style::UPPER_ROMAN,
+        _ => return None,
+    })
+}
+
 // Override the font to our specific bullet font when rendering bullets
 fn font_for_bullet_style(list_style_type: ListStyleType) -> Option<FontStack<'static>> {
     let bullet_font = Some("Bullet, monospace, sans-serif".into());
@@ -147,22 +193,6 @@ fn font_for_bullet_style(list_style_type: ListStyleType) -> Option<FontStack<'st
     }
 }
 
-const ALPHABET: [char; 26] = [
-    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
-    't', 'u', 'v', 'w', 'x', 'y', 'z',
-];
-
-// Construct alphanumeric marker from index, appending characters when index exceeds powers of 26
-fn build_alpha_marker(index: usize, str: &mut String) {
-    let rem = index % 26;
-    let sym = ALPHABET[rem];
-    str.insert(0, sym);
-    let rest = (index - rem) as i64 / 26 - 1;
-    if rest >= 0 {
-        build_alpha_marker(rest as usize, str);
-    }
-}
-
 #[test]
 fn test_marker_for_disc() {
     let result = marker_for_style(ListStyleType::Disc, 0);
@@ -200,3 +230,37 @@ fn test_marker_for_upper_alpha() {
     assert_eq!(result_extended_1, Some(Marker::String("AA. ".to_string())));
     assert_eq!(result_extended_2, Some(Marker::String("AB. ".to_string())));
 }
+
+#[test]
+fn test_ol_type_attribute_selects_roman_numerals() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (doc, items) = doc_with_body(800, 600, "", |mutr, body| {
+        let ol = mutr.create_element(qual_name!("ol", html), vec![]);
+        mutr.set_attribute(ol, qual_name!("type"), "i");
+        let items: Vec<usize> = (0..3)
+            .map(|_| {
+                let li = mutr.create_element(qual_name!("li", html), vec![]);
+                let text = mutr.create_text_node("item");
+                mutr.append_children(li, &[text]);
+                li
+            })
+            .collect();
+        mutr.append_children(ol, &items);
+        mutr.append_children(body, &[ol]);
+        items
+    });
+
+    let markers: Vec<Marker> = items
+        .iter()
+        .map(|id| {
+            let element_data = doc.nodes[*id].element_data().unwrap();
+            element_data.list_item_data.as_ref().unwrap().marker.clone()
+        })
+        .collect();
+    assert_eq!(
+        markers,
+        ["i. ", "ii. ", "iii. "].map(|marker| Marker::String(marker.to_string()))
+    );
+}
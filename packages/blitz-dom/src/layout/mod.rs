@@ -13,13 +13,14 @@ use style::Atom;
 use style::values::computed::CSSPixelLength;
 use style::values::computed::length_percentage::CalcLengthPercentage;
 use taffy::{
-    BlockContext, CollapsibleMarginSet, FlexDirection, LayoutPartialTree, NodeId, ResolveOrZero,
-    RoundTree, Style, TraversePartialTree, TraverseTree, compute_block_layout,
+    BlockContext, CollapsibleMarginSet, DetailedGridInfo, FlexDirection, LayoutPartialTree, NodeId,
+    ResolveOrZero, RoundTree, Style, TraversePartialTree, TraverseTree, compute_block_layout,
     compute_cached_layout, compute_flexbox_layout, compute_grid_layout, compute_leaf_layout,
     prelude::*,
 };
 
 pub(crate) mod construct;
+pub(crate) mod counter_style;
 pub(crate) mod damage;
 pub(crate) mod inline;
 pub(crate) mod list;
@@ -152,12 +153,22 @@ impl BaseDocument {
                             );
                         }
                         None | Some("text" | "password" | "email" | "tel" | "url" | "search") => {
+                            // The `size` attribute sets the width of the field in characters. Like the
+                            // rest of the intrinsic size, it is clamped by `min-width` / `max-width`
+                            // (which `compute_leaf_layout` applies to the measured size).
+                            let size = element_data
+                                .attr(local_name!("size"))
+                                .and_then(|val| val.parse::<u32>().ok())
+                                .filter(|size| *size > 0);
+
                             return compute_leaf_layout(
                                 inputs,
                                 &node.style,
                                 resolve_calc_value,
                                 |_known_size, _available_space| taffy::Size {
-                                    width: 300.0,
+                                    width: size
+                                        .map(|size| size as f32 * font_size.unwrap_or(16.0) * 0.6)
+                                        .unwrap_or(300.0),
                                     height: resolved_line_height.unwrap_or(16.0),
                                 },
                             );
@@ -183,6 +194,17 @@ impl BaseDocument {
                             .and_then(|val| val.parse::<f32>().ok()),
                     };
 
+                    // Images with no image to display are sized to fit their alt text (if any)
+                    let alt_text_size = element_data
+                        .alt_text_layout
+                        .as_ref()
+                        .filter(|_| element_data.has_missing_image())
+                        .map(|layout| taffy::Size {
+                            width: layout.full_width() / layout.scale(),
+                            height: layout.height() / layout.scale(),
+                        })
+                        .unwrap_or(taffy::Size::ZERO);
+
                     // Get image's native sizespecial_data
                     let inherent_size = match &element_data.special_data {
                         SpecialElementData::Image(image_data) => match &**image_data {
@@ -198,10 +220,10 @@ impl BaseDocument {
                                     height: size.height(),
                                 }
                             }
-                            ImageData::None => taffy::Size::ZERO,
+                            ImageData::None => alt_text_size,
                         },
                         SpecialElementData::Canvas(_) => taffy::Size::ZERO,
-                        SpecialElementData::None => taffy::Size::ZERO,
+                        SpecialElementData::None => alt_text_size,
                         _ => unreachable!(),
                     };
 
@@ -436,6 +458,10 @@ impl taffy::LayoutGridContainer for BaseDocument {
     fn get_grid_child_style(&self, child_node_id: NodeId) -> Self::GridItemStyle<'_> {
         self.get_core_container_style(child_node_id)
     }
+
+    fn set_detailed_grid_info(&mut self, node_id: NodeId, detailed_grid_info: DetailedGridInfo) {
+        self.node_from_id_mut(node_id).grid_info = Some(Box::new(detailed_grid_info));
+    }
 }
 
 impl RoundTree for BaseDocument {
@@ -506,3 +532,28 @@ impl Iterator for RefCellChildIter<'_> {
         })
     }
 }
+
+#[test]
+fn text_input_size_is_clamped_by_max_width() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (doc, [wide, capped]) = doc_with_body(800, 600, "", |mutr, body| {
+        let wide = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(wide, qual_name!("size"), "40");
+        mutr.set_attribute(wide, qual_name!("style"), "box-sizing: border-box");
+        let capped = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(capped, qual_name!("size"), "40");
+        mutr.set_attribute(
+            capped,
+            qual_name!("style"),
+            "box-sizing: border-box; max-width: 100px",
+        );
+        mutr.append_children(body, &[wide, capped]);
+        [wide, capped]
+    });
+
+    // Without a constraint, 40 characters is much wider than 100px
+    assert!(doc.get_node(wide).unwrap().final_layout.size.width > 100.0);
+    assert_eq!(doc.get_node(capped).unwrap().final_layout.size.width, 100.0);
+}
@@ -1,6 +1,7 @@
 use std::{ops::Range, sync::Arc};
 
 use markup5ever::local_name;
+use style::computed_values::visibility::T as Visibility;
 use style::values::specified::box_::{DisplayInside, DisplayOutside};
 use style::{Atom, computed_values::table_layout::T as TableLayout};
 use taffy::{
@@ -82,7 +83,23 @@ pub(crate) fn build_table_context(
         .into_iter()
         .map(|dim| TrackSizingFunction::from(dim).into())
         .collect();
-    style.grid_template_rows = vec![style_helpers::auto(); row as usize];
+    // Rows with `visibility: collapse` are removed from the table (their cells still contribute to
+    // the widths of columns, but the row takes up no space)
+    //
+    // TODO: also remove the `border-spacing` around collapsed rows, and support collapsing columns
+    style.grid_template_rows = items
+        .iter()
+        .filter(|item| item.kind == TableItemKind::Row)
+        .map(|item| {
+            let is_collapsed = doc.nodes[item.node_id]
+                .primary_styles()
+                .is_some_and(|style| style.clone_visibility() == Visibility::Collapse);
+            match is_collapsed {
+                true => style_helpers::zero(),
+                false => style_helpers::auto(),
+            }
+        })
+        .collect();
 
     let layout_children = items
         .iter()
@@ -333,3 +350,43 @@ impl taffy::LayoutGridContainer for TableTreeWrapper<'_> {
         &self.ctx.items[usize::from(child_node_id)].style
     }
 }
+
+#[test]
+fn collapsed_table_rows_take_up_no_space() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    // Lays out a table with three rows, giving the middle row `visibility` and returning the
+    // position of each row's cell
+    let cell_positions = |visibility: &str| {
+        let (doc, cells) = doc_with_body(800, 600, "", |mutr, body| {
+            let table = mutr.create_element(qual_name!("table", html), vec![]);
+            mutr.set_attribute(table, qual_name!("style"), "border-spacing: 0");
+            let mut cells = Vec::new();
+            for idx in 0..3 {
+                let row = mutr.create_element(qual_name!("tr", html), vec![]);
+                if idx == 1 {
+                    let style = format!("visibility: {visibility}");
+                    mutr.set_attribute(row, qual_name!("style"), &style);
+                }
+                let cell = mutr.create_element(qual_name!("td", html), vec![]);
+                let style = "padding: 0; width: 50px; height: 20px";
+                mutr.set_attribute(cell, qual_name!("style"), style);
+                mutr.append_children(row, &[cell]);
+                mutr.append_children(table, &[row]);
+                cells.push(cell);
+            }
+            mutr.append_children(body, &[table]);
+            cells
+        });
+
+        cells
+            .iter()
+            .map(|id| doc.nodes[*id].final_layout.location.y)
+            .collect::<Vec<_>>()
+    };
+
+    // A hidden row leaves a gap, whereas a collapsed row is removed
+    assert_eq!(cell_positions("hidden"), [0.0, 20.0, 40.0]);
+    assert_eq!(cell_positions("collapse")[2], 20.0);
+}
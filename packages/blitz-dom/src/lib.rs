@@ -41,7 +41,9 @@ mod document;
 pub mod node;
 
 mod config;
+mod datalist;
 mod debug;
+mod dialog;
 mod events;
 mod font_metrics;
 mod form;
@@ -49,8 +51,11 @@ mod html;
 /// Integration of taffy and the DOM.
 mod layout;
 mod mutator;
+mod pagination;
 mod query_selector;
 mod resolve;
+mod scroll_into_view;
+mod scroll_snap;
 /// Implementations that interact with servo's style engine
 mod stylo;
 mod stylo_to_cursor_icon;
@@ -58,13 +63,20 @@ mod stylo_to_parley;
 mod traversal;
 mod url;
 
+/// CSS box geometry (border radii, border edges and outlines) as kurbo paths.
+pub mod kurbo_css;
 pub mod net;
 pub mod util;
 
+/// Helpers for building documents in tests
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
 #[cfg(feature = "accessibility")]
 mod accessibility;
 
 pub use config::DocumentConfig;
+pub use datalist::{DATALIST_OPTION_HEIGHT, DatalistOption, DatalistPopup};
 pub use document::{BaseDocument, Document};
 pub use markup5ever::{
     LocalName, Namespace, NamespaceStaticSet, Prefix, PrefixStaticSet, QualName, local_name,
@@ -73,6 +85,7 @@ pub use markup5ever::{
 pub use mutator::DocumentMutator;
 pub use node::{Attribute, ElementData, Node, NodeData, TextNodeData};
 pub use parley::FontContext;
+pub use scroll_into_view::{ScrollBehavior, ScrollIntoViewOptions, ScrollLogicalPosition};
 pub use style::Atom;
 pub use style::invalidation::element::restyle_hints::RestyleHint;
 pub type SelectorList = selectors::SelectorList<style::selector_parser::SelectorImpl>;
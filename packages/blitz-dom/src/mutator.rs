@@ -36,6 +36,7 @@ enum SpecialOp {
     UnloadStylesheet(usize),
     LoadCustomPaintSource(usize),
     ProcessButtonInput(usize),
+    UpdateBaseElement,
 }
 
 pub struct DocumentMutator<'doc> {
@@ -199,15 +200,24 @@ impl DocumentMutator<'_> {
 
     pub fn set_attribute(&mut self, node_id: usize, name: QualName, value: &str) {
         self.doc.snapshot_node(node_id);
+        self.doc.snapshot_attr_change(node_id, &name.local);
+
+        // The `style` attribute only affects the element's own declarations, so only the element needs
+        // restyling (Stylo restyles its descendants too if an inherited property changed). Selectors which
+        // match on the attribute itself are invalidated using the snapshot.
+        let is_style_attr = name.local == local_name!("style");
 
         let node = &mut self.doc.nodes[node_id];
         if let Some(data) = &mut *node.stylo_element_data.borrow_mut() {
-            data.hint |= RestyleHint::restyle_subtree();
+            data.hint |= match is_style_attr {
+                true => RestyleHint::RESTYLE_STYLE_ATTRIBUTE,
+                false => RestyleHint::restyle_subtree(),
+            };
             data.damage.insert(ALL_DAMAGE);
         }
 
         // TODO: make this fine grained / conditional based on ElementSelectorFlags
-        let parent = node.parent;
+        let parent = node.parent.filter(|_| !is_style_attr);
         if let Some(parent_id) = parent {
             let parent = &mut self.doc.nodes[parent_id];
             if let Some(data) = &mut *parent.stylo_element_data.borrow_mut() {
@@ -226,10 +236,31 @@ impl DocumentMutator<'_> {
         let tag = &element.name.local;
         let attr = &name.local;
 
+        // Changes to these attributes may change the input's validity
+        if *tag == local_name!("input")
+            && matches!(
+                *attr,
+                local_name!("type")
+                    | local_name!("value")
+                    | local_name!("min")
+                    | local_name!("max")
+                    | local_name!("step")
+            )
+        {
+            self.form_nodes.insert(node_id);
+        }
+
         if *attr == local_name!("id") {
             element.id = Some(Atom::from(value))
         }
 
+        if matches!(
+            *attr,
+            local_name!("tabindex") | local_name!("href") | local_name!("disabled")
+        ) {
+            element.flush_is_focussable();
+        }
+
         if *attr == local_name!("value") {
             if let Some(input_data) = element.text_input_data_mut() {
                 // Update text input value
@@ -261,6 +292,10 @@ impl DocumentMutator<'_> {
             self.load_custom_paint_src(node_id);
         } else if (tag, attr) == tag_and_attr!("link", "href") {
             self.load_linked_stylesheet(node_id);
+        } else if (tag, attr) == tag_and_attr!("base", "href")
+            || (tag, attr) == tag_and_attr!("base", "target")
+        {
+            self.doc.update_base_element();
         }
     }
 
@@ -271,7 +306,10 @@ impl DocumentMutator<'_> {
 
         let mut stylo_element_data = node.stylo_element_data.borrow_mut();
         if let Some(data) = &mut *stylo_element_data {
-            data.hint |= RestyleHint::restyle_subtree();
+            data.hint |= match name.local == local_name!("style") {
+                true => RestyleHint::RESTYLE_STYLE_ATTRIBUTE,
+                false => RestyleHint::restyle_subtree(),
+            };
             data.damage.insert(ALL_DAMAGE);
         }
         drop(stylo_element_data);
@@ -290,6 +328,13 @@ impl DocumentMutator<'_> {
             element.id = None;
         }
 
+        if matches!(
+            name.local,
+            local_name!("tabindex") | local_name!("href") | local_name!("disabled")
+        ) {
+            element.flush_is_focussable();
+        }
+
         // Update text input value
         if name.local == local_name!("value") {
             if let Some(input_data) = element.text_input_data_mut() {
@@ -309,6 +354,10 @@ impl DocumentMutator<'_> {
             self.recompute_is_animating = true;
         } else if (tag, attr) == tag_and_attr!("link", "href") {
             self.unload_stylesheet(node_id);
+        } else if (tag, attr) == tag_and_attr!("base", "href")
+            || (tag, attr) == tag_and_attr!("base", "target")
+        {
+            self.doc.update_base_element();
         }
     }
 
@@ -329,6 +378,7 @@ impl DocumentMutator<'_> {
             let parent = &mut self.doc.nodes[parent_id];
             parent.insert_damage(ALL_DAMAGE);
             parent.children.retain(|id| *id != node_id);
+            self.invalidate_child_list(parent_id);
             self.maybe_record_node(parent_id);
         }
 
@@ -354,16 +404,8 @@ impl DocumentMutator<'_> {
         if let Some(parent_id) = node.as_ref().and_then(|node| node.parent) {
             let parent = &mut self.doc.nodes[parent_id];
             parent.insert_damage(ALL_DAMAGE);
-            let parent_is_in_doc = parent.flags.is_in_document();
-
-            // TODO: make this fine grained / conditional based on ElementSelectorFlags
-            if parent_is_in_doc {
-                if let Some(data) = &mut *parent.stylo_element_data.borrow_mut() {
-                    data.hint |= RestyleHint::restyle_subtree();
-                }
-            }
-
             parent.children.retain(|id| *id != node_id);
+            self.invalidate_child_list(parent_id);
             self.maybe_record_node(parent_id);
         }
 
@@ -371,17 +413,9 @@ impl DocumentMutator<'_> {
     }
 
     pub fn remove_and_drop_all_children(&mut self, node_id: usize) {
-        let parent = &mut self.doc.nodes[node_id];
-        let parent_is_in_doc = parent.flags.is_in_document();
-
-        // TODO: make this fine grained / conditional based on ElementSelectorFlags
-        if parent_is_in_doc {
-            if let Some(data) = &mut *parent.stylo_element_data.borrow_mut() {
-                data.hint |= RestyleHint::restyle_subtree();
-            }
-        }
+        self.invalidate_child_list(node_id);
 
-        let children = mem::take(&mut parent.children);
+        let children = mem::take(&mut self.doc.nodes[node_id].children);
         for child_id in children {
             self.process_removed_subtree(child_id);
             let _ = self.remove_node_ignoring_parent(child_id);
@@ -424,15 +458,8 @@ impl DocumentMutator<'_> {
         let new_parent = &mut self.doc.nodes[parent_id];
         new_parent.insert_damage(ALL_DAMAGE);
         let new_parent_is_in_doc = new_parent.flags.is_in_document();
-
-        // TODO: make this fine grained / conditional based on ElementSelectorFlags
-        if new_parent_is_in_doc {
-            if let Some(data) = &mut *new_parent.stylo_element_data.borrow_mut() {
-                data.hint |= RestyleHint::restyle_subtree();
-            }
-        }
-
         insert_children_fn(new_parent, child_ids);
+        self.invalidate_child_list(parent_id);
 
         for child_id in child_ids.iter().copied() {
             let child = &mut self.doc.nodes[child_id];
@@ -446,15 +473,8 @@ impl DocumentMutator<'_> {
             if let Some(old_parent_id) = old_parent_id {
                 let old_parent = &mut self.doc.nodes[old_parent_id];
                 old_parent.insert_damage(ALL_DAMAGE);
-
-                // TODO: make this fine grained / conditional based on ElementSelectorFlags
-                if child_was_in_doc {
-                    if let Some(data) = &mut *old_parent.stylo_element_data.borrow_mut() {
-                        data.hint |= RestyleHint::restyle_subtree();
-                    }
-                }
-
                 old_parent.children.retain(|id| *id != child_id);
+                self.invalidate_child_list(old_parent_id);
                 self.maybe_record_node(old_parent_id);
             }
         }
@@ -475,6 +495,7 @@ impl DocumentMutator<'_> {
 
     pub fn reparent_children(&mut self, old_parent_id: usize, new_parent_id: usize) {
         let child_ids = std::mem::take(&mut self.doc.nodes[old_parent_id].children);
+        self.invalidate_child_list(old_parent_id);
         self.maybe_record_node(old_parent_id);
         self.append_children(new_parent_id, &child_ids);
     }
@@ -503,6 +524,8 @@ impl<'doc> DocumentMutator<'doc> {
 
         for id in self.form_nodes.drain() {
             self.doc.reset_form_owner(id);
+            self.doc
+                .snapshot_node_and(id, |node| node.update_validity());
         }
 
         #[cfg(feature = "autofocus")]
@@ -530,6 +553,7 @@ impl<'doc> DocumentMutator<'doc> {
                 SpecialOp::UnloadStylesheet(node_id) => self.unload_stylesheet(node_id),
                 SpecialOp::LoadCustomPaintSource(node_id) => self.load_custom_paint_src(node_id),
                 SpecialOp::ProcessButtonInput(node_id) => self.process_button_input(node_id),
+                SpecialOp::UpdateBaseElement => self.doc.update_base_element(),
             }
         }
 
@@ -556,6 +580,7 @@ impl<'doc> DocumentMutator<'doc> {
             let tag = element.name.local.as_ref();
             match tag {
                 "title" => self.title_node = Some(node_id),
+                "base" => self.eager_op_queue.push(SpecialOp::UpdateBaseElement),
                 "link" => self.eager_op_queue.push(SpecialOp::LoadStylesheet(node_id)),
                 "img" => self.eager_op_queue.push(SpecialOp::LoadImage(node_id)),
                 "canvas" => self
@@ -592,6 +617,9 @@ impl<'doc> DocumentMutator<'doc> {
             let node = &mut doc.nodes[node_id];
             node.flags.set(NodeFlags::IS_IN_DOCUMENT, false);
 
+            // Elements which are removed from the document are also removed from the top layer
+            doc.top_layer.retain(|id| *id != node_id);
+
             // If the node has an "id" attribute remove it from the ID map.
             if let Some(id_attr) = node.attr(local_name!("id")) {
                 doc.nodes_to_id.remove(id_attr);
@@ -601,6 +629,10 @@ impl<'doc> DocumentMutator<'doc> {
                 return;
             };
 
+            if element.name.local == local_name!("base") {
+                self.eager_op_queue.push(SpecialOp::UpdateBaseElement);
+            }
+
             match &element.special_data {
                 SpecialElementData::Stylesheet(_) => self
                     .eager_op_queue
@@ -621,6 +653,23 @@ impl<'doc> DocumentMutator<'doc> {
         self.flush_eager_ops();
     }
 
+    /// Restyle the children of a node after children have been added to or removed from it. Structural
+    /// pseudo-classes (such as `:first-child`, `:last-child` and `:nth-child()`) and sibling combinators
+    /// may match the remaining children differently now that their positions have changed, and
+    /// `:empty` may match the node itself differently.
+    ///
+    /// TODO: make this fine grained / conditional based on ElementSelectorFlags (only restyling
+    /// the siblings which are affected)
+    fn invalidate_child_list(&mut self, parent_id: usize) {
+        let parent = &self.doc.nodes[parent_id];
+        if !parent.flags.is_in_document() {
+            return;
+        }
+        if let Some(data) = &mut *parent.stylo_element_data.borrow_mut() {
+            data.hint |= RestyleHint::restyle_subtree();
+        }
+    }
+
     fn maybe_record_node(&mut self, node_id: impl Into<Option<usize>>) {
         let Some(node_id) = node_id.into() else {
             return;
@@ -814,3 +863,94 @@ impl Drop for ViewportMut<'_> {
         }
     }
 }
+
+#[test]
+fn nth_child_striping_updates_after_insert_and_remove() {
+    use crate::test_utils::doc_with_body;
+    use style::values::computed::Color;
+
+    let css = "tr:nth-child(even) { background-color: blue }";
+    let (mut doc, (tbody, rows)) = doc_with_body(800, 600, css, |mutr, body| {
+        let table = mutr.create_element(qual_name!("table", html), vec![]);
+        let tbody = mutr.create_element(qual_name!("tbody", html), vec![]);
+        let rows: Vec<usize> = (0..3)
+            .map(|_| {
+                let row = mutr.create_element(qual_name!("tr", html), vec![]);
+                let cell = mutr.create_element(qual_name!("td", html), vec![]);
+                mutr.append_children(row, &[cell]);
+                row
+            })
+            .collect();
+        mutr.append_children(tbody, &rows);
+        mutr.append_children(table, &[tbody]);
+        mutr.append_children(body, &[table]);
+        (tbody, rows)
+    });
+
+    let striped = |doc: &BaseDocument| -> Vec<bool> {
+        doc.nodes[tbody]
+            .children
+            .iter()
+            .map(|id| {
+                let styles = doc.nodes[*id].primary_styles().unwrap();
+                styles.clone_background_color() != Color::TRANSPARENT_BLACK
+            })
+            .collect()
+    };
+    assert_eq!(striped(&doc), [false, true, false]);
+
+    // Inserting a row at the start shifts the stripes of the existing rows
+    let mut mutr = doc.mutate();
+    let new_row = mutr.create_element(qual_name!("tr", html), vec![]);
+    mutr.insert_nodes_before(rows[0], &[new_row]);
+    drop(mutr);
+    doc.resolve(0.0);
+    assert_eq!(striped(&doc), [false, true, false, true]);
+    assert_eq!(doc.nodes[tbody].children[1], rows[0]);
+
+    // As does removing one
+    let mut mutr = doc.mutate();
+    mutr.remove_node(new_row);
+    drop(mutr);
+    doc.resolve(0.0);
+    assert_eq!(striped(&doc), [false, true, false]);
+
+    let mut mutr = doc.mutate();
+    mutr.remove_and_drop_node(rows[0]);
+    drop(mutr);
+    doc.resolve(0.0);
+    assert_eq!(striped(&doc), [false, true]);
+}
+
+#[test]
+fn style_attribute_changes_restyle_siblings() {
+    use crate::test_utils::doc_with_body;
+    use style::values::computed::Color;
+
+    let css = "div[style*=x] + p { background-color: blue }";
+    let (mut doc, [div, p]) = doc_with_body(800, 600, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        let p = mutr.create_element(qual_name!("p", html), vec![]);
+        mutr.append_children(body, &[div, p]);
+        [div, p]
+    });
+
+    let highlighted = |doc: &BaseDocument| {
+        let styles = doc.nodes[p].primary_styles().unwrap();
+        styles.clone_background_color() != Color::TRANSPARENT_BLACK
+    };
+    assert!(!highlighted(&doc));
+
+    // The sibling selector depends on the value of the div's `style` attribute
+    let mut mutr = doc.mutate();
+    mutr.set_attribute(div, qual_name!("style"), "--x: 1");
+    drop(mutr);
+    doc.resolve(0.0);
+    assert!(highlighted(&doc));
+
+    let mut mutr = doc.mutate();
+    mutr.clear_attribute(div, qual_name!("style"));
+    drop(mutr);
+    doc.resolve(0.0);
+    assert!(!highlighted(&doc));
+}
@@ -23,10 +23,12 @@ use crate::util::ImageType;
 #[derive(Clone, Debug)]
 pub enum Resource {
     Image(usize, ImageType, u32, u32, Arc<Vec<u8>>),
+    /// An SVG image, along with its source if it is a background image which refers to `currentColor`
     #[cfg(feature = "svg")]
-    Svg(usize, ImageType, Box<usvg::Tree>),
+    Svg(usize, ImageType, Box<usvg::Tree>, Option<Bytes>),
     Css(usize, DocumentStyleSheet),
-    Font(Bytes),
+    /// Font data, with the family name it should be registered under (if it was loaded by a `@font-face` rule)
+    Font(Option<String>, Bytes),
     Navigation {
         url: String,
         document: Bytes,
@@ -164,7 +166,7 @@ impl NetHandler<Resource> for CssHandler {
         )
     }
 }
-struct FontFaceHandler(FontFaceSourceFormatKeyword);
+struct FontFaceHandler(FontFaceSourceFormatKeyword, Option<String>);
 impl NetHandler<Resource> for FontFaceHandler {
     fn bytes(mut self: Box<Self>, doc_id: usize, bytes: Bytes, callback: SharedCallback<Resource>) {
         if self.0 == FontFaceSourceFormatKeyword::None && bytes.len() >= 4 {
@@ -241,7 +243,7 @@ impl NetHandler<Resource> for FontFaceHandler {
             _ => {}
         }
 
-        callback.call(doc_id, Ok(Resource::Font(bytes)))
+        callback.call(doc_id, Ok(Resource::Font(self.1, bytes)))
     }
 }
 
@@ -256,15 +258,27 @@ fn fetch_font_face(
         .rules(read_guard)
         .iter()
         .filter_map(|rule| match rule {
-            CssRule::FontFace(font_face) => font_face.read_with(read_guard).sources.as_ref(),
+            CssRule::FontFace(font_face) => {
+                let font_face = font_face.read_with(read_guard);
+                let family = font_face
+                    .family
+                    .as_ref()
+                    .map(|family| family.name.to_string());
+                Some((family, font_face.sources.as_ref()?))
+            }
             _ => None,
         })
-        .flat_map(|source_list| &source_list.0)
-        .filter_map(|source| match source {
-            Source::Url(url_source) => Some(url_source),
+        .flat_map(|(family, source_list)| {
+            source_list
+                .0
+                .iter()
+                .map(move |source| (family.clone(), source))
+        })
+        .filter_map(|(family, source)| match source {
+            Source::Url(url_source) => Some((family, url_source)),
             _ => None,
         })
-        .for_each(|url_source| {
+        .for_each(|(family, url_source)| {
             let mut format = match &url_source.format_hint {
                 Some(FontFaceSourceFormat::Keyword(fmt)) => *fmt,
                 Some(FontFaceSourceFormat::String(str)) => match str.as_str() {
@@ -298,7 +312,11 @@ fn fetch_font_face(
                 return;
             }
             let url = url_source.url.url().unwrap().as_ref().clone();
-            network_provider.fetch(doc_id, Request::get(url), Box::new(FontFaceHandler(format)))
+            network_provider.fetch(
+                doc_id,
+                Request::get(url),
+                Box::new(FontFaceHandler(format, family)),
+            )
         });
 }
 
@@ -332,9 +350,16 @@ impl NetHandler<Resource> for ImageHandler {
 
         #[cfg(feature = "svg")]
         {
-            use crate::util::parse_svg;
+            use crate::util::{parse_svg, svg_uses_current_color};
             if let Ok(tree) = parse_svg(&bytes) {
-                callback.call(doc_id, Ok(Resource::Svg(self.0, self.1, Box::new(tree))));
+                // Background images are re-parsed with the color of the element they are painted for
+                let source = (matches!(self.1, ImageType::Background(_))
+                    && svg_uses_current_color(&bytes))
+                .then_some(bytes);
+                callback.call(
+                    doc_id,
+                    Ok(Resource::Svg(self.0, self.1, Box::new(tree), source)),
+                );
                 return;
             }
         }
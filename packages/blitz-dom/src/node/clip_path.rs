@@ -0,0 +1,181 @@
+//! Resolving the `clip-path` of elements. This is shared by painting and hit testing so that the parts
+//! of an element which are clipped away can't be hit.
+
+use super::Node;
+use crate::kurbo_css::{CssBox, NonUniformRoundedRectRadii, create_css_rect};
+use kurbo::{BezPath, Circle, Ellipse, Insets, Point, Rect, Shape, Vec2};
+use style::properties::ComputedValues;
+use style::values::computed::{BorderCornerRadius, CSSPixelLength, LengthPercentage, Position};
+use style::values::generics::basic_shape::{
+    GenericBasicShape, GenericClipPath, GenericShapeRadius, ShapeBox, ShapeGeometryBox,
+};
+use style::values::generics::position::GenericPositionOrAuto;
+
+/// The tolerance used when converting curved clip shapes to paths
+const TOLERANCE: f64 = 0.1;
+
+impl Node {
+    /// The shape that the element is clipped to by its `clip-path` property (if any), in CSS pixels
+    /// relative to its border box.
+    ///
+    /// The shape is cached in [`clip_path_cache`](Self::clip_path_cache) until the element is
+    /// restyled or resized.
+    pub fn clip_path(&self) -> Option<BezPath> {
+        self.clip_path_cache.get_or_insert_with(self, || {
+            let style = self.primary_styles()?;
+            let frame = create_css_rect(&style, &self.final_layout, 1.0);
+            let margin = self.final_layout.margin.map(|m| m as f64);
+            let margin = Insets::new(margin.left, margin.top, margin.right, margin.bottom);
+            clip_path_from_styles(&style, &frame, margin, 1.0)
+        })
+    }
+}
+
+/// Resolve the `clip-path` property of an element to a path in the element's (scaled) coordinate space.
+///
+/// Only basic shapes and reference boxes are supported. Returns `None` if the element is not
+/// clipped (or its `clip-path` is not supported).
+///
+/// TODO: support `url()` references to SVG `<clipPath>` elements, `path()` and `shape()`, and the
+/// `evenodd` fill rule for polygons (clip layers are always filled using the nonzero rule).
+fn clip_path_from_styles(
+    style: &ComputedValues,
+    frame: &CssBox,
+    margin: Insets,
+    scale: f64,
+) -> Option<BezPath> {
+    let (shape, geometry_box) = match &style.get_svg().clip_path {
+        GenericClipPath::None | GenericClipPath::Url(_) => return None,
+        GenericClipPath::Shape(shape, geometry_box) => (Some(&**shape), geometry_box),
+        GenericClipPath::Box(geometry_box) => (None, geometry_box),
+    };
+
+    // SVG reference boxes (fill-box, stroke-box and view-box) fall back to the border box for HTML elements
+    let reference_box = match geometry_box {
+        ShapeGeometryBox::ShapeBox(ShapeBox::Margin) => frame.border_box + margin,
+        ShapeGeometryBox::ShapeBox(ShapeBox::PaddingBox) => frame.padding_box,
+        ShapeGeometryBox::ShapeBox(ShapeBox::ContentBox) => frame.content_box,
+        _ => frame.border_box,
+    };
+
+    let Some(shape) = shape else {
+        // A reference box on its own clips to that box (with the element's border radius for the border box)
+        return Some(match geometry_box {
+            ShapeGeometryBox::ShapeBox(ShapeBox::PaddingBox) => frame.padding_box_path(),
+            ShapeGeometryBox::ShapeBox(ShapeBox::ContentBox) => frame.content_box_path(),
+            ShapeGeometryBox::ShapeBox(ShapeBox::Margin) => reference_box.to_path(TOLERANCE),
+            _ => frame.border_box_path(),
+        });
+    };
+
+    // Lengths are in CSS pixels and percentages resolve against the (unscaled) reference box
+    let resolve = |length: &LengthPercentage, basis: f64| -> f64 {
+        length
+            .resolve(CSSPixelLength::new((basis / scale) as f32))
+            .px() as f64
+            * scale
+    };
+    let center = |position: &GenericPositionOrAuto<Position>| -> Point {
+        match position {
+            GenericPositionOrAuto::Position(position) => Point {
+                x: reference_box.x0 + resolve(&position.horizontal, reference_box.width()),
+                y: reference_box.y0 + resolve(&position.vertical, reference_box.height()),
+            },
+            GenericPositionOrAuto::Auto => reference_box.center(),
+        }
+    };
+    // The distances from the center to the closest and farthest horizontal and vertical sides
+    let side_distances = |center: Point| -> (Vec2, Vec2) {
+        let x = (center.x - reference_box.x0).abs();
+        let x2 = (reference_box.x1 - center.x).abs();
+        let y = (center.y - reference_box.y0).abs();
+        let y2 = (reference_box.y1 - center.y).abs();
+        (
+            Vec2::new(x.min(x2), y.min(y2)),
+            Vec2::new(x.max(x2), y.max(y2)),
+        )
+    };
+
+    let path = match shape {
+        GenericBasicShape::Rect(inset) => {
+            let rect = Rect::new(
+                reference_box.x0 + resolve(&inset.rect.3, reference_box.width()),
+                reference_box.y0 + resolve(&inset.rect.0, reference_box.height()),
+                reference_box.x1 - resolve(&inset.rect.1, reference_box.width()),
+                reference_box.y1 - resolve(&inset.rect.2, reference_box.height()),
+            );
+            let rect = rect.abs();
+            let resolve_radii = |radius: &BorderCornerRadius| -> Vec2 {
+                Vec2 {
+                    x: resolve(&radius.0.width.0, rect.width()),
+                    y: resolve(&radius.0.height.0, rect.height()),
+                }
+            };
+            let radii = NonUniformRoundedRectRadii {
+                top_left: resolve_radii(&inset.round.top_left),
+                top_right: resolve_radii(&inset.round.top_right),
+                bottom_right: resolve_radii(&inset.round.bottom_right),
+                bottom_left: resolve_radii(&inset.round.bottom_left),
+            };
+            CssBox::new(rect, Insets::ZERO, Insets::ZERO, 0.0, radii).border_box_path()
+        }
+        GenericBasicShape::Circle(circle) => {
+            let center = center(&circle.position);
+            let (closest, farthest) = side_distances(center);
+            let radius = match &circle.radius {
+                GenericShapeRadius::Length(length) => {
+                    // Percentages resolve against the normalized diagonal of the reference box
+                    let basis = reference_box.width().hypot(reference_box.height())
+                        / std::f64::consts::SQRT_2;
+                    resolve(&length.0, basis)
+                }
+                GenericShapeRadius::ClosestSide => closest.x.min(closest.y),
+                GenericShapeRadius::FarthestSide => farthest.x.max(farthest.y),
+            };
+            Circle::new(center, radius).to_path(TOLERANCE)
+        }
+        GenericBasicShape::Ellipse(ellipse) => {
+            let center = center(&ellipse.position);
+            let (closest, farthest) = side_distances(center);
+            let semiaxis = |radius: &GenericShapeRadius<_>, basis, closest, farthest| match radius {
+                GenericShapeRadius::Length(length) => resolve(&length.0, basis),
+                GenericShapeRadius::ClosestSide => closest,
+                GenericShapeRadius::FarthestSide => farthest,
+            };
+            let radii = Vec2 {
+                x: semiaxis(
+                    &ellipse.semiaxis_x,
+                    reference_box.width(),
+                    closest.x,
+                    farthest.x,
+                ),
+                y: semiaxis(
+                    &ellipse.semiaxis_y,
+                    reference_box.height(),
+                    closest.y,
+                    farthest.y,
+                ),
+            };
+            Ellipse::new(center, radii, 0.0).to_path(TOLERANCE)
+        }
+        GenericBasicShape::Polygon(polygon) => {
+            let mut path = BezPath::new();
+            for (idx, coord) in polygon.coordinates.iter().enumerate() {
+                let point = Point {
+                    x: reference_box.x0 + resolve(&coord.0, reference_box.width()),
+                    y: reference_box.y0 + resolve(&coord.1, reference_box.height()),
+                };
+                if idx == 0 {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+            path.close_path();
+            path
+        }
+        GenericBasicShape::PathOrShape(_) => return None,
+    };
+
+    Some(path)
+}
@@ -1,13 +1,19 @@
+#[cfg(feature = "svg")]
+use blitz_traits::net::Bytes;
 use cssparser::ParserInput;
 use linebender_resource_handle::Blob;
 use markup5ever::{LocalName, QualName, local_name};
-use parley::{ContentWidths, FontContext, LayoutContext};
+use parley::{ContentWidths, FontContext, LayoutContext, StyleProperty};
 use selectors::matching::QuirksMode;
+#[cfg(feature = "svg")]
+use std::cell::{Ref, RefCell};
 use std::str::FromStr;
 use std::sync::Arc;
 use style::Atom;
 use style::parser::ParserContext;
-use style::properties::{Importance, PropertyDeclaration, PropertyId, SourcePropertyDeclaration};
+use style::properties::{
+    ComputedValues, Importance, PropertyDeclaration, PropertyId, SourcePropertyDeclaration,
+};
 use style::stylesheets::{DocumentStyleSheet, Origin, UrlExtraData};
 use style::{
     properties::{PropertyDeclarationBlock, parse_style_attribute},
@@ -20,6 +26,8 @@ use url::Url;
 
 use super::{Attribute, Attributes};
 use crate::layout::table::TableContext;
+#[cfg(feature = "svg")]
+use crate::util::{Color, parse_svg_with_current_color};
 
 #[derive(Debug, Clone)]
 pub struct ElementData {
@@ -32,9 +40,13 @@ pub struct ElementData {
     /// The element's attributes
     pub attrs: Attributes,
 
-    /// Whether the element is focussable
+    /// Whether the element is focussable (by clicking it or programmatically)
     pub is_focussable: bool,
 
+    /// Whether the element is reachable by sequential (Tab key) focus navigation.
+    /// Elements with a negative `tabindex` are focussable but not sequentially focussable.
+    pub is_sequentially_focussable: bool,
+
     /// The element's parsed style attribute (used by stylo)
     pub style_attribute: Option<ServoArc<Locked<PropertyDeclarationBlock>>>,
 
@@ -54,6 +66,9 @@ pub struct ElementData {
     /// does not exclude inline_layout_data
     pub list_item_data: Option<Box<ListItemLayout>>,
 
+    /// The laid out `alt` text of an \<img\> element (only set if it has no image to display)
+    pub alt_text_layout: Option<Box<parley::Layout<TextBrush>>>,
+
     /// The element's template contents (\<template\> elements only)
     pub template_contents: Option<usize>,
     // /// Whether the node is a [HTML integration point] (https://html.spec.whatwg.org/multipage/#html-integration-point)
@@ -116,9 +131,11 @@ impl ElementData {
             id: id_attr_atom,
             attrs: Attributes::new(attrs),
             is_focussable: false,
+            is_sequentially_focussable: false,
             style_attribute: Default::default(),
             inline_layout_data: None,
             list_item_data: None,
+            alt_text_layout: None,
             special_data: SpecialElementData::None,
             template_contents: None,
             background_images: Vec::new(),
@@ -167,6 +184,15 @@ impl ElementData {
         }
     }
 
+    /// Whether the element is an \<img\> (or other replaced element) with no image to display
+    pub fn has_missing_image(&self) -> bool {
+        match &self.special_data {
+            SpecialElementData::Image(data) => matches!(**data, ImageData::None),
+            SpecialElementData::None => true,
+            _ => false,
+        }
+    }
+
     pub fn raster_image_data_mut(&mut self) -> Option<&mut RasterImageData> {
         match self.image_data_mut()? {
             ImageData::Raster(data) => Some(data),
@@ -247,7 +273,7 @@ impl ElementData {
 
         self.is_focussable = !disabled
             && match tabindex {
-                Some(index) => index >= 0,
+                Some(_) => true,
                 None => {
                     // Some focusable HTML elements have a default tabindex value of 0 set under the hood by the user agent.
                     // These elements are:
@@ -269,7 +295,9 @@ impl ElementData {
                         DEFAULT_FOCUSSABLE_ELEMENTS.contains(&self.name.local)
                     }
                 }
-            }
+            };
+        self.is_sequentially_focussable =
+            self.is_focussable && tabindex.is_none_or(|index| index >= 0);
     }
 
     pub fn flush_style_attribute(&mut self, guard: &SharedRwLock, url_extra_data: &UrlExtraData) {
@@ -424,6 +452,13 @@ pub struct BackgroundImageData {
     pub status: Status,
     /// The image data
     pub image: ImageData,
+    /// The source of an SVG image which refers to `currentColor`, so that it can be re-parsed with
+    /// the color of the element it is painted for
+    #[cfg(feature = "svg")]
+    svg_source: Option<Bytes>,
+    /// The SVG image re-parsed for the element's color (and the color it was parsed for)
+    #[cfg(feature = "svg")]
+    recolored_svg: RefCell<Option<(Color, Box<usvg::Tree>)>>,
 }
 
 impl BackgroundImageData {
@@ -432,8 +467,57 @@ impl BackgroundImageData {
             url,
             status: Status::Loading,
             image: ImageData::None,
+            #[cfg(feature = "svg")]
+            svg_source: None,
+            #[cfg(feature = "svg")]
+            recolored_svg: RefCell::new(None),
         }
     }
+
+    /// The SVG image to paint for an element whose `color` is `current_color` (or `None` if the
+    /// image is not an SVG).
+    ///
+    /// Unlike inline `<svg>` elements, images don't inherit `color` from the document. But so that
+    /// monochrome icons can follow the color of the text around them, SVG backgrounds which refer to
+    /// `currentColor` are re-parsed with it resolved to `current_color`. The result is cached until
+    /// the color changes.
+    #[cfg(feature = "svg")]
+    pub fn svg_image(&self, current_color: Color) -> Option<Ref<'_, usvg::Tree>> {
+        let ImageData::Svg(svg) = &self.image else {
+            return None;
+        };
+
+        if let Some(source) = &self.svg_source {
+            let is_cached = matches!(
+                &*self.recolored_svg.borrow(),
+                Some((color, _)) if *color == current_color
+            );
+            if !is_cached {
+                let recolored = parse_svg_with_current_color(source, Some(current_color)).ok();
+                *self.recolored_svg.borrow_mut() =
+                    recolored.map(|tree| (current_color, Box::new(tree)));
+            }
+        }
+
+        Some(Ref::map(
+            self.recolored_svg.borrow(),
+            |recolored| match recolored {
+                Some((_, tree)) => &**tree,
+                // Images which don't refer to `currentColor` (or which failed to re-parse) are
+                // painted as they are
+                None => &**svg,
+            },
+        ))
+    }
+
+    /// Set the image to a (loaded) SVG image. `source` should be provided if it refers to `currentColor`.
+    #[cfg(feature = "svg")]
+    pub(crate) fn set_svg(&mut self, tree: Box<usvg::Tree>, source: Option<Bytes>) {
+        self.status = Status::Ok;
+        self.image = ImageData::Svg(tree);
+        self.svg_source = source;
+        self.recolored_svg = RefCell::new(None);
+    }
 }
 
 pub struct TextInputData {
@@ -441,6 +525,11 @@ pub struct TextInputData {
     pub editor: Box<parley::PlainEditor<TextBrush>>,
     /// Whether the input is a singleline or multiline input
     pub is_multiline: bool,
+    /// The laid out `placeholder` text, shown while the input is empty
+    pub placeholder: Option<Box<parley::Layout<TextBrush>>>,
+    /// The computed styles of the input's `::placeholder` pseudo-element, which the placeholder text
+    /// is laid out and painted with
+    pub placeholder_style: Option<ServoArc<ComputedValues>>,
 }
 
 // FIXME: Implement Clone for PlainEditor
@@ -456,9 +545,27 @@ impl TextInputData {
         Self {
             editor,
             is_multiline,
+            placeholder: None,
+            placeholder_style: None,
         }
     }
 
+    /// Apply the input's computed text styles to the editor. This affects the size of the text
+    /// as well as the caret and selection geometry (which span the full line height).
+    pub fn set_text_style(&mut self, parley_style: &parley::TextStyle<'static, TextBrush>) {
+        let styles = self.editor.edit_styles();
+        styles.retain(|_| false);
+        styles.insert(StyleProperty::FontSize(parley_style.font_size));
+        styles.insert(StyleProperty::LineHeight(parley_style.line_height));
+        styles.insert(StyleProperty::Brush(parley_style.brush));
+    }
+
+    /// Whether the input currently has no value (and should therefore display its placeholder)
+    pub fn is_empty(&self) -> bool {
+        // Inputs without a value are seeded with a single space so that they have a line to lay out
+        matches!(self.editor.raw_text(), "" | " ")
+    }
+
     pub fn set_text(
         &mut self,
         font_ctx: &mut FontContext,
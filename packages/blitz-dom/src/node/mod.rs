@@ -1,8 +1,11 @@
 #![allow(clippy::module_inception)]
 
 mod attributes;
+mod clip_path;
 mod element;
 mod node;
+mod paint_cache;
+mod transform;
 
 pub use attributes::{Attribute, Attributes};
 pub use element::{
@@ -11,3 +14,5 @@ pub use element::{
     Status, TextBrush, TextInputData, TextLayout,
 };
 pub use node::*;
+pub use paint_cache::PaintCache;
+pub use transform::flatten_transform;
@@ -1,15 +1,17 @@
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
 use bitflags::bitflags;
 use blitz_traits::events::{BlitzMouseButtonEvent, DomEventData, HitResult};
-use blitz_traits::shell::ShellProvider;
+use blitz_traits::shell::{ColorScheme, ShellProvider};
 use html_escape::encode_quoted_attribute_to_string;
 use keyboard_types::Modifiers;
+use kurbo::{BezPath, Shape as _};
 use markup5ever::{LocalName, local_name};
 use parley::Cluster;
 use selectors::matching::ElementSelectorFlags;
 use slab::Slab;
 use std::cell::{Cell, RefCell};
 use std::fmt::Write;
+use std::ops::ControlFlow;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use style::Atom;
@@ -20,17 +22,21 @@ use style::selector_parser::{PseudoElement, RestyleDamage};
 use style::stylesheets::UrlExtraData;
 use style::values::computed::Display as StyloDisplay;
 use style::values::specified::box_::{DisplayInside, DisplayOutside};
+use style::values::specified::color::ColorSchemeFlags;
 use style::{data::ElementData as StyloElementData, shared_lock::SharedRwLock};
 use style_dom::ElementState;
 use style_traits::values::ToCss;
 use taffy::{
-    Cache,
+    Cache, DetailedGridInfo,
     prelude::{Layout, Style},
 };
 
 use crate::layout::damage::HoistedPaintChildren;
 
-use super::{Attribute, ElementData};
+use super::{Attribute, ElementData, PaintCache, flatten_transform};
+
+/// The size (in CSS pixels) of the grip in the bottom-right corner of resizable elements
+pub const RESIZE_GRIP_SIZE: f32 = 12.0;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DisplayOuter {
@@ -117,7 +123,12 @@ pub struct Node {
     pub cache: Cache,
     pub unrounded_layout: Layout,
     pub final_layout: Layout,
+    /// The track sizes and item placement computed by the most recent layout of a grid container
+    pub grid_info: Option<Box<DetailedGridInfo>>,
     pub scroll_offset: crate::Point<f64>,
+    /// The shape of the element's `clip-path` (in CSS pixels relative to the border box), cached
+    /// between frames. See [`Node::clip_path`].
+    pub clip_path_cache: PaintCache<Option<BezPath>>,
 }
 
 unsafe impl Send for Node {}
@@ -159,7 +170,9 @@ impl Node {
             cache: Cache::new(),
             unrounded_layout: Layout::new(),
             final_layout: Layout::new(),
+            grid_info: None,
             scroll_offset: crate::Point::ZERO,
+            clip_path_cache: PaintCache::default(),
         }
     }
 
@@ -231,6 +244,13 @@ impl Node {
             .unwrap_or(false)
     }
 
+    pub fn is_sequentially_focussable(&self) -> bool {
+        self.data
+            .downcast_element()
+            .map(|el| el.is_sequentially_focussable)
+            .unwrap_or(false)
+    }
+
     pub fn set_restyle_hint(&self, hint: RestyleHint) {
         if let Some(element_data) = self.stylo_element_data.borrow_mut().as_mut() {
             element_data.hint.insert(hint);
@@ -294,9 +314,11 @@ impl Node {
         self.element_state.contains(ElementState::HOVER)
     }
 
-    pub fn focus(&mut self, shell_provider: Arc<dyn ShellProvider>) {
+    /// Focus this node. `focus_visible` determines whether it matches `:focus-visible`.
+    pub fn focus(&mut self, shell_provider: Arc<dyn ShellProvider>, focus_visible: bool) {
+        self.element_state.insert(ElementState::FOCUS);
         self.element_state
-            .insert(ElementState::FOCUS | ElementState::FOCUSRING);
+            .set(ElementState::FOCUSRING, focus_visible);
         self.set_restyle_hint(RestyleHint::restyle_subtree());
 
         // If focussing a text input, enable IME and set IME area
@@ -334,6 +356,35 @@ impl Node {
         self.element_state.contains(ElementState::FOCUS)
     }
 
+    /// Set whether this node contains the focussed node (the `:focus-within` pseudo-class)
+    pub fn set_focus_within(&mut self, focus_within: bool) {
+        self.element_state
+            .set(ElementState::FOCUS_WITHIN, focus_within);
+        self.set_restyle_hint(RestyleHint::restyle_subtree());
+    }
+
+    /// Set whether this node is the target of the document URL's fragment (the `:target` pseudo-class)
+    pub fn set_is_url_target(&mut self, is_target: bool) {
+        self.element_state.set(ElementState::URLTARGET, is_target);
+        self.set_restyle_hint(RestyleHint::restyle_subtree());
+    }
+
+    /// Update whether this node matches `:valid` or `:invalid` (see [`ElementData::validity`])
+    pub fn update_validity(&mut self) {
+        let validity = self.element_data().and_then(|element| element.validity());
+        let mut state = self.element_state;
+        state.remove(ElementState::VALID | ElementState::INVALID);
+        match validity {
+            Some(true) => state.insert(ElementState::VALID),
+            Some(false) => state.insert(ElementState::INVALID),
+            None => {}
+        }
+        if state != self.element_state {
+            self.element_state = state;
+            self.set_restyle_hint(RestyleHint::restyle_subtree());
+        }
+    }
+
     pub fn active(&mut self) {
         self.element_state.insert(ElementState::ACTIVE);
         self.set_restyle_hint(RestyleHint::restyle_subtree());
@@ -682,6 +733,70 @@ impl Node {
         Some(&attr.value)
     }
 
+    /// The language of the node, as specified by the `lang` attribute of the node or its nearest
+    /// ancestor. An empty `lang` attribute means that the language is unknown.
+    pub fn lang(&self) -> Option<&str> {
+        let mut node = self;
+        loop {
+            if let Some(lang) = node.attr(local_name!("lang")) {
+                return (!lang.is_empty()).then_some(lang);
+            }
+            node = self.with(node.parent?);
+        }
+    }
+
+    /// Whether the node is [inert](https://html.spec.whatwg.org/multipage/interaction.html#inert-subtrees)
+    /// because it or one of its ancestors has the `inert` attribute. Inert nodes are still painted, but
+    /// they can't be hit, focussed or selected.
+    pub fn is_inert(&self) -> bool {
+        let mut node = self;
+        loop {
+            if node.data.has_attr(local_name!("inert")) {
+                return true;
+            }
+            match node.parent {
+                Some(parent_id) => node = self.with(parent_id),
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether the node is hidden by a `hidden="until-found"` attribute on itself or one of its
+    /// ancestors. Such nodes keep their place in layout, but aren't painted or hit (whatever the
+    /// `visibility` of their descendants) until they are revealed.
+    pub fn is_hidden_until_found(&self) -> bool {
+        let mut node = self;
+        loop {
+            if node
+                .attr(local_name!("hidden"))
+                .is_some_and(|value| value.eq_ignore_ascii_case("until-found"))
+            {
+                return true;
+            }
+            match node.parent {
+                Some(parent_id) => node = self.with(parent_id),
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether the user can resize the element by dragging the grip in its bottom-right corner
+    ///
+    /// TODO: support the `resize` property. Stylo only implements it for the Gecko engine, so for
+    /// now only textareas are resizable (matching the `resize: both` that browsers give them).
+    pub fn is_resizable(&self) -> bool {
+        self.data.is_element_with_tag_name(&local_name!("textarea"))
+            && !self.data.has_attr(local_name!("disabled"))
+    }
+
+    /// Whether a position (relative to the node's border box) is within the node's resize grip
+    pub fn is_within_resize_grip(&self, x: f32, y: f32) -> bool {
+        let size = self.final_layout.size;
+        self.is_resizable()
+            && (size.width - RESIZE_GRIP_SIZE..=size.width).contains(&x)
+            && (size.height - RESIZE_GRIP_SIZE..=size.height).contains(&y)
+    }
+
     pub fn primary_styles(&self) -> Option<AtomicRef<'_, ComputedValues>> {
         let stylo_element_data = self.stylo_element_data.borrow();
         if stylo_element_data
@@ -742,6 +857,25 @@ impl Node {
             .unwrap_or(0)
     }
 
+    /// The color scheme which the element's UA-provided chrome (form controls, the canvas background, etc)
+    /// is drawn with, given the user's `preferred` color scheme.
+    ///
+    /// Elements which support both schemes (`color-scheme: light dark`) follow the preference, while
+    /// `color-scheme: normal` (the default) is always drawn light.
+    pub fn used_color_scheme(&self, preferred: ColorScheme) -> ColorScheme {
+        let Some(style) = self.primary_styles() else {
+            return ColorScheme::Light;
+        };
+        let bits = style.clone_color_scheme().bits;
+        let supports_light = bits.contains(ColorSchemeFlags::LIGHT);
+        let supports_dark = bits.contains(ColorSchemeFlags::DARK);
+        match (supports_light, supports_dark) {
+            (true, true) => preferred,
+            (false, true) => ColorScheme::Dark,
+            _ => ColorScheme::Light,
+        }
+    }
+
     // https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_positioned_layout/Stacking_context#features_creating_stacking_contexts
     pub fn is_stacking_context_root(&self, is_flex_or_grid_item: bool) -> bool {
         let Some(style) = self.primary_styles() else {
@@ -784,22 +918,96 @@ impl Node {
     /// TODO: z-index
     /// (If multiple children are positioned at the position then a random one will be recursed into)
     pub fn hit(&self, x: f32, y: f32) -> Option<HitResult> {
+        let mut result = None;
+        self.visit_hits(x, y, &mut |hit| {
+            result = Some(hit);
+            ControlFlow::Break(())
+        });
+        result
+    }
+
+    /// Like [`hit`](Self::hit), but returns every node under the position rather than just the
+    /// topmost one. Nodes are returned in paint order (topmost first), so each node is followed by
+    /// its ancestors.
+    pub fn hit_all(&self, x: f32, y: f32) -> Vec<HitResult> {
+        let mut hits = Vec::new();
+        self.visit_hits(x, y, &mut |hit| {
+            hits.push(hit);
+            ControlFlow::Continue(())
+        });
+        hits
+    }
+
+    /// Calls `visit` with each node under the position in paint order (topmost first) until it
+    /// returns `ControlFlow::Break`.
+    fn visit_hits(
+        &self,
+        x: f32,
+        y: f32,
+        visit: &mut impl FnMut(HitResult) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
         use style::computed_values::visibility::T as Visibility;
+        use style::values::computed::Overflow;
 
-        // Don't hit on visbility:hidden elements
+        // Inert subtrees are skipped (as if they had `pointer-events: none`), so positions over them
+        // hit whatever is behind them. Positioned descendants are hoisted out of their parent into a
+        // stacking context, so all of the node's ancestors are checked (not just the node itself).
+        if self.is_inert() || self.is_hidden_until_found() {
+            return ControlFlow::Continue(());
+        }
+
+        let mut clips_content = false;
         if let Some(style) = self.primary_styles() {
+            // Don't hit on visbility:hidden elements
             if matches!(
                 style.clone_visibility(),
                 Visibility::Hidden | Visibility::Collapse
             ) {
-                return None;
+                return ControlFlow::Continue(());
             }
+
+            let box_style = style.get_box();
+            clips_content = box_style.overflow_x != Overflow::Visible
+                || box_style.overflow_y != Overflow::Visible;
         }
 
-        let mut x = x - self.final_layout.location.x + self.scroll_offset.x as f32;
-        let mut y = y - self.final_layout.location.y + self.scroll_offset.y as f32;
+        let mut x = x - self.final_layout.location.x;
+        let mut y = y - self.final_layout.location.y;
+        let untransformed = (x, y);
 
+        // Map the position into the element's own (untransformed) coordinate space
         let size = self.final_layout.size;
+        let border_box = kurbo::Rect::new(0.0, 0.0, size.width as f64, size.height as f64);
+        if let Some(transform) = flatten_transform(&self.transform_matrix(1.0), border_box) {
+            // Elements which are scaled down to nothing aren't painted, so they can't be hit
+            if transform.determinant() == 0.0 {
+                return ControlFlow::Continue(());
+            }
+            let point = transform.inverse() * kurbo::Point::new(x as f64, y as f64);
+            x = point.x as f32;
+            y = point.y as f32;
+        }
+
+        // Children which share the element's 3D rendering context include its transform in their own
+        // (see `Node::transform_matrix`), so they are hit tested without it
+        let children_offset = if self.preserves_3d() {
+            (untransformed.0 - x, untransformed.1 - y)
+        } else {
+            (0.0, 0.0)
+        };
+
+        x += self.scroll_offset.x as f32;
+        y += self.scroll_offset.y as f32;
+
+        // The parts of the element (and its descendants) which are clipped away by `clip-path` can't be hit
+        if let Some(clip_path) = self.clip_path() {
+            let border_box_x = x - self.scroll_offset.x as f32;
+            let border_box_y = y - self.scroll_offset.y as f32;
+            if !clip_path.contains(kurbo::Point::new(border_box_x as f64, border_box_y as f64)) {
+                return ControlFlow::Continue(());
+            }
+        }
+
         let matches_self = !(x < 0.0
             || x > size.width + self.scroll_offset.x as f32
             || y < 0.0
@@ -822,8 +1030,9 @@ impl Node {
             None => false,
         };
 
-        if !matches_self && !matches_content && !matches_hoisted_content {
-            return None;
+        // Content which overflows a clipping element is not painted, so it can't be hit either
+        if !matches_self && (clips_content || (!matches_content && !matches_hoisted_content)) {
+            return ControlFlow::Continue(());
         }
 
         if self.flags.is_inline_root() {
@@ -835,35 +1044,31 @@ impl Node {
             y -= content_box_offset.y;
         }
 
+        let (child_x, child_y) = (x + children_offset.0, y + children_offset.1);
+
         // Positive z_index hoisted children
         if matches_hoisted_content {
             if let Some(hoisted) = &self.stacking_context {
                 for hoisted_child in hoisted.pos_z_hoisted_children().rev() {
-                    let x = x - hoisted_child.position.x;
-                    let y = y - hoisted_child.position.y;
-                    if let Some(hit) = self.with(hoisted_child.node_id).hit(x, y) {
-                        return Some(hit);
-                    }
+                    let x = child_x - hoisted_child.position.x;
+                    let y = child_y - hoisted_child.position.y;
+                    self.with(hoisted_child.node_id).visit_hits(x, y, visit)?;
                 }
             }
         }
 
-        // Call `.hit()` on each child in turn. If any return `Some` then return that value. Else return `Some(self.id).
+        // Call `.visit_hits()` on each child in turn
         for child_id in self.paint_children.borrow().iter().flatten().rev() {
-            if let Some(hit) = self.with(*child_id).hit(x, y) {
-                return Some(hit);
-            }
+            self.with(*child_id).visit_hits(child_x, child_y, visit)?;
         }
 
         // Negative z_index hoisted children
         if matches_hoisted_content {
             if let Some(hoisted) = &self.stacking_context {
                 for hoisted_child in hoisted.neg_z_hoisted_children().rev() {
-                    let x = x - hoisted_child.position.x;
-                    let y = y - hoisted_child.position.y;
-                    if let Some(hit) = self.with(hoisted_child.node_id).hit(x, y) {
-                        return Some(hit);
-                    }
+                    let x = child_x - hoisted_child.position.x;
+                    let y = child_y - hoisted_child.position.y;
+                    self.with(hoisted_child.node_id).visit_hits(x, y, visit)?;
                 }
             }
         }
@@ -876,22 +1081,30 @@ impl Node {
 
             if let Some((cluster, _side)) = Cluster::from_point_exact(layout, x * scale, y * scale)
             {
-                let style_index = cluster.glyphs().next()?.style_index();
-                let node_id = layout.styles()[style_index].brush.id;
-                return Some(HitResult { node_id, x, y });
+                if let Some(glyph) = cluster.glyphs().next() {
+                    // The inline node which the text belongs to, followed by its inline ancestors
+                    let mut node_id = layout.styles()[glyph.style_index()].brush.id;
+                    while node_id != self.id {
+                        visit(HitResult { node_id, x, y })?;
+                        match self.with(node_id).parent {
+                            Some(parent_id) => node_id = parent_id,
+                            None => break,
+                        }
+                    }
+                }
             }
         }
 
         // Self (this node)
         if matches_self {
-            return Some(HitResult {
+            visit(HitResult {
                 node_id: self.id,
                 x,
                 y,
-            });
+            })?;
         }
 
-        None
+        ControlFlow::Continue(())
     }
 
     /// Computes the Document-relative coordinates of the Node
@@ -0,0 +1,110 @@
+//! Values computed from an element's styles and layout, kept between frames
+
+use std::cell::RefCell;
+
+use style::properties::ComputedValues;
+use style::servo_arc::Arc as ServoArc;
+use taffy::{Rect, Size};
+
+use super::Node;
+
+/// A value computed from an element's styles and layout (such as the path of its `clip-path`), kept
+/// between frames.
+///
+/// The value is reused until the element is restyled or its boxes are resized. Stylo only restyles
+/// the elements whose styles have changed (and their descendants when an inherited property changed),
+/// so changing a property of one element leaves the caches of the rest of the document intact.
+#[derive(Debug)]
+pub struct PaintCache<T> {
+    entry: RefCell<Option<PaintCacheEntry<T>>>,
+}
+
+#[derive(Debug)]
+struct PaintCacheEntry<T> {
+    /// The styles the value was computed from. Restyling an element replaces its styles, so comparing
+    /// pointers is enough to tell whether it has been restyled.
+    style: ServoArc<ComputedValues>,
+    boxes: BoxSizes,
+    value: T,
+}
+
+/// The parts of an element's layout which cached values depend on. Cached values are relative to the
+/// element's border box, so moving the element doesn't invalidate them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoxSizes {
+    size: Size<f32>,
+    border: Rect<f32>,
+    padding: Rect<f32>,
+    margin: Rect<f32>,
+}
+
+impl BoxSizes {
+    fn of(node: &Node) -> Self {
+        let layout = &node.final_layout;
+        Self {
+            size: layout.size,
+            border: layout.border,
+            padding: layout.padding,
+            margin: layout.margin,
+        }
+    }
+}
+
+impl<T> Default for PaintCache<T> {
+    fn default() -> Self {
+        Self {
+            entry: RefCell::new(None),
+        }
+    }
+}
+
+impl<T: Clone> PaintCache<T> {
+    /// The cached value (which may be out of date if the element has since been restyled or resized)
+    pub fn get(&self) -> Option<T> {
+        self.entry
+            .borrow()
+            .as_ref()
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Whether there is a cached value which is up to date with the node's styles and layout
+    pub fn is_valid_for(&self, node: &Node) -> bool {
+        let Some(style) = primary_style_arc(node) else {
+            return false;
+        };
+        self.entry.borrow().as_ref().is_some_and(|entry| {
+            ServoArc::ptr_eq(&entry.style, &style) && entry.boxes == BoxSizes::of(node)
+        })
+    }
+
+    /// The cached value if it is up to date with the node's styles and layout. Otherwise the value is
+    /// computed with `compute` (and cached if the node has been styled).
+    pub fn get_or_insert_with(&self, node: &Node, compute: impl FnOnce() -> T) -> T {
+        if self.is_valid_for(node) {
+            if let Some(value) = self.get() {
+                return value;
+            }
+        }
+
+        let value = compute();
+        *self.entry.borrow_mut() = primary_style_arc(node).map(|style| PaintCacheEntry {
+            style,
+            boxes: BoxSizes::of(node),
+            value: value.clone(),
+        });
+        value
+    }
+
+    /// Discard the cached value
+    pub fn clear(&self) {
+        *self.entry.borrow_mut() = None;
+    }
+}
+
+fn primary_style_arc(node: &Node) -> Option<ServoArc<ComputedValues>> {
+    node.stylo_element_data
+        .borrow()
+        .as_ref()
+        .and_then(|data| data.styles.get_primary())
+        .cloned()
+}
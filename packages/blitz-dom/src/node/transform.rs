@@ -0,0 +1,336 @@
+//! Resolving the CSS `transform` of elements. This is shared by painting and hit testing so that
+//! elements are hit where they are painted.
+
+use euclid::default::Transform3D;
+use kurbo::{Affine, Point, Rect, Vec2};
+use style::computed_values::transform_box::T as TransformBox;
+use style::computed_values::transform_style::T as TransformStyle;
+use style::properties::ComputedValues;
+use style::values::computed::{CSSPixelLength, TransformOrigin};
+use style::values::generics::box_::GenericPerspective;
+use taffy::Layout;
+
+use super::Node;
+
+impl Node {
+    /// The 3D transform matrix of the element (relative to its own border box), in physical pixels
+    /// at `scale`. This includes the perspective applied by its parent, and the transforms of any
+    /// ancestors it shares a 3D rendering context with (established using `transform-style: preserve-3d`).
+    ///
+    /// Use [`flatten_transform`] to convert the matrix into a 2D transform.
+    pub fn transform_matrix(&self, scale: f64) -> Transform3D<f32> {
+        let Some(style) = self.primary_styles() else {
+            return Transform3D::identity();
+        };
+        let mut matrix = local_transform(&style, &self.final_layout, scale);
+
+        // Apply the perspective of the parent element (if any)
+        if let Some(perspective) = self.parent_perspective(scale) {
+            matrix = matrix.then(&perspective);
+        }
+
+        // If the parent preserves 3D then compose with the parent's transform rather than flattening
+        let Some(parent) = self.layout_parent.get().map(|id| self.with(id)) else {
+            return matrix;
+        };
+        if !parent.preserves_3d() {
+            return matrix;
+        }
+
+        let parent_matrix = parent.transform_matrix(scale);
+        let location = self.final_layout.location;
+        let offset = Vec2::new(location.x as f64, location.y as f64) * scale;
+        compose_with_parent_transform(&matrix, offset, &parent_matrix)
+    }
+
+    /// Whether the element's children share its 3D rendering context (`transform-style: preserve-3d`),
+    /// in which case their [`transform_matrix`](Self::transform_matrix) already includes its transform
+    pub fn preserves_3d(&self) -> bool {
+        self.primary_styles()
+            .is_some_and(|style| style.clone_transform_style() == TransformStyle::Preserve3d)
+    }
+
+    /// Compute the perspective matrix that the parent of the element applies to it (in the element's
+    /// coordinate space)
+    ///
+    /// See <https://drafts.csswg.org/css-transforms-2/#perspective-property>
+    fn parent_perspective(&self, scale: f64) -> Option<Transform3D<f32>> {
+        let parent = self.with(self.layout_parent.get()?);
+        let parent_style = parent.primary_styles()?;
+        let GenericPerspective::Length(distance) = parent_style.get_box().perspective else {
+            return None;
+        };
+
+        let scale = scale as f32;
+        let parent_size = parent.final_layout.size;
+        let perspective_origin = &parent_style.get_box().perspective_origin;
+        let origin_x = perspective_origin
+            .horizontal
+            .resolve(CSSPixelLength::new(parent_size.width))
+            .px()
+            * scale;
+        let origin_y = perspective_origin
+            .vertical
+            .resolve(CSSPixelLength::new(parent_size.height))
+            .px()
+            * scale;
+
+        // The offset of the element within its parent
+        let offset_x = self.final_layout.location.x * scale;
+        let offset_y = self.final_layout.location.y * scale;
+
+        Some(perspective_matrix(
+            distance.0.px() * scale,
+            origin_x - offset_x,
+            origin_y - offset_y,
+        ))
+    }
+}
+
+/// Select the box (in CSS pixels, relative to the border box) that transforms (and the transform
+/// origin) are resolved against based on `transform-box`.
+///
+/// For elements with a CSS layout box `fill-box` behaves as `content-box`, and `stroke-box` and
+/// `view-box` behave as `border-box`. See <https://drafts.csswg.org/css-transforms-1/#transform-box>
+fn transform_reference_box(transform_box: TransformBox, layout: &Layout) -> Rect {
+    let border_box = Rect::new(
+        0.0,
+        0.0,
+        layout.size.width as f64,
+        layout.size.height as f64,
+    );
+    match transform_box {
+        TransformBox::ContentBox | TransformBox::FillBox => Rect::new(
+            border_box.x0 + (layout.border.left + layout.padding.left) as f64,
+            border_box.y0 + (layout.border.top + layout.padding.top) as f64,
+            border_box.x1 - (layout.border.right + layout.padding.right) as f64,
+            border_box.y1 - (layout.border.bottom + layout.padding.bottom) as f64,
+        ),
+        _ => border_box,
+    }
+}
+
+/// The transform of an element (relative to its own border box) including its `transform-origin`
+///
+/// The transform is resolved in CSS pixels (so that lengths such as `translateZ(10px)` or
+/// `perspective(100px)` are scaled consistently with the perspective applied by the parent) and
+/// then scaled by `scale` into physical pixels.
+fn local_transform(style: &ComputedValues, layout: &Layout, scale: f64) -> Transform3D<f32> {
+    // Reference box for resolving percentage transforms and the transform origin
+    let reference_box = transform_reference_box(style.clone_transform_box(), layout);
+    let euclid_reference_box = euclid::Rect::new(
+        euclid::Point2D::new(CSSPixelLength::new(0.0), CSSPixelLength::new(0.0)),
+        euclid::Size2D::new(
+            CSSPixelLength::new(reference_box.width() as f32),
+            CSSPixelLength::new(reference_box.height() as f32),
+        ),
+    );
+
+    let (t, _has_3d) = style
+        .get_box()
+        .transform
+        .to_transform_3d_matrix(Some(&euclid_reference_box))
+        .unwrap_or((Transform3D::default(), false));
+
+    // Apply the transform origin by:
+    //   - Translating by the inverse of the origin offset
+    //   - Applying our transform
+    //   - Translating by the origin offset
+    let transform_origin = &style.get_box().transform_origin;
+    let origin = transform_origin_offset(transform_origin, reference_box);
+    let transform = Transform3D::translation(-origin.x as f32, -origin.y as f32, 0.0)
+        .then(&t)
+        .then(&Transform3D::translation(
+            origin.x as f32,
+            origin.y as f32,
+            0.0,
+        ));
+
+    // Scaling uniformly (including along the z axis) keeps 3D transforms in proportion
+    let scale = scale as f32;
+    Transform3D::scale(scale.recip(), scale.recip(), scale.recip())
+        .then(&transform)
+        .then(&Transform3D::scale(scale, scale, scale))
+}
+
+/// Resolve `transform-origin` to an offset from the element's border box origin
+fn transform_origin_offset(transform_origin: &TransformOrigin, reference_box: Rect) -> Vec2 {
+    Vec2 {
+        x: reference_box.x0
+            + transform_origin
+                .horizontal
+                .resolve(CSSPixelLength::new(reference_box.width() as f32))
+                .px() as f64,
+        y: reference_box.y0
+            + transform_origin
+                .vertical
+                .resolve(CSSPixelLength::new(reference_box.height() as f32))
+                .px() as f64,
+    }
+}
+
+/// Compose the transform of an element with that of its parent (positioned at `offset` relative
+/// to the parent), expressing the result in the element's coordinate space
+fn compose_with_parent_transform(
+    matrix: &Transform3D<f32>,
+    offset: Vec2,
+    parent_matrix: &Transform3D<f32>,
+) -> Transform3D<f32> {
+    let (x, y) = (offset.x as f32, offset.y as f32);
+    matrix
+        .then(&Transform3D::translation(x, y, 0.0))
+        .then(parent_matrix)
+        .then(&Transform3D::translation(-x, -y, 0.0))
+}
+
+/// A perspective transform with its vanishing point at `(origin_x, origin_y)`
+fn perspective_matrix(distance: f32, origin_x: f32, origin_y: f32) -> Transform3D<f32> {
+    // A perspective of zero (or less) is treated as 1px
+    // See: https://drafts.csswg.org/css-transforms-2/#perspective-property
+    let distance = distance.max(1.0);
+    Transform3D::translation(-origin_x, -origin_y, 0.0)
+        .then(&Transform3D::perspective(distance))
+        .then(&Transform3D::translation(origin_x, origin_y, 0.0))
+}
+
+/// Convert a 3D transform into a 2D affine transform that can be used for painting and hit testing.
+///
+/// Only affine transforms can be painted exactly, as `PaintScene` has no projective transforms.
+/// Transforms which are already 2D are converted exactly. Other transforms are replaced by the
+/// affine transform which maps the top-left, top-right, and bottom-left corners of `rect` to their
+/// projected positions. This is exact for 3D transforms without perspective (which are flattened
+/// onto the screen), but perspective foreshortening is lost: the bottom-right corner ends up where
+/// the other three corners place it rather than at its projected position.
+pub fn flatten_transform(matrix: &Transform3D<f32>, rect: Rect) -> Option<Affine> {
+    if matrix.is_2d() {
+        // See: https://drafts.csswg.org/css-transforms-2/#two-dimensional-subset
+        // And https://docs.rs/kurbo/latest/kurbo/struct.Affine.html#method.new
+        let m = matrix;
+        return Some(Affine::new(
+            [m.m11, m.m12, m.m21, m.m22, m.m41, m.m42].map(|v| v as f64),
+        ));
+    }
+
+    let project = |x: f64, y: f64| -> Option<Point> {
+        let point = matrix.transform_point2d(euclid::Point2D::new(x as f32, y as f32))?;
+        Some(Point::new(point.x as f64, point.y as f64))
+    };
+    let top_left = project(rect.x0, rect.y0)?;
+    let top_right = project(rect.x1, rect.y0)?;
+    let bottom_left = project(rect.x0, rect.y1)?;
+
+    if rect.width() == 0.0 || rect.height() == 0.0 {
+        return None;
+    }
+    let x_axis = (top_right - top_left) / rect.width();
+    let y_axis = (bottom_left - top_left) / rect.height();
+    let origin = top_left - x_axis * rect.x0 - y_axis * rect.y0;
+
+    Some(Affine::new([
+        x_axis.x, x_axis.y, y_axis.x, y_axis.y, origin.x, origin.y,
+    ]))
+}
+
+#[test]
+fn content_box_transform_origin_accounts_for_padding() {
+    use style::values::computed::LengthPercentage;
+    use style::values::generics::transform::GenericTransformOrigin;
+
+    let mut layout = Layout::new();
+    layout.size = taffy::Size {
+        width: 100.0,
+        height: 100.0,
+    };
+    layout.padding = taffy::Rect {
+        left: 20.0,
+        right: 0.0,
+        top: 10.0,
+        bottom: 0.0,
+    };
+    // transform-origin: 0 0
+    let zero = LengthPercentage::new_length(CSSPixelLength::new(0.0));
+    let origin = GenericTransformOrigin::new(zero.clone(), zero, CSSPixelLength::new(0.0));
+
+    let border_box = transform_reference_box(TransformBox::BorderBox, &layout);
+    assert_eq!(transform_origin_offset(&origin, border_box), Vec2::ZERO);
+
+    let content_box = transform_reference_box(TransformBox::ContentBox, &layout);
+    assert_eq!(
+        transform_origin_offset(&origin, content_box),
+        Vec2::new(20.0, 10.0)
+    );
+}
+
+#[test]
+fn perspective_is_flattened_to_an_affine_transform() {
+    // A 100x100 child rotated 45deg around its y axis inside a container with `perspective: 200px`
+    // with the perspective origin at the centre of the child
+    let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let angle = euclid::Angle::degrees(45.0f32);
+    let rotation = Transform3D::translation(-50.0, -50.0, 0.0)
+        .then(&Transform3D::rotation(0.0, 1.0, 0.0, angle))
+        .then(&Transform3D::translation(50.0, 50.0, 0.0));
+    let matrix = rotation.then(&perspective_matrix(200.0, 50.0, 50.0));
+
+    let transform = flatten_transform(&matrix, rect).unwrap();
+
+    // The left edge rotates towards the viewer (and grows), the right edge away (and shrinks)
+    let top_left = matrix
+        .transform_point2d(euclid::Point2D::new(0.0, 0.0))
+        .unwrap();
+    let top_right = matrix
+        .transform_point2d(euclid::Point2D::new(100.0, 0.0))
+        .unwrap();
+    assert!(top_left.y < 0.0);
+    assert!(top_right.y > 0.0);
+    assert!((top_right.x - top_left.x) < 100.0);
+
+    // The flattened transform maps those corners to their projected positions
+    let mapped = transform * Point::new(100.0, 0.0);
+    assert!((mapped.x - top_right.x as f64).abs() < 1e-3);
+    assert!((mapped.y - top_right.y as f64).abs() < 1e-3);
+
+    // But being affine, it can't foreshorten the far edge: the bottom-right corner is placed to
+    // complete a parallelogram rather than at its projected position
+    let bottom_right = matrix
+        .transform_point2d(euclid::Point2D::new(100.0, 100.0))
+        .unwrap();
+    let mapped = transform * Point::new(100.0, 100.0);
+    assert!((mapped.y - bottom_right.y as f64).abs() > 1.0);
+}
+
+#[test]
+fn preserve_3d_composes_child_and_parent_rotations() {
+    let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let rotate_y = |degrees: f32| {
+        Transform3D::translation(-50.0, -50.0, 0.0)
+            .then(&Transform3D::rotation(
+                0.0,
+                1.0,
+                0.0,
+                euclid::Angle::degrees(degrees),
+            ))
+            .then(&Transform3D::translation(50.0, 50.0, 0.0))
+    };
+
+    // A parent rotated 90deg is edge-on to the viewer. Flattening it would collapse its children to
+    // a line, but in a shared 3D context a child rotated back by -90deg faces the viewer again.
+    let parent = rotate_y(90.0);
+    assert!(
+        flatten_transform(&parent, rect)
+            .unwrap()
+            .determinant()
+            .abs()
+            < 1e-3
+    );
+
+    let child = compose_with_parent_transform(&rotate_y(-90.0), Vec2::ZERO, &parent);
+    let child = flatten_transform(&child, rect).unwrap();
+    assert!((child * Point::new(100.0, 100.0) - Point::new(100.0, 100.0)).hypot() < 1e-3);
+
+    // A sibling rotated by -45deg keeps its relative 3D orientation (45deg from the viewer)
+    let sibling = compose_with_parent_transform(&rotate_y(-45.0), Vec2::ZERO, &parent);
+    let sibling = flatten_transform(&sibling, rect).unwrap();
+    let width = (sibling * Point::new(100.0, 0.0) - sibling * Point::new(0.0, 0.0)).hypot();
+    assert!((width - 100.0 * std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+}
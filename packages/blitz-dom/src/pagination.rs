@@ -0,0 +1,153 @@
+//! Splitting a document into pages for paged media (<https://drafts.csswg.org/css-break-3>)
+//!
+//! The document is laid out as a single continuous page (with the viewport width set to the width
+//! of a page) and then sliced into pages vertically. Page boundaries fall where a page is full,
+//! unless an earlier break is forced by `break-before` / `break-after` (or the legacy `page-break-*`
+//! properties), or a box which avoids breaks inside it would be split.
+//!
+//! TODO:
+//!   - Fragmenting boxes across pages (boxes which are split are simply sliced)
+//!   - `orphans` / `widows` and avoiding breaks between lines of text
+//!   - `break-before: avoid` / `break-after: avoid`
+//!   - `@page` rules (margins and page sizes)
+
+use style::values::computed::{BreakBetween, BreakWithin};
+
+use crate::{BaseDocument, Node};
+
+impl BaseDocument {
+    /// The vertical offsets (in CSS pixels) at which each page starts when the document is split into
+    /// pages that are `page_height` CSS pixels tall. The first page always starts at `0.0`, and page
+    /// `i` ends where page `i + 1` starts (or at the bottom of the document for the last page).
+    ///
+    /// This assumes styles are resolved and layout is complete. Render with
+    /// [`MediaType::Print`](blitz_traits::shell::MediaType::Print) so that `@media print` rules apply.
+    pub fn page_breaks(&self, page_height: f32) -> Vec<f32> {
+        let root = self.root_element();
+        let root_y = root.final_layout.location.y;
+        let content_height = root_y + root.final_layout.size.height;
+
+        let mut forced = Vec::new();
+        let mut avoid = Vec::new();
+        collect_breaks(root, root_y, &mut forced, &mut avoid);
+        forced.sort_by(f32::total_cmp);
+
+        let mut breaks = vec![0.0];
+        if page_height <= 0.0 {
+            return breaks;
+        }
+
+        let mut page_start = 0.0;
+        loop {
+            let page_end = page_start + page_height;
+
+            // Forced breaks at the very top of a page are ignored so that they don't create blank pages
+            let next_forced = forced
+                .iter()
+                .copied()
+                .find(|&y| y > page_start && y < page_end && y < content_height);
+            let next = match next_forced {
+                Some(y) => y,
+                None if page_end >= content_height => break,
+                // Break before (rather than through) boxes which avoid breaks inside them, as long
+                // as they fit on a page by themselves
+                None => avoid
+                    .iter()
+                    .filter(|(top, bottom)| {
+                        *top > page_start && *top < page_end && *bottom > page_end
+                    })
+                    .filter(|(top, bottom)| bottom - top <= page_height)
+                    .map(|(top, _)| *top)
+                    .fold(page_end, f32::min),
+            };
+
+            // A page height which is tiny compared to `page_start` can round away when added to it,
+            // in which case there is no way to make progress
+            if next <= page_start {
+                break;
+            }
+
+            breaks.push(next);
+            page_start = next;
+        }
+
+        breaks
+    }
+}
+
+/// Collect the forced break positions and the extents of the boxes that avoid breaks inside them
+/// for the descendants of `node`. `y` is the offset of the node's border box from the top of the document.
+fn collect_breaks(node: &Node, y: f32, forced: &mut Vec<f32>, avoid: &mut Vec<(f32, f32)>) {
+    let layout_children = node.layout_children.borrow();
+    for &child_id in layout_children.iter().flatten() {
+        let child = node.with(child_id);
+        let child_y = y + child.final_layout.location.y;
+        let child_bottom = child_y + child.final_layout.size.height;
+
+        if let Some(styles) = child.primary_styles() {
+            if is_forced_break(styles.clone_break_before()) {
+                forced.push(child_y);
+            }
+            if is_forced_break(styles.clone_break_after()) {
+                forced.push(child_bottom);
+            }
+            if matches!(
+                styles.clone_break_inside(),
+                BreakWithin::Avoid | BreakWithin::AvoidPage
+            ) {
+                avoid.push((child_y, child_bottom));
+            }
+        }
+
+        collect_breaks(child, child_y, forced, avoid);
+    }
+}
+
+fn is_forced_break(value: BreakBetween) -> bool {
+    matches!(
+        value,
+        BreakBetween::Always | BreakBetween::Page | BreakBetween::Left | BreakBetween::Right
+    )
+}
+
+#[test]
+fn tall_content_splits_across_pages() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use blitz_traits::shell::{ColorScheme, MediaType, Viewport};
+
+    let css = "body { margin: 0 }
+         .block { height: 400px }
+         .screen-only { display: none }
+         @media print { .screen-only { display: block; height: 50px } }
+         .new-page { page-break-before: always }";
+    let (mut doc, (print_only, second)) = doc_with_body(800, 600, css, |mutr, body| {
+        let first = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(first, qual_name!("class", html), "block");
+        let print_only = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(print_only, qual_name!("class", html), "screen-only");
+        let second = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(second, qual_name!("class", html), "block");
+        mutr.append_children(body, &[first, print_only, second]);
+        (print_only, second)
+    });
+    doc.set_viewport(
+        Viewport::new(800, 600, 1.0, ColorScheme::Light).with_media_type(MediaType::Print),
+    );
+    doc.resolve(0.0);
+
+    // `@media print` rules apply
+    assert_eq!(
+        doc.get_node(print_only).unwrap().final_layout.size.height,
+        50.0
+    );
+
+    // 850px of content is split into a full page and the 250px that overflows it
+    assert_eq!(doc.page_breaks(600.0), [0.0, 600.0]);
+
+    // A forced break starts the second block on a new page
+    doc.mutate()
+        .set_attribute(second, qual_name!("class", html), "block new-page");
+    doc.resolve(0.0);
+    assert_eq!(doc.page_breaks(600.0), [0.0, 450.0]);
+}
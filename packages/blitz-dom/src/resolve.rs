@@ -44,6 +44,9 @@ impl BaseDocument {
             return;
         }
 
+        self.update_tooltip(current_time_for_animations);
+        self.update_smooth_scrolls(current_time_for_animations);
+
         let root_node_id = self.root_element().id;
         debug_timer!(timer, feature = "log_phase_times");
 
@@ -72,10 +75,15 @@ impl BaseDocument {
         self.resolve_layout();
         timer.record_time("layout");
 
-        // Clear all damage
+        // Clear all damage (recording damaged nodes for paint flashing)
         #[cfg(feature = "incremental")]
         {
-            for (_, node) in self.nodes.iter_mut() {
+            let paint_flash = self.devtool_settings.paint_flash;
+            self.repainted_nodes.clear();
+            for (id, node) in self.nodes.iter_mut() {
+                if paint_flash && node.damage().is_some_and(|damage| !damage.is_empty()) {
+                    self.repainted_nodes.push(id);
+                }
                 node.clear_damage_mut();
             }
             timer.record_time("c_damage");
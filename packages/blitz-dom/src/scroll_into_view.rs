@@ -0,0 +1,440 @@
+//! Programmatically scrolling elements into view (<https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>)
+//!
+//! TODO:
+//!   - Right-to-left and vertical writing modes (`start` is always the top / left edge)
+//!   - The `scroll-behavior` property (`ScrollBehavior::Auto` always scrolls instantly)
+//!   - Cancelling smooth scrolls when the user scrolls
+
+use style::values::computed::{CSSPixelLength, NonNegativeLengthPercentageOrAuto};
+use style::values::generics::length::GenericLengthPercentageOrAuto;
+
+use crate::scroll_snap::is_scroll_container;
+use crate::{BaseDocument, Node, Point};
+
+/// How long (in seconds) a smooth scroll takes to reach its destination
+pub(crate) const SMOOTH_SCROLL_DURATION: f64 = 0.3;
+
+/// How an element is aligned within a scroll container on one axis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollLogicalPosition {
+    /// Align the start edge of the element with the start edge of the scrollport
+    #[default]
+    Start,
+    /// Center the element within the scrollport
+    Center,
+    /// Align the end edge of the element with the end edge of the scrollport
+    End,
+    /// Scroll as little as possible to bring the element into view (not at all if it is already visible)
+    Nearest,
+}
+
+/// Whether scrolling happens immediately or is animated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollBehavior {
+    /// Use the scroll container's `scroll-behavior`
+    #[default]
+    Auto,
+    Instant,
+    /// Animate towards the new scroll offsets as the document is resolved
+    Smooth,
+}
+
+/// Options for [`BaseDocument::scroll_to`]. Equivalent to the DOM's `ScrollIntoViewOptions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollIntoViewOptions {
+    /// The alignment on the block (vertical) axis
+    pub block: ScrollLogicalPosition,
+    /// The alignment on the inline (horizontal) axis
+    pub inline: ScrollLogicalPosition,
+    pub behavior: ScrollBehavior,
+}
+
+impl Default for ScrollIntoViewOptions {
+    fn default() -> Self {
+        Self {
+            block: ScrollLogicalPosition::Start,
+            inline: ScrollLogicalPosition::Nearest,
+            behavior: ScrollBehavior::Auto,
+        }
+    }
+}
+
+/// A scroll container (or the viewport) which is being smoothly scrolled
+pub(crate) struct SmoothScroll {
+    /// The scroll container, or `None` for the viewport
+    node_id: Option<usize>,
+    from: Point<f64>,
+    to: Point<f64>,
+    /// The animation time of the first tick after the scroll started
+    start_time: Option<f64>,
+}
+
+impl BaseDocument {
+    /// Scroll the element `node_id` into view. Each scroll container which contains the element is
+    /// scrolled (innermost first, finishing with the viewport) so that the element's border box
+    /// (extended by its `scroll-margin`) is aligned within the scrollport (inset by the container's
+    /// `scroll-padding`) as described by `options`.
+    ///
+    /// Returns whether any scroll offset changed (or, for smooth scrolling, will change).
+    pub fn scroll_to(&mut self, node_id: usize, options: ScrollIntoViewOptions) -> bool {
+        let Some(node) = self.get_node(node_id) else {
+            return false;
+        };
+        let root_id = self.root_element().id;
+        let margin = scroll_margin(node);
+        let size = node.final_layout.size;
+
+        // The new scroll offsets, as (scroll container, old offset, new offset)
+        let mut scrolls = Vec::new();
+
+        // The position of the element's border box relative to the border box of the ancestor that
+        // is currently being visited (ignoring that ancestor's scroll offset)
+        let mut position = Point::ZERO;
+        let mut current_id = node_id;
+        loop {
+            let current = &self.nodes[current_id];
+            position.x += current.final_layout.location.x as f64;
+            position.y += current.final_layout.location.y as f64;
+            let Some(parent_id) = current.layout_parent.get() else {
+                break;
+            };
+
+            let parent = &self.nodes[parent_id];
+            if parent_id != root_id && is_scroll_container(parent) {
+                let layout = &parent.final_layout;
+                let scrollport = Scrollport {
+                    x: layout.border.left as f64,
+                    y: layout.border.top as f64,
+                    width: (layout.size.width
+                        - layout.border.left
+                        - layout.border.right
+                        - layout.scrollbar_size.width) as f64,
+                    height: (layout.size.height
+                        - layout.border.top
+                        - layout.border.bottom
+                        - layout.scrollbar_size.height) as f64,
+                    max_x: layout.scroll_width() as f64,
+                    max_y: layout.scroll_height() as f64,
+                };
+                let target = Target::new(position, size, &margin);
+                let from = parent.scroll_offset;
+                let to = scrollport
+                    .inset_by_scroll_padding(parent)
+                    .align(&target, from, options);
+                self.nodes[parent_id].scroll_offset = to;
+                scrolls.push((Some(parent_id), from, to));
+            }
+
+            let scroll_offset = self.nodes[parent_id].scroll_offset;
+            position.x -= scroll_offset.x;
+            position.y -= scroll_offset.y;
+            current_id = parent_id;
+        }
+
+        // Finally scroll the viewport (using the root element's `scroll-padding`)
+        let root = self.root_element();
+        let viewport_width = self.viewport.window_size.0 as f64 / self.viewport.scale_f64();
+        let viewport_height = self.viewport.window_size.1 as f64 / self.viewport.scale_f64();
+        let scrollport = Scrollport {
+            x: 0.0,
+            y: 0.0,
+            width: viewport_width,
+            height: viewport_height,
+            max_x: (root.final_layout.size.width as f64 - viewport_width).max(0.0),
+            max_y: (root.final_layout.size.height as f64 - viewport_height).max(0.0),
+        };
+        let target = Target::new(position, size, &margin);
+        let from = self.viewport_scroll;
+        let to = scrollport
+            .inset_by_scroll_padding(root)
+            .align(&target, from, options);
+        self.viewport_scroll = to;
+        scrolls.push((None, from, to));
+
+        scrolls.retain(|(_, from, to)| from != to);
+        if scrolls.is_empty() {
+            return false;
+        }
+
+        if options.behavior == ScrollBehavior::Smooth {
+            for (node_id, from, to) in scrolls {
+                self.set_scroll_offset(node_id, from);
+                self.smooth_scrolls
+                    .retain(|scroll| scroll.node_id != node_id);
+                self.smooth_scrolls.push(SmoothScroll {
+                    node_id,
+                    from,
+                    to,
+                    start_time: None,
+                });
+            }
+        }
+
+        self.shell_provider.request_redraw();
+        true
+    }
+
+    /// Advance any smooth scrolls started by [`Self::scroll_to`]
+    pub(crate) fn update_smooth_scrolls(&mut self, current_time: f64) {
+        let mut smooth_scrolls = std::mem::take(&mut self.smooth_scrolls);
+        smooth_scrolls.retain_mut(|scroll| {
+            let start_time = *scroll.start_time.get_or_insert(current_time);
+            let progress = ((current_time - start_time) / SMOOTH_SCROLL_DURATION).clamp(0.0, 1.0);
+
+            // Ease out (cubic)
+            let t = 1.0 - (1.0 - progress).powi(3);
+            let offset = Point {
+                x: scroll.from.x + (scroll.to.x - scroll.from.x) * t,
+                y: scroll.from.y + (scroll.to.y - scroll.from.y) * t,
+            };
+            self.set_scroll_offset(scroll.node_id, offset);
+            progress < 1.0
+        });
+        self.smooth_scrolls = smooth_scrolls;
+    }
+
+    fn set_scroll_offset(&mut self, node_id: Option<usize>, offset: Point<f64>) {
+        match node_id {
+            Some(node_id) => {
+                if let Some(node) = self.nodes.get_mut(node_id) {
+                    node.scroll_offset = offset;
+                }
+            }
+            None => self.viewport_scroll = offset,
+        }
+    }
+}
+
+/// The area of the element which should be brought into view, in the (unscrolled) coordinate space
+/// of the scroll container's border box
+struct Target {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Target {
+    /// The element's border box (at `position`, and of `size`) extended by its `scroll-margin`
+    fn new(position: Point<f64>, size: taffy::Size<f32>, margin: &taffy::Rect<f64>) -> Self {
+        Self {
+            x: position.x - margin.left,
+            y: position.y - margin.top,
+            width: size.width as f64 + margin.left + margin.right,
+            height: size.height as f64 + margin.top + margin.bottom,
+        }
+    }
+}
+
+/// The visible area of a scroll container (when it is not scrolled), and its maximum scroll offsets
+struct Scrollport {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Scrollport {
+    fn inset_by_scroll_padding(self, node: &Node) -> Self {
+        let Some(styles) = node.primary_styles() else {
+            return self;
+        };
+        let padding = styles.get_padding();
+        let top = resolve_scroll_padding(&padding.scroll_padding_top, self.height);
+        let bottom = resolve_scroll_padding(&padding.scroll_padding_bottom, self.height);
+        let left = resolve_scroll_padding(&padding.scroll_padding_left, self.width);
+        let right = resolve_scroll_padding(&padding.scroll_padding_right, self.width);
+        Self {
+            x: self.x + left,
+            y: self.y + top,
+            width: (self.width - left - right).max(0.0),
+            height: (self.height - top - bottom).max(0.0),
+            ..self
+        }
+    }
+
+    /// The scroll offset which aligns the target within the scrollport
+    fn align(
+        &self,
+        target: &Target,
+        current: Point<f64>,
+        options: ScrollIntoViewOptions,
+    ) -> Point<f64> {
+        let x = align_axis(
+            options.inline,
+            target.x,
+            target.width,
+            self.x,
+            self.width,
+            current.x,
+        );
+        let y = align_axis(
+            options.block,
+            target.y,
+            target.height,
+            self.y,
+            self.height,
+            current.y,
+        );
+        Point {
+            x: x.clamp(0.0, self.max_x.max(0.0)),
+            y: y.clamp(0.0, self.max_y.max(0.0)),
+        }
+    }
+}
+
+/// The scroll offset on one axis which aligns the target (starting at `start` and of length `size`)
+/// within the scrollport (starting at `port_start` and of length `port_size`)
+fn align_axis(
+    position: ScrollLogicalPosition,
+    start: f64,
+    size: f64,
+    port_start: f64,
+    port_size: f64,
+    current: f64,
+) -> f64 {
+    let align_start = start - port_start;
+    let align_end = start + size - (port_start + port_size);
+    match position {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => start + size / 2.0 - (port_start + port_size / 2.0),
+        ScrollLogicalPosition::Nearest => {
+            let before = start < port_start + current;
+            let after = start + size > port_start + current + port_size;
+            match (before, after) {
+                // The target is either entirely visible, or covers the whole scrollport
+                (false, false) | (true, true) => current,
+                (true, false) if size <= port_size => align_start,
+                (false, true) if size > port_size => align_start,
+                _ => align_end,
+            }
+        }
+    }
+}
+
+/// An element's `scroll-margin` on each side (in CSS pixels)
+fn scroll_margin(node: &Node) -> taffy::Rect<f64> {
+    let Some(styles) = node.primary_styles() else {
+        return taffy::Rect {
+            left: 0.0,
+            right: 0.0,
+            top: 0.0,
+            bottom: 0.0,
+        };
+    };
+    let margin = styles.get_margin();
+    taffy::Rect {
+        left: margin.scroll_margin_left.px() as f64,
+        right: margin.scroll_margin_right.px() as f64,
+        top: margin.scroll_margin_top.px() as f64,
+        bottom: margin.scroll_margin_bottom.px() as f64,
+    }
+}
+
+fn resolve_scroll_padding(padding: &NonNegativeLengthPercentageOrAuto, basis: f64) -> f64 {
+    match padding {
+        GenericLengthPercentageOrAuto::Auto => 0.0,
+        GenericLengthPercentageOrAuto::LengthPercentage(length) => {
+            length.0.resolve(CSSPixelLength::new(basis as f32)).px() as f64
+        }
+    }
+}
+
+#[test]
+fn scroll_to_aligns_element_in_scroll_container() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+    use ScrollLogicalPosition::*;
+
+    let css = "body { margin: 0 }
+               .scroller { height: 100px; overflow-x: hidden; overflow-y: scroll }
+               .spacer { height: 300px }
+               .target { height: 50px }
+               .padded { scroll-padding-top: 20px }
+               .margin { scroll-margin-top: 10px }";
+    let (mut doc, [scroller, target]) = doc_with_body(800, 600, css, |mutr, body| {
+        let scroller = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(scroller, qual_name!("class", html), "scroller");
+        let before = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(before, qual_name!("class", html), "spacer");
+        let target = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(target, qual_name!("class", html), "target");
+        let after = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(after, qual_name!("class", html), "spacer");
+        mutr.append_children(scroller, &[before, target, after]);
+        mutr.append_children(body, &[scroller]);
+        [scroller, target]
+    });
+
+    let layout = doc.nodes[scroller].final_layout;
+    let port = (layout.size.height - layout.scrollbar_size.height) as f64;
+    let scroll_from = |doc: &mut BaseDocument, offset: f64, block| {
+        doc.nodes[scroller].scroll_offset.y = offset;
+        let options = ScrollIntoViewOptions {
+            block,
+            ..Default::default()
+        };
+        let changed = doc.scroll_to(target, options);
+        (changed, doc.nodes[scroller].scroll_offset.y)
+    };
+
+    // The target spans 300px to 350px within the scroller
+    assert_eq!(scroll_from(&mut doc, 0.0, Start), (true, 300.0));
+    assert_eq!(scroll_from(&mut doc, 0.0, End), (true, 350.0 - port));
+    assert_eq!(
+        scroll_from(&mut doc, 0.0, Center),
+        (true, 325.0 - port / 2.0)
+    );
+
+    // `nearest` aligns whichever edge is closest, and doesn't scroll if the target is already visible
+    assert_eq!(scroll_from(&mut doc, 0.0, Nearest), (true, 350.0 - port));
+    assert_eq!(scroll_from(&mut doc, 500.0, Nearest), (true, 300.0));
+    assert_eq!(scroll_from(&mut doc, 290.0, Nearest), (false, 290.0));
+
+    // `scroll-padding` on the scroller and `scroll-margin` on the target are both respected
+    doc.mutate()
+        .set_attribute(scroller, qual_name!("class", html), "scroller padded");
+    doc.mutate()
+        .set_attribute(target, qual_name!("class", html), "target margin");
+    doc.resolve(0.0);
+    assert_eq!(scroll_from(&mut doc, 0.0, Start), (true, 270.0));
+
+    // The viewport doesn't need to scroll as the scroller is at the top of the document
+    assert_eq!(doc.viewport_scroll(), Point::ZERO);
+}
+
+#[test]
+fn smooth_scroll_to_animates_to_destination() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (mut doc, target) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let spacer = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(spacer, qual_name!("style"), "height: 1000px");
+        let target = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(target, qual_name!("style"), "height: 1000px");
+        mutr.append_children(body, &[spacer, target]);
+        target
+    });
+
+    let options = ScrollIntoViewOptions {
+        behavior: ScrollBehavior::Smooth,
+        ..Default::default()
+    };
+    assert!(doc.scroll_to(target, options));
+    assert_eq!(doc.viewport_scroll().y, 0.0);
+    assert!(doc.is_animating());
+
+    // The scroll starts on the next tick and moves part of the way before reaching the target
+    doc.resolve(1.0);
+    assert_eq!(doc.viewport_scroll().y, 0.0);
+    doc.resolve(1.0 + SMOOTH_SCROLL_DURATION / 2.0);
+    let halfway = doc.viewport_scroll().y;
+    assert!(halfway > 0.0 && halfway < 1000.0);
+    doc.resolve(1.0 + SMOOTH_SCROLL_DURATION);
+    assert_eq!(doc.viewport_scroll().y, 1000.0);
+    assert!(!doc.is_animating());
+}
@@ -0,0 +1,194 @@
+//! Scroll snapping (<https://drafts.csswg.org/css-scroll-snap-1>)
+//!
+//! Snapping happens once a scroll gesture ends: the scroll offset jumps to a snap point rather than
+//! being animated there.
+//!
+//! TODO:
+//!   - Snapping on the inline axis
+//!   - `proximity` snapping (only `mandatory` snapping is supported)
+//!   - `scroll-padding` and `scroll-margin`
+
+use style::values::computed::Overflow;
+use style::values::specified::box_::{
+    ScrollSnapAlignKeyword, ScrollSnapAxis, ScrollSnapStrictness,
+};
+
+use crate::{BaseDocument, Node};
+
+impl BaseDocument {
+    /// Snap the scroll offsets of the scroll snap containers that contain `node_id` (and of the viewport
+    /// if the root element is a scroll snap container) to their snap points. This should be called when
+    /// a scroll gesture (such as a mouse wheel tick, or lifting fingers off a touchpad) ends.
+    ///
+    /// `direction` is the direction in which the gesture moved the scroll offset (positive for down).
+    /// If it is non-zero then the nearest snap point in that direction is preferred so that small
+    /// scrolls advance to the next snap point rather than returning to the current one.
+    ///
+    /// Returns whether any scroll offset changed.
+    pub fn snap_scroll_position(&mut self, node_id: Option<usize>, direction: f64) -> bool {
+        let root_id = self.root_element().id;
+        let mut has_changed = false;
+
+        let mut maybe_node_id = node_id;
+        while let Some(node_id) = maybe_node_id {
+            let node = &self.nodes[node_id];
+            maybe_node_id = node.parent;
+
+            // The root element's scroll-snap-type applies to the viewport
+            if node_id == root_id || !is_scroll_container(node) || !snaps_block_axis(node) {
+                continue;
+            }
+
+            // Snap positions are relative to the top of the scrollport (the padding box)
+            let layout = &node.final_layout;
+            let scrollport_height = (layout.size.height
+                - layout.border.top
+                - layout.border.bottom
+                - layout.scrollbar_size.height) as f64;
+            let max_offset = layout.scroll_height() as f64;
+            let mut positions = Vec::new();
+            collect_snap_positions(
+                node,
+                -layout.border.top as f64,
+                scrollport_height,
+                &mut positions,
+            );
+
+            let current = node.scroll_offset.y;
+            if let Some(offset) = choose_snap_position(&positions, current, max_offset, direction) {
+                self.nodes[node_id].scroll_offset.y = offset;
+                has_changed |= offset != current;
+            }
+        }
+
+        let root = self.root_element();
+        if snaps_block_axis(root) {
+            let scrollport_height =
+                self.viewport.window_size.1 as f64 / self.viewport.scale() as f64;
+            let max_offset = (root.final_layout.size.height as f64 - scrollport_height).max(0.0);
+            let mut positions = Vec::new();
+            collect_snap_positions(
+                root,
+                root.final_layout.location.y as f64,
+                scrollport_height,
+                &mut positions,
+            );
+
+            let current = self.viewport_scroll.y;
+            if let Some(offset) = choose_snap_position(&positions, current, max_offset, direction) {
+                self.viewport_scroll.y = offset;
+                has_changed |= offset != current;
+            }
+        }
+
+        has_changed
+    }
+}
+
+pub(crate) fn is_scroll_container(node: &Node) -> bool {
+    node.primary_styles().is_some_and(|styles| {
+        matches!(
+            styles.clone_overflow_y(),
+            Overflow::Scroll | Overflow::Auto | Overflow::Hidden
+        )
+    })
+}
+
+/// Whether the node has mandatory scroll snapping on the block (vertical) axis
+fn snaps_block_axis(node: &Node) -> bool {
+    node.primary_styles().is_some_and(|styles| {
+        let snap_type = styles.clone_scroll_snap_type();
+        snap_type.strictness == ScrollSnapStrictness::Mandatory
+            && matches!(
+                snap_type.axis,
+                ScrollSnapAxis::Y | ScrollSnapAxis::Block | ScrollSnapAxis::Both
+            )
+    })
+}
+
+/// Collect the vertical scroll offsets at which the snap areas within `node` are aligned with the
+/// scrollport according to their `scroll-snap-align`. `y` is the offset of the node's border box from
+/// the top of the scrollport (when it is not scrolled).
+///
+/// Descendants of nested scroll containers snap within those containers and so are not collected.
+fn collect_snap_positions(node: &Node, y: f64, scrollport_height: f64, positions: &mut Vec<f64>) {
+    let layout_children = node.layout_children.borrow();
+    for &child_id in layout_children.iter().flatten() {
+        let child = node.with(child_id);
+        let child_y = y + child.final_layout.location.y as f64;
+        let child_height = child.final_layout.size.height as f64;
+
+        if let Some(styles) = child.primary_styles() {
+            match styles.clone_scroll_snap_align().block {
+                ScrollSnapAlignKeyword::None => {}
+                ScrollSnapAlignKeyword::Start => positions.push(child_y),
+                ScrollSnapAlignKeyword::End => {
+                    positions.push(child_y + child_height - scrollport_height)
+                }
+                ScrollSnapAlignKeyword::Center => {
+                    positions.push(child_y + (child_height - scrollport_height) / 2.0)
+                }
+            }
+        }
+
+        if !is_scroll_container(child) {
+            collect_snap_positions(child, child_y, scrollport_height, positions);
+        }
+    }
+}
+
+/// Choose the snap position nearest to `current`, preferring those in `direction` (if it is non-zero)
+fn choose_snap_position(
+    positions: &[f64],
+    current: f64,
+    max_offset: f64,
+    direction: f64,
+) -> Option<f64> {
+    let clamped = positions.iter().map(|pos| pos.clamp(0.0, max_offset));
+    let distance = |pos: &f64| (pos - current).abs();
+    let nearest = |a: &f64, b: &f64| distance(a).total_cmp(&distance(b));
+
+    let in_direction = clamped
+        .clone()
+        .filter(|pos| direction != 0.0 && (pos - current) * direction >= 0.0)
+        .min_by(nearest);
+    in_direction.or_else(|| clamped.min_by(nearest))
+}
+
+#[test]
+fn partial_scroll_settles_on_snap_point() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let css = "body { margin: 0 }
+               .carousel { height: 100px; overflow-y: scroll; scroll-snap-type: y mandatory }
+               .slide { height: 100px; scroll-snap-align: start }";
+    let (mut doc, (carousel, slides)) = doc_with_body(800, 600, css, |mutr, body| {
+        let carousel = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(carousel, qual_name!("class", html), "carousel");
+        let slides: Vec<usize> = (0..5)
+            .map(|_| {
+                let slide = mutr.create_element(qual_name!("div", html), vec![]);
+                mutr.set_attribute(slide, qual_name!("class", html), "slide");
+                slide
+            })
+            .collect();
+        mutr.append_children(carousel, &slides);
+        mutr.append_children(body, &[carousel]);
+        (carousel, slides)
+    });
+
+    // A partial scroll settles on the nearest slide
+    doc.scroll_node_by(slides[1], 0.0, -130.0);
+    assert_eq!(doc.get_node(carousel).unwrap().scroll_offset.y, 130.0);
+    assert!(doc.snap_scroll_position(Some(slides[1]), 0.0));
+    assert_eq!(doc.get_node(carousel).unwrap().scroll_offset.y, 100.0);
+
+    // A small scroll in a direction advances to the next slide in that direction
+    doc.scroll_node_by(slides[1], 0.0, -20.0);
+    assert!(doc.snap_scroll_position(Some(slides[1]), 20.0));
+    assert_eq!(doc.get_node(carousel).unwrap().scroll_offset.y, 200.0);
+
+    // Snapping an already snapped container does nothing
+    assert!(!doc.snap_scroll_position(Some(slides[2]), 0.0));
+}
@@ -406,14 +406,16 @@ impl selectors::Element for BlitzNode<'_> {
                 .downcast_element()
                 .and_then(|elem| elem.checkbox_input_checked())
                 .unwrap_or(false),
-            NonTSPseudoClass::Valid => false,
-            NonTSPseudoClass::Invalid => false,
+            NonTSPseudoClass::Valid => self.element_state.contains(ElementState::VALID),
+            NonTSPseudoClass::Invalid => self.element_state.contains(ElementState::INVALID),
             NonTSPseudoClass::Defined => false,
             NonTSPseudoClass::Disabled => false,
             NonTSPseudoClass::Enabled => false,
             NonTSPseudoClass::Focus => self.element_state.contains(ElementState::FOCUS),
-            NonTSPseudoClass::FocusWithin => false,
-            NonTSPseudoClass::FocusVisible => false,
+            NonTSPseudoClass::FocusWithin => {
+                self.element_state.contains(ElementState::FOCUS_WITHIN)
+            }
+            NonTSPseudoClass::FocusVisible => self.element_state.contains(ElementState::FOCUSRING),
             NonTSPseudoClass::Fullscreen => false,
             NonTSPseudoClass::Hover => self.element_state.contains(ElementState::HOVER),
             NonTSPseudoClass::Indeterminate => false,
@@ -431,13 +433,13 @@ impl selectors::Element for BlitzNode<'_> {
             NonTSPseudoClass::ReadWrite => false,
             NonTSPseudoClass::ReadOnly => false,
             NonTSPseudoClass::ServoNonZeroBorder => false,
-            NonTSPseudoClass::Target => false,
+            NonTSPseudoClass::Target => self.element_state.contains(ElementState::URLTARGET),
             NonTSPseudoClass::Visited => false,
             NonTSPseudoClass::Autofill => false,
             NonTSPseudoClass::Default => false,
 
             NonTSPseudoClass::InRange => false,
-            NonTSPseudoClass::Modal => false,
+            NonTSPseudoClass::Modal => self.element_state.contains(ElementState::MODAL),
             NonTSPseudoClass::Optional => false,
             NonTSPseudoClass::OutOfRange => false,
             NonTSPseudoClass::PopoverOpen => false,
@@ -926,8 +928,21 @@ impl<'a> TElement for BlitzNode<'a> {
             }
 
             if *name == local_name!("hidden") {
+                use style::properties::generated::longhands::visibility::SpecifiedValue as Visibility;
                 use style::values::specified::Display;
-                push_style(PropertyDeclaration::Display(Display::None));
+
+                // `hidden="until-found"` elements stay in layout (so that they can be found and
+                // scrolled to) but aren't painted until they are revealed. Descendants can override
+                // `visibility`, so painting and hit testing also skip the whole subtree (see
+                // `Node::is_hidden_until_found`).
+                //
+                // TODO: use `content-visibility: hidden` (which still paints the element's own box)
+                // once Stylo supports it for the servo engine
+                if value.eq_ignore_ascii_case("until-found") {
+                    push_style(PropertyDeclaration::Visibility(Visibility::Hidden));
+                } else {
+                    push_style(PropertyDeclaration::Display(Display::None));
+                }
             }
         }
     }
@@ -7,11 +7,13 @@ use crate::node::TextBrush;
 
 // Module of type aliases so we can refer to stylo types with nicer names
 pub(crate) mod stylo {
+    pub(crate) use style::computed_values::font_variant_caps::T as FontVariantCaps;
     pub(crate) use style::computed_values::text_wrap_mode::T as TextWrapMode;
     pub(crate) use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
     pub(crate) use style::properties::ComputedValues;
     pub(crate) use style::values::computed::OverflowWrap;
     pub(crate) use style::values::computed::WordBreak;
+    pub(crate) use style::values::computed::font::FontFeatureSettings;
     pub(crate) use style::values::computed::font::FontStretch;
     pub(crate) use style::values::computed::font::FontStyle;
     pub(crate) use style::values::computed::font::FontVariationSettings;
@@ -22,6 +24,7 @@ pub(crate) mod stylo {
 }
 
 pub(crate) mod parley {
+    pub(crate) use parley::FontFeature;
     pub(crate) use parley::FontVariation;
     pub(crate) use parley::fontique::QueryFamily;
     pub(crate) use parley::style::*;
@@ -92,20 +95,70 @@ pub(crate) fn font_variations(input: &stylo::FontVariationSettings) -> Vec<parle
         .collect()
 }
 
+pub(crate) fn font_features(
+    font_variant_caps: stylo::FontVariantCaps,
+    font_feature_settings: &stylo::FontFeatureSettings,
+) -> Vec<parley::FontFeature> {
+    let mut features = Vec::new();
+
+    // OpenType features implied by the `font-variant-*` properties
+    // TODO: font-variant-ligatures, font-variant-numeric, etc
+    if font_variant_caps == stylo::FontVariantCaps::SmallCaps {
+        features.push(parley::FontFeature {
+            tag: u32::from_be_bytes(*b"smcp"),
+            value: 1,
+        });
+    }
+
+    // Raw `font-feature-settings` take precedence over `font-variant-*` so they must come last
+    features.extend(font_feature_settings.0.iter().map(|f| parley::FontFeature {
+        tag: f.tag.0,
+        value: f.value as u16,
+    }));
+
+    features
+}
+
 pub(crate) fn white_space_collapse(input: stylo::WhiteSpaceCollapse) -> parley::WhiteSpaceCollapse {
     match input {
         stylo::WhiteSpaceCollapse::Collapse => parley::WhiteSpaceCollapse::Collapse,
         stylo::WhiteSpaceCollapse::Preserve => parley::WhiteSpaceCollapse::Preserve,
 
-        // TODO: Implement PreserveBreaks and BreakSpaces modes
+        // TODO: Implement PreserveBreaks mode
         stylo::WhiteSpaceCollapse::PreserveBreaks => parley::WhiteSpaceCollapse::Preserve,
+        // Break opportunities after each space are added when building the inline layout
         stylo::WhiteSpaceCollapse::BreakSpaces => parley::WhiteSpaceCollapse::Preserve,
     }
 }
 
+pub(crate) fn word_break(input: stylo::WordBreak) -> parley::WordBreakStrength {
+    match input {
+        stylo::WordBreak::Normal => parley::WordBreakStrength::Normal,
+        stylo::WordBreak::BreakAll => parley::WordBreakStrength::BreakAll,
+        stylo::WordBreak::KeepAll => parley::WordBreakStrength::KeepAll,
+    }
+}
+
+/// Note that `overflow-wrap` only introduces break opportunities where a line would otherwise
+/// overflow, so long unbreakable strings (URLs, hashes, etc) are wrapped rather than overflowing.
+pub(crate) fn overflow_wrap(input: stylo::OverflowWrap) -> parley::OverflowWrap {
+    match input {
+        stylo::OverflowWrap::Normal => parley::OverflowWrap::Normal,
+        stylo::OverflowWrap::BreakWord => parley::OverflowWrap::BreakWord,
+        stylo::OverflowWrap::Anywhere => parley::OverflowWrap::Anywhere,
+    }
+}
+
+/// Convert a language tag (from the `lang` attribute) into a locale for shaping and font fallback.
+/// Tags which aren't well-formed are treated as an unknown language.
+pub(crate) fn locale(lang: &str) -> Option<parley::Language> {
+    parley::Language::parse(lang).ok()
+}
+
 pub(crate) fn style(
     span_id: usize,
     style: &stylo::ComputedValues,
+    lang: Option<&str>,
 ) -> parley::TextStyle<'static, TextBrush> {
     let font_styles = style.get_font();
     let itext_styles = style.get_inherited_text();
@@ -129,6 +182,10 @@ pub(crate) fn style(
     let font_style = self::font_style(font_styles.font_style);
     let font_width = self::font_width(font_styles.font_stretch);
     let font_variations = self::font_variations(&font_styles.font_variation_settings);
+    let font_features = self::font_features(
+        font_styles.font_variant_caps,
+        &font_styles.font_feature_settings,
+    );
 
     // Convert font family
     let families: Vec<_> = font_styles
@@ -161,16 +218,8 @@ pub(crate) fn style(
         .collect();
 
     // Wrapping and breaking
-    let word_break = match itext_styles.word_break {
-        stylo::WordBreak::Normal => parley::WordBreakStrength::Normal,
-        stylo::WordBreak::BreakAll => parley::WordBreakStrength::BreakAll,
-        stylo::WordBreak::KeepAll => parley::WordBreakStrength::KeepAll,
-    };
-    let overflow_wrap = match itext_styles.overflow_wrap {
-        stylo::OverflowWrap::Normal => parley::OverflowWrap::Normal,
-        stylo::OverflowWrap::BreakWord => parley::OverflowWrap::BreakWord,
-        stylo::OverflowWrap::Anywhere => parley::OverflowWrap::Anywhere,
-    };
+    let word_break = self::word_break(itext_styles.word_break);
+    let overflow_wrap = self::overflow_wrap(itext_styles.overflow_wrap);
     let text_wrap_mode = match itext_styles.text_wrap_mode {
         stylo::TextWrapMode::Wrap => parley::TextWrapMode::Wrap,
         stylo::TextWrapMode::Nowrap => parley::TextWrapMode::NoWrap,
@@ -184,8 +233,8 @@ pub(crate) fn style(
         font_style,
         font_weight,
         font_variations: parley::FontSettings::List(Cow::Owned(font_variations)),
-        font_features: parley::FontSettings::List(Cow::Borrowed(&[])),
-        locale: Default::default(),
+        font_features: parley::FontSettings::List(Cow::Owned(font_features)),
+        locale: lang.and_then(self::locale),
         line_height,
         word_spacing: Default::default(),
         letter_spacing,
@@ -211,3 +260,47 @@ pub(crate) fn style(
         strikethrough_brush: Default::default(),
     }
 }
+
+#[test]
+fn small_caps_enables_smcp_feature() {
+    let settings = stylo::FontFeatureSettings::normal();
+
+    let features = font_features(stylo::FontVariantCaps::Normal, &settings);
+    assert!(features.is_empty());
+
+    let features = font_features(stylo::FontVariantCaps::SmallCaps, &settings);
+    assert_eq!(features.len(), 1);
+    assert_eq!(features[0].tag, u32::from_be_bytes(*b"smcp"));
+    assert_eq!(features[0].value, 1);
+}
+
+#[test]
+fn overflow_wrap_breaks_long_words() {
+    use crate::qual_name;
+    use crate::test_utils::doc_with_body;
+
+    let (doc, divs) = doc_with_body(800, 600, "", |mutr, body| {
+        let word = "a".repeat(50);
+        let divs = ["normal", "anywhere", "break-word"].map(|overflow_wrap| {
+            let div = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!("width: 50px; font-size: 10px; overflow-wrap: {overflow_wrap}");
+            mutr.set_attribute(div, qual_name!("style"), &style);
+            let text = mutr.create_text_node(&word);
+            mutr.append_children(div, &[text]);
+            div
+        });
+        mutr.append_children(body, &divs);
+        divs
+    });
+
+    let line_count = |id: usize| {
+        let element = doc.nodes[id].element_data().unwrap();
+        element.inline_layout_data.as_ref().unwrap().layout.len()
+    };
+
+    // Without a break opportunity the word overflows on a single line
+    let [normal, anywhere, break_word] = divs.map(line_count);
+    assert_eq!(normal, 1);
+    assert!(anywhere > 1);
+    assert!(break_word > 1);
+}
@@ -0,0 +1,37 @@
+//! Helpers for building documents in tests. These are available to other crates (for use in their
+//! own tests) with the `test-utils` feature.
+
+use crate::{BaseDocument, DocumentConfig, DocumentMutator, qual_name};
+use blitz_traits::shell::{ColorScheme, Viewport};
+
+/// Create a `width` x `height` document with `css` in a `<style>` element, and a `<body>` whose
+/// contents are created by `build` (which is passed the body's id). The document is resolved.
+///
+/// The document has a scale of 1.0. Use [`BaseDocument::set_viewport`] (and resolve the document
+/// again) to paint it at a different scale.
+pub fn doc_with_body<T>(
+    width: u32,
+    height: u32,
+    css: &str,
+    build: impl FnOnce(&mut DocumentMutator<'_>, usize) -> T,
+) -> (BaseDocument, T) {
+    let mut doc = BaseDocument::new(DocumentConfig {
+        viewport: Some(Viewport::new(width, height, 1.0, ColorScheme::Light)),
+        ..Default::default()
+    });
+    let mut mutr = doc.mutate();
+    let html = mutr.create_element(qual_name!("html", html), vec![]);
+    if !css.is_empty() {
+        let style = mutr.create_element(qual_name!("style", html), vec![]);
+        let css = mutr.create_text_node(css);
+        mutr.append_children(style, &[css]);
+        mutr.append_children(html, &[style]);
+    }
+    let body = mutr.create_element(qual_name!("body", html), vec![]);
+    let built = build(&mut mutr, body);
+    mutr.append_children(html, &[body]);
+    mutr.append_children(0, &[html]);
+    drop(mutr);
+    doc.resolve(0.0);
+    (doc, built)
+}
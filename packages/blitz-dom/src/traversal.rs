@@ -219,6 +219,78 @@ impl BaseDocument {
         }
     }
 
+    /// Like [`next_node`](Self::next_node), but searches backwards through the document
+    pub fn prev_node(&self, start: &Node, mut filter: impl FnMut(&Node) -> bool) -> Option<usize> {
+        let start_id = start.id;
+        let mut node = start;
+        loop {
+            let prev = if let Some(parent) = node.parent_node() {
+                let self_idx = parent
+                    .children
+                    .iter()
+                    .position(|id| *id == node.id)
+                    .unwrap();
+                match self_idx.checked_sub(1) {
+                    // Previous is the last descendant of the previous sibling
+                    Some(sibling_idx) => {
+                        self.last_descendant(&self.nodes[parent.children[sibling_idx]])
+                    }
+                    // Previous is parent
+                    None => parent,
+                }
+            }
+            // Continue search from the end of the document
+            else {
+                self.last_descendant(node)
+            };
+
+            if filter(prev) {
+                return Some(prev.id);
+            } else if prev.id == start_id {
+                return None;
+            }
+
+            node = prev;
+        }
+    }
+
+    fn last_descendant<'a>(&'a self, mut node: &'a Node) -> &'a Node {
+        while let Some(&last_child_id) = node.children.last() {
+            node = &self.nodes[last_child_id];
+        }
+        node
+    }
+
+    /// Whether `node_id` is `ancestor_id` or one of its DOM descendants
+    pub fn is_inclusive_descendant(&self, node_id: usize, ancestor_id: usize) -> bool {
+        let mut maybe_id = Some(node_id);
+        while let Some(id) = maybe_id {
+            if id == ancestor_id {
+                return true;
+            }
+            maybe_id = self.nodes[id].parent;
+        }
+        false
+    }
+
+    /// The node and its DOM ancestors, ordered from the root down to the node
+    pub fn node_ancestors(&self, node_id: usize) -> Vec<usize> {
+        let mut ancestors = Vec::with_capacity(12);
+        let mut maybe_id = Some(node_id);
+        while let Some(id) = maybe_id {
+            ancestors.push(id);
+            maybe_id = self.nodes[id].parent;
+        }
+        ancestors.reverse();
+        ancestors
+    }
+
+    pub fn maybe_node_ancestors(&self, node_id: Option<usize>) -> Vec<usize> {
+        node_id
+            .map(|id| self.node_ancestors(id))
+            .unwrap_or_default()
+    }
+
     pub fn node_layout_ancestors(&self, node_id: usize) -> Vec<usize> {
         let mut ancestors = Vec::with_capacity(12);
         let mut maybe_id = Some(node_id);
@@ -8,16 +8,39 @@ use url::Url;
 #[derive(Clone)]
 pub(crate) struct DocumentUrl {
     base_url: ServoArc<Url>,
+    /// The URL specified by the document's `<base href>` element (if any). Overrides `base_url`
+    /// when resolving relative URLs.
+    base_element_url: Option<ServoArc<Url>>,
 }
 
 impl DocumentUrl {
+    /// The URL against which relative URLs are resolved
+    fn effective_base_url(&self) -> &ServoArc<Url> {
+        self.base_element_url.as_ref().unwrap_or(&self.base_url)
+    }
+
     /// Create a stylo `UrlExtraData` from the URL
     pub(crate) fn url_extra_data(&self) -> UrlExtraData {
-        UrlExtraData(ServoArc::clone(&self.base_url))
+        UrlExtraData(ServoArc::clone(self.effective_base_url()))
     }
 
     pub(crate) fn resolve_relative(&self, raw: &str) -> Option<url::Url> {
-        self.base_url.join(raw).ok()
+        self.effective_base_url().join(raw).ok()
+    }
+
+    /// Set the fragment of the document's URL (for same-document navigations)
+    pub(crate) fn set_fragment(&mut self, fragment: Option<&str>) {
+        let mut url = (*self.base_url).clone();
+        url.set_fragment(fragment);
+        self.base_url = ServoArc::new(url);
+    }
+
+    /// Set (or unset) the base URL from a `<base href>` element. The `href` is resolved
+    /// against the document's own URL.
+    pub(crate) fn set_base_element_href(&mut self, href: Option<&str>) {
+        self.base_element_url = href
+            .and_then(|href| self.base_url.join(href).ok())
+            .map(ServoArc::new);
     }
 }
 
@@ -30,19 +53,26 @@ impl FromStr for DocumentUrl {
     type Err = <Url as FromStr>::Err;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let base_url = ServoArc::new(Url::parse(s)?);
-        Ok(Self { base_url })
+        Ok(Self {
+            base_url,
+            base_element_url: None,
+        })
     }
 }
 impl From<Url> for DocumentUrl {
     fn from(base_url: Url) -> Self {
         Self {
             base_url: ServoArc::new(base_url),
+            base_element_url: None,
         }
     }
 }
 impl From<ServoArc<Url>> for DocumentUrl {
     fn from(base_url: ServoArc<Url>) -> Self {
-        Self { base_url }
+        Self {
+            base_url,
+            base_element_url: None,
+        }
     }
 }
 impl Deref for DocumentUrl {
@@ -1,6 +1,7 @@
 use crate::node::{Node, NodeData};
 use color::{AlphaColor, Srgb};
 use style::color::AbsoluteColor;
+use style::values::computed::Image;
 
 pub type Color = AlphaColor<Srgb>;
 
@@ -19,6 +20,8 @@ pub(crate) static FONT_DB: LazyLock<Arc<fontdb::Database>> = LazyLock::new(|| {
 pub enum ImageType {
     Image,
     Background(usize),
+    /// An image referenced by `cursor: url(...)` (the index of the image in the document's cursor images)
+    Cursor(usize),
 }
 
 /// A point
@@ -102,8 +105,23 @@ pub fn walk_tree(indent: usize, node: &Node) {
 
 #[cfg(feature = "svg")]
 pub(crate) fn parse_svg(source: &[u8]) -> Result<usvg::Tree, usvg::Error> {
+    parse_svg_with_current_color(source, None)
+}
+
+/// Parse an SVG image, resolving `currentColor` to `current_color` (if any) rather than to the
+/// `color` specified within the image
+#[cfg(feature = "svg")]
+pub(crate) fn parse_svg_with_current_color(
+    source: &[u8],
+    current_color: Option<Color>,
+) -> Result<usvg::Tree, usvg::Error> {
+    let style_sheet = current_color.map(|color| {
+        let [r, g, b, a] = color.to_rgba8().to_u8_array();
+        format!("svg {{ color: rgba({r}, {g}, {b}, {}) }}", a as f32 / 255.0)
+    });
     let options = usvg::Options {
         fontdb: Arc::clone(&*FONT_DB),
+        style_sheet,
         ..Default::default()
     };
 
@@ -111,6 +129,28 @@ pub(crate) fn parse_svg(source: &[u8]) -> Result<usvg::Tree, usvg::Error> {
     Ok(tree)
 }
 
+/// Whether the source of an SVG image refers to `currentColor` (and so could follow the color of
+/// the element that it is painted for)
+#[cfg(feature = "svg")]
+pub(crate) fn svg_uses_current_color(source: &[u8]) -> bool {
+    const CURRENT_COLOR: &[u8] = b"currentcolor";
+    source
+        .windows(CURRENT_COLOR.len())
+        .any(|window| window.eq_ignore_ascii_case(CURRENT_COLOR))
+}
+
+/// Resolve an `image-set()` to the candidate which the style engine selected for the device pixel
+/// ratio, along with the candidate's resolution (in image pixels per CSS pixel). Other images are
+/// returned as they are, with a resolution of `1.0`.
+pub fn select_image_set_candidate(image: &Image) -> (&Image, f32) {
+    if let Image::ImageSet(image_set) = image {
+        if let Some(item) = image_set.items.get(image_set.selected_index) {
+            return (&item.image, item.resolution.dppx());
+        }
+    }
+    (image, 1.0)
+}
+
 pub trait ToColorColor {
     /// Converts a color into the `AlphaColor<Srgb>` type from the `color` crate
     fn as_color_color(&self) -> Color;
@@ -59,10 +59,13 @@ impl<D: 'static> Provider<D> {
                 (request.url.to_string(), Bytes::from(file_content))
             }
             _ => {
-                let response = client
+                let mut builder = client
                     .request(request.method, request.url)
-                    .headers(request.headers)
-                    .header("Content-Type", request.content_type.as_str())
+                    .headers(request.headers);
+                if !request.content_type.is_empty() {
+                    builder = builder.header("Content-Type", request.content_type.as_str());
+                }
+                let response = builder
                     .header("User-Agent", USER_AGENT)
                     .apply_body(request.body, request.content_type.as_str())
                     .await
@@ -0,0 +1,182 @@
+//! A [`PaintScene`] decorator which draws shapes with crisp (non-antialiased) edges
+//!
+//! [`PaintScene`] has no way to turn off anti-aliasing for individual draws, so instead the outlines
+//! of filled and stroked shapes are snapped to the device pixel grid. Pixel-aligned edges don't
+//! cover any pixel partially, so they are drawn without anti-aliasing. This makes straight,
+//! axis-aligned edges (as in pixel art and most diagrams) fully crisp, while curves and diagonal
+//! edges are only snapped to whole pixels.
+//!
+//! TODO: disable anti-aliasing in the renderer once `anyrender` supports a per-draw hint
+
+use anyrender::{Glyph, NormalizedCoord, PaintRef, PaintScene};
+use kurbo::{Affine, BezPath, PathEl, Point, Rect, Shape, Stroke, StrokeOpts};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+
+/// The tolerance used when flattening curves and stroking shapes
+const TOLERANCE: f64 = 0.1;
+
+/// Wraps any [`PaintScene`], snapping the outlines of filled and stroked shapes to the device pixel
+/// grid before forwarding them. Every other command is forwarded unchanged.
+pub(crate) struct CrispEdgesScene<'s, S: PaintScene> {
+    inner: &'s mut S,
+}
+
+impl<'s, S: PaintScene> CrispEdgesScene<'s, S> {
+    pub(crate) fn new(inner: &'s mut S) -> Self {
+        Self { inner }
+    }
+}
+
+/// Transform a path into device space and round each of its points to the nearest pixel corner
+fn snap_to_pixels(transform: Affine, path: impl IntoIterator<Item = PathEl>) -> BezPath {
+    let snap = |point: Point| {
+        let point = transform * point;
+        Point::new(point.x.round(), point.y.round())
+    };
+    path.into_iter()
+        .map(|el| match el {
+            PathEl::MoveTo(p) => PathEl::MoveTo(snap(p)),
+            PathEl::LineTo(p) => PathEl::LineTo(snap(p)),
+            PathEl::QuadTo(p1, p2) => PathEl::QuadTo(snap(p1), snap(p2)),
+            PathEl::CurveTo(p1, p2, p3) => PathEl::CurveTo(snap(p1), snap(p2), snap(p3)),
+            PathEl::ClosePath => PathEl::ClosePath,
+        })
+        .collect()
+}
+
+impl<S: PaintScene> PaintScene for CrispEdgesScene<'_, S> {
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.inner.push_layer(blend, alpha, transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.inner.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        // Fill the (snapped) outline of the stroke instead
+        let outline = kurbo::stroke(
+            shape.path_elements(TOLERANCE),
+            style,
+            &StrokeOpts::default(),
+            TOLERANCE,
+        );
+        let brush_transform = transform * brush_transform.unwrap_or_default();
+        self.inner.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            brush,
+            Some(brush_transform),
+            &snap_to_pixels(transform, outline),
+        );
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush_transform = transform * brush_transform.unwrap_or_default();
+        self.inner.fill(
+            style,
+            Affine::IDENTITY,
+            brush,
+            Some(brush_transform),
+            &snap_to_pixels(transform, shape.path_elements(TOLERANCE)),
+        );
+    }
+
+    fn draw_glyphs<'a, 's2: 'a>(
+        &'s2 mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        self.inner.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.inner
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+}
+
+#[test]
+fn crisp_edges_have_no_partially_covered_pixels() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use kurbo::Join;
+
+    // A filled and outlined square which doesn't line up with the pixel grid
+    fn draw_square(scene: &mut impl PaintScene) {
+        let square = Rect::new(4.3, 4.3, 15.6, 15.6);
+        let transform = Affine::scale(1.5);
+        scene.fill(Fill::NonZero, transform, Color::BLACK, None, &square);
+        let stroke = Stroke::new(1.3).with_join(Join::Miter);
+        scene.stroke(&stroke, transform, Color::BLACK, None, &square);
+    }
+
+    let render = |crisp: bool| {
+        anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+            |scene| match crisp {
+                true => draw_square(&mut CrispEdgesScene::new(scene)),
+                false => draw_square(scene),
+            },
+            32,
+            32,
+        )
+    };
+    let partially_covered = |pixels: Vec<u8>| {
+        pixels
+            .chunks(4)
+            .filter(|pixel| pixel[3] != 0 && pixel[3] != 255)
+            .count()
+    };
+
+    assert!(partially_covered(render(false)) > 0);
+    assert_eq!(partially_covered(render(true)), 0);
+}
@@ -1,6 +1,9 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use anyrender::PaintScene;
 use blitz_dom::BaseDocument;
-use kurbo::{Affine, Rect, Vec2};
+use kurbo::{Affine, BezPath, Point, Rect, Stroke, Vec2};
+use taffy::DetailedGridTracksInfo;
 
 use crate::color::Color;
 
@@ -133,3 +136,284 @@ fn draw_cutout_rect(
     fill(bt + Vec2::new(ew.left, 0.0), inner_w, ew.top); // top
     fill(bt + Vec2::new(ew.left, bottom), inner_w, ew.bottom); // bottom
 }
+
+/// The colors cycled through by successive frames when paint flashing is enabled
+const PAINT_FLASH_COLORS: [Color; 3] = [
+    Color::from_rgba8(255, 0, 255, 64),
+    Color::from_rgba8(0, 255, 255, 64),
+    Color::from_rgba8(255, 255, 0, 64),
+];
+static PAINT_FLASH_FRAME: AtomicUsize = AtomicUsize::new(0);
+
+/// Renders a translucent overlay over the border box of every node that was repainted this frame.
+/// The color changes each frame so that repeated repaints are visible.
+pub(crate) fn render_paint_flash(scene: &mut impl PaintScene, dom: &BaseDocument, scale: f64) {
+    let frame = PAINT_FLASH_FRAME.fetch_add(1, Ordering::Relaxed);
+    let color = PAINT_FLASH_COLORS[frame % PAINT_FLASH_COLORS.len()];
+    let viewport_scroll = dom.as_ref().viewport_scroll();
+
+    for &node_id in dom.repainted_nodes() {
+        let Some(node) = dom.get_node(node_id) else {
+            continue;
+        };
+        let position = node.absolute_position(0.0, 0.0);
+        let size = node.final_layout.size;
+        let rect = Rect::new(
+            f64::from(position.x) - viewport_scroll.x,
+            f64::from(position.y) - viewport_scroll.y,
+            f64::from(position.x + size.width) - viewport_scroll.x,
+            f64::from(position.y + size.height) - viewport_scroll.y,
+        );
+        let transform = Affine::scale(scale);
+        scene.fill(peniko::Fill::NonZero, transform, color, None, &rect);
+    }
+}
+
+/// The color used to shade the gaps between flex and grid items
+pub(crate) const GAP_OVERLAY_COLOR: Color = Color::from_rgba8(191, 64, 191, 96); // purple
+
+/// How the items of a container are arranged (which determines where its gaps are)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GapLayout {
+    Grid,
+    FlexRow,
+    FlexColumn,
+}
+
+/// Compute the regions between the items of a flex or grid container which are occupied by gaps.
+///
+/// Rows (or for a column flexbox, columns) are found by merging the extents of the items, and the
+/// space between them is a row gap spanning the container's content box. Column gaps are the spaces
+/// between the items within each flex line, or between the columns of a grid.
+pub(crate) fn gap_regions(
+    items: &[Rect],
+    content_box: Rect,
+    layout: GapLayout,
+    column_gap: bool,
+    row_gap: bool,
+) -> Vec<Rect> {
+    if layout == GapLayout::FlexColumn {
+        let transpose = |rect: Rect| Rect::new(rect.y0, rect.x0, rect.y1, rect.x1);
+        let items: Vec<Rect> = items.iter().copied().map(transpose).collect();
+        let regions = gap_regions(
+            &items,
+            transpose(content_box),
+            GapLayout::FlexRow,
+            row_gap,
+            column_gap,
+        );
+        return regions.into_iter().map(transpose).collect();
+    }
+
+    let rows = merge_intervals(items.iter().map(|item| (item.y0, item.y1)));
+    let mut regions = Vec::new();
+    if row_gap {
+        regions
+            .extend(holes(&rows).map(|(y0, y1)| Rect::new(content_box.x0, y0, content_box.x1, y1)));
+    }
+    if column_gap {
+        if layout == GapLayout::Grid {
+            let columns = merge_intervals(items.iter().map(|item| (item.x0, item.x1)));
+            regions.extend(
+                holes(&columns).map(|(x0, x1)| Rect::new(x0, content_box.y0, x1, content_box.y1)),
+            );
+        } else {
+            for &(y0, y1) in &rows {
+                let line = merge_intervals(
+                    items
+                        .iter()
+                        .filter(|item| item.y0 >= y0 && item.y1 <= y1)
+                        .map(|item| (item.x0, item.x1)),
+                );
+                regions.extend(holes(&line).map(|(x0, x1)| Rect::new(x0, y0, x1, y1)));
+            }
+        }
+    }
+
+    regions
+}
+
+/// Merge overlapping intervals into a sorted list of disjoint intervals
+fn merge_intervals(intervals: impl Iterator<Item = (f64, f64)>) -> Vec<(f64, f64)> {
+    let mut intervals: Vec<(f64, f64)> = intervals.collect();
+    intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut merged: Vec<(f64, f64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// The spaces between a sorted list of disjoint intervals
+fn holes(intervals: &[(f64, f64)]) -> impl Iterator<Item = (f64, f64)> + '_ {
+    intervals.windows(2).map(|pair| (pair[0].1, pair[1].0))
+}
+
+/// The color of the lines (and line number labels) drawn by the grid overlay
+pub(crate) const GRID_LINE_COLOR: Color = Color::from_rgba8(155, 81, 224, 255); // purple
+const GRID_LABEL_TEXT_COLOR: Color = Color::from_rgba8(255, 255, 255, 255);
+
+/// The start and end position of each track of a grid (in scaled pixels), with the first track
+/// starting after the leading gutter at `origin`.
+///
+/// TODO: account for the offsets added to tracks by `justify-content` and `align-content`
+pub(crate) fn grid_track_extents(
+    tracks: &DetailedGridTracksInfo,
+    origin: f64,
+    scale: f64,
+) -> Vec<(f64, f64)> {
+    let mut offset = origin;
+    tracks
+        .sizes
+        .iter()
+        .enumerate()
+        .map(|(idx, size)| {
+            offset += f64::from(tracks.gutters.get(idx).copied().unwrap_or(0.0)) * scale;
+            let start = offset;
+            offset += f64::from(*size) * scale;
+            (start, offset)
+        })
+        .collect()
+}
+
+/// The number of the grid line at the start of the track at `track_idx` (or at the end of the last
+/// track if `track_idx` is the number of tracks). Lines are numbered from the start of the explicit
+/// grid, so implicit lines before it are unnumbered.
+pub(crate) fn grid_line_number(tracks: &DetailedGridTracksInfo, track_idx: usize) -> Option<usize> {
+    (track_idx + 1)
+        .checked_sub(usize::from(tracks.negative_implicit_tracks))
+        .filter(|number| *number > 0)
+}
+
+/// The width and height of a digit in a line number label (in CSS pixels)
+const DIGIT_SIZE: (f64, f64) = (4.0, 8.0);
+/// The space around and between the digits of a line number label (in CSS pixels)
+const LABEL_PADDING: f64 = 2.0;
+
+/// The segments of a seven segment display lit for each digit (bits 0 to 6 are the top,
+/// top-right, bottom-right, bottom, bottom-left, top-left and middle segments)
+const DIGIT_SEGMENTS: [u8; 10] = [0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F];
+
+/// Draw a small label containing `number` with its top-left corner at `origin` (in scaled pixels).
+///
+/// The digits are drawn as line segments rather than text so that labels don't depend on the
+/// fonts available to the document.
+pub(crate) fn draw_grid_line_label(
+    scene: &mut impl PaintScene,
+    transform: Affine,
+    origin: Point,
+    number: usize,
+    scale: f64,
+) {
+    let digits = number.to_string();
+    let (digit_width, digit_height) = DIGIT_SIZE;
+    let width = LABEL_PADDING + digits.len() as f64 * (digit_width + LABEL_PADDING);
+    let height = digit_height + 2.0 * LABEL_PADDING;
+
+    let transform = transform * Affine::translate(origin.to_vec2()) * Affine::scale(scale);
+    let background = Rect::new(0.0, 0.0, width, height);
+    scene.fill(
+        peniko::Fill::NonZero,
+        transform,
+        GRID_LINE_COLOR,
+        None,
+        &background,
+    );
+
+    let mut path = BezPath::new();
+    for (idx, digit) in digits.bytes().enumerate() {
+        let x0 = LABEL_PADDING + idx as f64 * (digit_width + LABEL_PADDING);
+        let x1 = x0 + digit_width;
+        let y0 = LABEL_PADDING;
+        let y1 = y0 + digit_height / 2.0;
+        let y2 = y0 + digit_height;
+        let segments = [
+            ((x0, y0), (x1, y0)),
+            ((x1, y0), (x1, y1)),
+            ((x1, y1), (x1, y2)),
+            ((x0, y2), (x1, y2)),
+            ((x0, y1), (x0, y2)),
+            ((x0, y0), (x0, y1)),
+            ((x0, y1), (x1, y1)),
+        ];
+        let lit = DIGIT_SEGMENTS[usize::from(digit - b'0')];
+        for (bit, (from, to)) in segments.into_iter().enumerate() {
+            if lit & (1 << bit) != 0 {
+                path.move_to(from);
+                path.line_to(to);
+            }
+        }
+    }
+    scene.stroke(
+        &Stroke::new(1.0),
+        transform,
+        GRID_LABEL_TEXT_COLOR,
+        None,
+        &path,
+    );
+}
+
+#[test]
+fn gap_regions_follow_flex_lines() {
+    // Three items in a wrapping row flexbox with a 10px gap. The first line has two items of
+    // different heights, and the second line a single item.
+    let items = [
+        Rect::new(0.0, 0.0, 50.0, 20.0),
+        Rect::new(60.0, 0.0, 100.0, 30.0),
+        Rect::new(0.0, 40.0, 70.0, 60.0),
+    ];
+    let content_box = Rect::new(0.0, 0.0, 120.0, 60.0);
+
+    let regions = gap_regions(&items, content_box, GapLayout::FlexRow, true, true);
+    assert_eq!(
+        regions,
+        [
+            Rect::new(0.0, 30.0, 120.0, 40.0),
+            Rect::new(50.0, 0.0, 60.0, 30.0)
+        ]
+    );
+
+    // Only the enabled gaps are shaded
+    let regions = gap_regions(&items, content_box, GapLayout::FlexRow, false, true);
+    assert_eq!(regions, [Rect::new(0.0, 30.0, 120.0, 40.0)]);
+
+    // A column flexbox is the same with the axes swapped
+    let transpose = |rect: Rect| Rect::new(rect.y0, rect.x0, rect.y1, rect.x1);
+    let items = items.map(transpose);
+    let regions = gap_regions(
+        &items,
+        transpose(content_box),
+        GapLayout::FlexColumn,
+        true,
+        true,
+    );
+    assert_eq!(
+        regions,
+        [
+            Rect::new(30.0, 0.0, 40.0, 120.0),
+            Rect::new(0.0, 50.0, 30.0, 60.0)
+        ]
+    );
+}
+
+#[test]
+fn grid_tracks_are_offset_by_gutters() {
+    // Three 100px columns with 10px gaps and an implicit column before the explicit grid
+    let tracks = DetailedGridTracksInfo {
+        negative_implicit_tracks: 1,
+        explicit_tracks: 2,
+        positive_implicit_tracks: 0,
+        gutters: vec![0.0, 10.0, 10.0, 0.0],
+        sizes: vec![100.0, 100.0, 100.0],
+    };
+
+    let extents = grid_track_extents(&tracks, 5.0, 2.0);
+    assert_eq!(extents, [(5.0, 205.0), (225.0, 425.0), (445.0, 645.0)]);
+
+    let numbers: Vec<_> = (0..=3).map(|idx| grid_line_number(&tracks, idx)).collect();
+    assert_eq!(numbers, [None, Some(1), Some(2), Some(3)]);
+}
@@ -0,0 +1,263 @@
+//! A [`PaintScene`] decorator which draws text as filled glyph outlines
+//!
+//! Glyph rasterization differs between renderers (and between platforms, as hinting and glyph caches
+//! vary), which makes it hard to compare rendered text in tests. Instead, this reads the outline of
+//! each glyph from the font (without hinting) and fills it as a path, snapped to the device pixel grid
+//! in the same way as [`CrispEdgesScene`]. Text drawn this way is identical on every platform.
+//!
+//! This is intended for testing. Small text is noticeably distorted by the snapping, and curved and
+//! diagonal edges are still anti-aliased by the renderer, so it should not be used for display.
+
+use anyrender::{Glyph, NormalizedCoord, PaintRef, PaintScene};
+use kurbo::{Affine, BezPath, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+use skrifa::instance::{LocationRef, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::{GlyphId, MetadataProvider};
+
+use crate::crisp::CrispEdgesScene;
+
+/// Wraps any [`PaintScene`], replacing glyph runs with fills of the glyphs' (pixel-snapped) outlines.
+/// Every other command is forwarded unchanged.
+pub(crate) struct GlyphOutlineScene<'s, S: PaintScene> {
+    inner: &'s mut S,
+}
+
+impl<'s, S: PaintScene> GlyphOutlineScene<'s, S> {
+    pub(crate) fn new(inner: &'s mut S) -> Self {
+        Self { inner }
+    }
+}
+
+/// Collects the outline of a glyph into a [`BezPath`]
+struct BezPathPen(BezPath);
+
+impl OutlinePen for BezPathPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to((x as f64, y as f64));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to((x as f64, y as f64));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.0
+            .quad_to((cx0 as f64, cy0 as f64), (x as f64, y as f64));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.0.curve_to(
+            (cx0 as f64, cy0 as f64),
+            (cx1 as f64, cy1 as f64),
+            (x as f64, y as f64),
+        );
+    }
+
+    fn close(&mut self) {
+        self.0.close_path();
+    }
+}
+
+/// The outlines of `glyphs` as a single path, in the coordinate space of the glyph run
+fn glyph_run_outline(
+    font: &FontData,
+    font_size: f32,
+    normalized_coords: &[NormalizedCoord],
+    glyph_transform: Option<Affine>,
+    glyphs: impl Iterator<Item = Glyph>,
+) -> BezPath {
+    let mut path = BezPath::new();
+    let Ok(font_ref) = skrifa::FontRef::from_index(font.data.as_ref(), font.index) else {
+        return path;
+    };
+    let outlines = font_ref.outline_glyphs();
+    let coords: Vec<skrifa::instance::NormalizedCoord> = normalized_coords
+        .iter()
+        .map(|coord| skrifa::instance::NormalizedCoord::from_bits(*coord))
+        .collect();
+
+    // Outlines are y-up, whereas glyph runs are y-down
+    let glyph_transform = glyph_transform.unwrap_or_default() * Affine::FLIP_Y;
+    for glyph in glyphs {
+        // Glyphs without outlines (e.g. bitmap emoji) are skipped
+        let Some(outline) = outlines.get(GlyphId::new(glyph.id)) else {
+            continue;
+        };
+        let settings = DrawSettings::unhinted(Size::new(font_size), LocationRef::new(&coords));
+        let mut pen = BezPathPen(BezPath::new());
+        if outline.draw(settings, &mut pen).is_err() {
+            continue;
+        }
+        let transform = Affine::translate((glyph.x as f64, glyph.y as f64)) * glyph_transform;
+        path.extend(transform * pen.0);
+    }
+    path
+}
+
+impl<S: PaintScene> PaintScene for GlyphOutlineScene<'_, S> {
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.inner.push_layer(blend, alpha, transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.inner.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.inner
+            .stroke(style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.inner
+            .fill(style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs<'a, 's2: 'a>(
+        &'s2 mut self,
+        font: &'a FontData,
+        font_size: f32,
+        _hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let outline =
+            glyph_run_outline(font, font_size, normalized_coords, glyph_transform, glyphs);
+        if outline.is_empty() {
+            return;
+        }
+
+        // TODO: apply `brush_alpha` to gradient and image brushes
+        let brush = match brush.into() {
+            PaintRef::Solid(color) => PaintRef::Solid(color.multiply_alpha(brush_alpha)),
+            brush => brush,
+        };
+        let mut scene = CrispEdgesScene::new(self.inner);
+        match style.into() {
+            StyleRef::Fill(fill) => scene.fill(fill, transform, brush, None, &outline),
+            StyleRef::Stroke(stroke) => scene.stroke(stroke, transform, brush, None, &outline),
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.inner
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+}
+
+#[test]
+fn glyph_outlines_match_rasterized_text() {
+    use crate::{PaintStats, StatsScene, paint_scene, paint_scene_with_glyph_outlines};
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let css = "body { margin: 8px; font-size: 20px; color: black }";
+    let (doc, ()) = doc_with_body(120, 40, css, |mutr, body| {
+        let text = mutr.create_text_node("Hi");
+        mutr.append_children(body, &[text]);
+    });
+
+    let render = |outlines: bool| {
+        anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+            |scene| match outlines {
+                true => paint_scene_with_glyph_outlines(scene, &doc, 1.0, 120, 40),
+                false => paint_scene(scene, &doc, 1.0, 120, 40),
+            },
+            120,
+            40,
+        )
+    };
+    // The bounds of the pixels covered by text, as (left, top, right, bottom)
+    let ink_bounds = |pixels: &[u8]| {
+        let mut bounds = (usize::MAX, usize::MAX, 0, 0);
+        for (idx, pixel) in pixels.chunks(4).enumerate() {
+            if pixel[3] > 127 {
+                let (x, y) = (idx % 120, idx / 120);
+                bounds = (
+                    bounds.0.min(x),
+                    bounds.1.min(y),
+                    bounds.2.max(x),
+                    bounds.3.max(y),
+                );
+            }
+        }
+        bounds
+    };
+
+    // The text is drawn as fills, so no glyph runs reach the renderer
+    let stats = |outlines: bool| {
+        let mut stats = PaintStats::default();
+        anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+            |scene| {
+                let mut scene = StatsScene::new(scene);
+                match outlines {
+                    true => {
+                        paint_scene(&mut GlyphOutlineScene::new(&mut scene), &doc, 1.0, 120, 40)
+                    }
+                    false => paint_scene(&mut scene, &doc, 1.0, 120, 40),
+                }
+                stats = scene.stats();
+            },
+            120,
+            40,
+        );
+        stats
+    };
+    assert!(stats(false).glyph_runs > 0);
+    assert_eq!(stats(true).glyph_runs, 0);
+    assert!(stats(true).fills > stats(false).fills);
+
+    // The output is deterministic, and covers the same area as the renderer's own glyphs
+    let outlined = render(true);
+    assert_eq!(outlined, render(true));
+    let (expected, actual) = (ink_bounds(&render(false)), ink_bounds(&outlined));
+    assert!(
+        actual.0 < actual.2 && actual.1 < actual.3,
+        "no text was painted"
+    );
+    for (expected, actual) in [
+        (expected.0, actual.0),
+        (expected.1, actual.1),
+        (expected.2, actual.2),
+        (expected.3, actual.3),
+    ] {
+        assert!(expected.abs_diff(actual) <= 1, "{expected:?} != {actual:?}");
+    }
+}
@@ -1,8 +1,9 @@
 use crate::color::{Color, ToColorColor};
-use color::DynamicColor;
+use color::{ColorSpaceTag, DynamicColor, HueDirection};
 use kurbo::{self, Affine, Point, Rect, Vec2};
 use peniko::{self, ColorStop, Gradient, LinearGradientPosition, SweepGradientPosition};
-use style::color::AbsoluteColor;
+use style::color::mix::{ColorInterpolationMethod, HueInterpolationMethod};
+use style::color::{AbsoluteColor, ColorSpace};
 use style::{
     OwnedSlice,
     values::{
@@ -52,7 +53,7 @@ pub(crate) fn to_peniko_gradient(
     scale: f64,
     current_color: &AbsoluteColor,
 ) -> (peniko::Gradient, Option<Affine>) {
-    match gradient {
+    let (gradient, transform) = match gradient {
         // https://developer.mozilla.org/en-US/docs/Web/CSS/gradient/linear-gradient
         GenericGradient::Linear {
             direction,
@@ -74,7 +75,12 @@ pub(crate) fn to_peniko_gradient(
             flags,
             // compat_mode,
             ..
-        } => radial_gradient((shape, position, items, *flags), origin_rect, current_color),
+        } => radial_gradient(
+            (shape, position, items, *flags),
+            origin_rect,
+            scale,
+            current_color,
+        ),
         GenericGradient::Conic {
             angle,
             position,
@@ -82,7 +88,70 @@ pub(crate) fn to_peniko_gradient(
             flags,
             ..
         } => conic_gradient((angle, position, items, *flags), origin_rect, current_color),
+    };
+
+    let (interpolation_cs, hue_direction) = interpolation_method(gradient);
+    let gradient = gradient
+        .with_interpolation_cs(interpolation_cs)
+        .with_hue_direction(hue_direction);
+    (gradient, transform)
+}
+
+/// The color space (and for polar color spaces, the direction around the hue circle) that the colors
+/// of a gradient are interpolated in (<https://drafts.csswg.org/css-images-4/#color-interpolation>)
+fn interpolation_method(gradient: &StyloGradient) -> (ColorSpaceTag, HueDirection) {
+    let (method, flags): (&ColorInterpolationMethod, GradientFlags) = match gradient {
+        GenericGradient::Linear {
+            color_interpolation_method,
+            flags,
+            ..
+        }
+        | GenericGradient::Radial {
+            color_interpolation_method,
+            flags,
+            ..
+        }
+        | GenericGradient::Conic {
+            color_interpolation_method,
+            flags,
+            ..
+        } => (color_interpolation_method, *flags),
+    };
+
+    // Gradients which don't specify a color space interpolate in sRGB, which CSS requires for
+    // gradients between legacy colors (the only colors that have been supported for long enough
+    // that authors rely on how they blend).
+    //
+    // TODO: default to Oklab for gradients with non-legacy colors
+    if flags.contains(GradientFlags::HAS_DEFAULT_COLOR_INTERPOLATION_METHOD) {
+        return (ColorSpaceTag::Srgb, HueDirection::Shorter);
     }
+
+    let color_space = match method.space {
+        ColorSpace::Srgb => ColorSpaceTag::Srgb,
+        ColorSpace::SrgbLinear => ColorSpaceTag::LinearSrgb,
+        ColorSpace::Hsl => ColorSpaceTag::Hsl,
+        ColorSpace::Hwb => ColorSpaceTag::Hwb,
+        ColorSpace::Lab => ColorSpaceTag::Lab,
+        ColorSpace::Lch => ColorSpaceTag::Lch,
+        ColorSpace::Oklab => ColorSpaceTag::Oklab,
+        ColorSpace::Oklch => ColorSpaceTag::Oklch,
+        ColorSpace::DisplayP3 => ColorSpaceTag::DisplayP3,
+        ColorSpace::A98Rgb => ColorSpaceTag::A98Rgb,
+        ColorSpace::ProphotoRgb => ColorSpaceTag::ProphotoRgb,
+        ColorSpace::Rec2020 => ColorSpaceTag::Rec2020,
+        ColorSpace::XyzD50 => ColorSpaceTag::XyzD50,
+        ColorSpace::XyzD65 => ColorSpaceTag::XyzD65,
+    };
+    let hue_direction = match method.hue {
+        HueInterpolationMethod::Shorter => HueDirection::Shorter,
+        HueInterpolationMethod::Longer => HueDirection::Longer,
+        HueInterpolationMethod::Increasing => HueDirection::Increasing,
+        HueInterpolationMethod::Decreasing => HueDirection::Decreasing,
+        // Specified hue interpolation was dropped from CSS
+        HueInterpolationMethod::Specified => HueDirection::Shorter,
+    };
+    (color_space, hue_direction)
 }
 
 fn linear_gradient(
@@ -161,6 +230,7 @@ fn linear_gradient(
 fn radial_gradient(
     gradient: RadialGradient,
     rect: Rect,
+    scale: f64,
     current_color: &AbsoluteColor,
 ) -> (peniko::Gradient, Option<Affine>) {
     let (shape, position, items, flags) = gradient;
@@ -172,99 +242,103 @@ fn radial_gradient(
         peniko::Extend::Pad
     });
 
-    let (width_px, height_px) = (
+    // The geometry of the gradient is resolved in CSS pixels (which color stop lengths are relative to)
+    // and only scaled up to device pixels by the gradient transform
+    let width = rect.width() / scale;
+    let height = rect.height() / scale;
+    let center = Vec2::new(
         position
             .horizontal
-            .resolve(CSSPixelLength::new(rect.width() as f32))
+            .resolve(CSSPixelLength::new(width as f32))
             .px() as f64,
         position
             .vertical
-            .resolve(CSSPixelLength::new(rect.height() as f32))
+            .resolve(CSSPixelLength::new(height as f32))
             .px() as f64,
     );
 
-    let gradient_scale: Option<Vec2> = match shape {
+    // The distances from the center to the nearest and farthest sides of the box (in each axis)
+    let (near_x, far_x) = min_max(center.x.abs(), (width - center.x).abs());
+    let (near_y, far_y) = min_max(center.y.abs(), (height - center.y).abs());
+
+    // The radii of the ending shape (<https://drafts.csswg.org/css-images-3/#radial-gradient-syntax>)
+    let radii: Option<Vec2> = match shape {
         GenericEndingShape::Circle(circle) => {
-            let scale = match circle {
+            let radius = match circle {
                 GenericCircle::Extent(extent) => match extent {
-                    ShapeExtent::FarthestSide => width_px
-                        .max(rect.width() - width_px)
-                        .max(height_px.max(rect.height() - height_px)),
-                    ShapeExtent::ClosestSide => width_px
-                        .min(rect.width() - width_px)
-                        .min(height_px.min(rect.height() - height_px)),
-                    ShapeExtent::FarthestCorner => {
-                        (width_px.max(rect.width() - width_px)
-                            + height_px.max(rect.height() - height_px))
-                            * 0.5_f64.sqrt()
-                    }
-                    ShapeExtent::ClosestCorner => {
-                        (width_px.min(rect.width() - width_px)
-                            + height_px.min(rect.height() - height_px))
-                            * 0.5_f64.sqrt()
-                    }
+                    ShapeExtent::ClosestSide => near_x.min(near_y),
+                    ShapeExtent::FarthestSide => far_x.max(far_y),
+                    ShapeExtent::ClosestCorner => near_x.hypot(near_y),
+                    ShapeExtent::FarthestCorner => far_x.hypot(far_y),
                     _ => 0.0,
                 },
                 GenericCircle::Radius(radius) => radius.0.px() as f64,
             };
-            Some(Vec2::new(scale, scale))
+            Some(Vec2::new(radius, radius))
         }
         GenericEndingShape::Ellipse(ellipse) => match ellipse {
+            // Corner extents use the aspect ratio of the matching side extent, scaled up to pass
+            // through the corner
             GenericEllipse::Extent(extent) => match extent {
-                ShapeExtent::FarthestCorner | ShapeExtent::FarthestSide => {
-                    let mut scale = Vec2::new(
-                        width_px.max(rect.width() - width_px),
-                        height_px.max(rect.height() - height_px),
-                    );
-                    if *extent == ShapeExtent::FarthestCorner {
-                        scale *= 2.0_f64.sqrt();
-                    }
-                    Some(scale)
-                }
-                ShapeExtent::ClosestCorner | ShapeExtent::ClosestSide => {
-                    let mut scale = Vec2::new(
-                        width_px.min(rect.width() - width_px),
-                        height_px.min(rect.height() - height_px),
-                    );
-                    if *extent == ShapeExtent::ClosestCorner {
-                        scale *= 2.0_f64.sqrt();
-                    }
-                    Some(scale)
-                }
+                ShapeExtent::ClosestSide => Some(Vec2::new(near_x, near_y)),
+                ShapeExtent::FarthestSide => Some(Vec2::new(far_x, far_y)),
+                ShapeExtent::ClosestCorner => Some(Vec2::new(near_x, near_y) * 2.0_f64.sqrt()),
+                ShapeExtent::FarthestCorner => Some(Vec2::new(far_x, far_y) * 2.0_f64.sqrt()),
                 _ => None,
             },
             GenericEllipse::Radii(x, y) => Some(Vec2::new(
-                x.0.resolve(CSSPixelLength::new(rect.width() as f32)).px() as f64,
-                y.0.resolve(CSSPixelLength::new(rect.height() as f32)).px() as f64,
+                x.0.resolve(CSSPixelLength::new(width as f32)).px() as f64,
+                y.0.resolve(CSSPixelLength::new(height as f32)).px() as f64,
             )),
         },
     };
 
-    let gradient_transform = {
-        // If the gradient has no valid scale, we don't need to calculate the color stops
-        if let Some(gradient_scale) = gradient_scale {
-            let (first_offset, last_offset) = resolve_length_color_stops(
-                current_color,
-                items,
-                CSSPixelLength::new(gradient_scale.x as f32),
-                &mut gradient,
-                repeating,
-            );
-            let scale = if repeating && gradient.stops.len() >= 2 {
-                (last_offset - first_offset) as f64
-            } else {
-                1.0
-            };
-            Some(
-                Affine::scale_non_uniform(gradient_scale.x * scale, gradient_scale.y * scale)
-                    .then_translate(get_translation(position, rect)),
-            )
-        } else {
-            None
+    // If the gradient has no valid size, we don't need to calculate the color stops
+    let Some(radii) = radii else {
+        return (gradient, None);
+    };
+    let translation = rect.origin().to_vec2() + center * scale;
+
+    // A gradient with a zero-sized ending shape is painted with the color of its last color stop
+    //
+    // TODO: repeating gradients should use the average color of their stops instead
+    if !(radii.x > 0.0 && radii.y > 0.0) {
+        let last_color = items.iter().rev().find_map(|item| match item {
+            GenericGradientItem::SimpleColorStop(color)
+            | GenericGradientItem::ComplexColorStop { color, .. } => {
+                Some(color.resolve_to_absolute(current_color).as_dynamic_color())
+            }
+            GenericGradientItem::InterpolationHint(_) => None,
+        });
+        if let Some(color) = last_color {
+            gradient.stops.push(ColorStop { color, offset: 0.0 });
+            gradient.stops.push(ColorStop { color, offset: 1.0 });
         }
+        return (gradient, Some(Affine::translate(translation)));
+    }
+
+    let (first_offset, last_offset) = resolve_length_color_stops(
+        current_color,
+        items,
+        CSSPixelLength::new(radii.x as f32),
+        &mut gradient,
+        repeating,
+    );
+    let repeat_scale = if repeating && gradient.stops.len() >= 2 {
+        (last_offset - first_offset) as f64
+    } else {
+        1.0
     };
+    let radii = radii * scale * repeat_scale;
+    let gradient_transform =
+        Affine::scale_non_uniform(radii.x, radii.y).then_translate(translation);
 
-    (gradient, gradient_transform)
+    (gradient, Some(gradient_transform))
+}
+
+/// The smaller and the larger of two distances
+fn min_max(a: f64, b: f64) -> (f64, f64) {
+    (a.min(b), a.max(b))
 }
 
 fn conic_gradient(
@@ -337,27 +411,15 @@ fn resolve_color_stops<T>(
     item_resolver: impl Fn(CSSPixelLength, &T) -> Option<f32>,
 ) -> (f32, f32) {
     let mut hint: Option<f32> = None;
+    let offsets = resolve_stop_offsets(items, gradient_length, &item_resolver);
 
     for (idx, item) in items.iter().enumerate() {
         let (color, offset) = match item {
-            GenericGradientItem::SimpleColorStop(color) => {
-                let step = 1.0 / (items.len() as f32 - 1.0);
-                (
-                    color.resolve_to_absolute(current_color).as_dynamic_color(),
-                    step * idx as f32,
-                )
-            }
-            GenericGradientItem::ComplexColorStop { color, position } => {
-                let offset = item_resolver(gradient_length, position);
-                if let Some(offset) = offset {
-                    (
-                        color.resolve_to_absolute(current_color).as_dynamic_color(),
-                        offset,
-                    )
-                } else {
-                    continue;
-                }
-            }
+            GenericGradientItem::SimpleColorStop(color)
+            | GenericGradientItem::ComplexColorStop { color, .. } => (
+                color.resolve_to_absolute(current_color).as_dynamic_color(),
+                offsets[idx].unwrap(),
+            ),
             GenericGradientItem::InterpolationHint(position) => {
                 hint = item_resolver(gradient_length, position);
                 continue;
@@ -473,6 +535,78 @@ fn resolve_color_stops<T>(
     }
 }
 
+/// Resolve the offset of each color stop along the gradient line (as a fraction of its length),
+/// following <https://drafts.csswg.org/css-images-4/#color-stop-fixup>. The result has an entry for
+/// each of `items`, which is `None` for interpolation hints.
+///
+/// Stops without a position are spaced evenly between the positioned stops on either side of them
+/// (with the first and last stops defaulting to the start and end of the line), and a stop is never
+/// positioned before any stop that precedes it.
+fn resolve_stop_offsets<T>(
+    items: &[GradientItem<T>],
+    gradient_length: CSSPixelLength,
+    item_resolver: impl Fn(CSSPixelLength, &T) -> Option<f32>,
+) -> Vec<Option<f32>> {
+    let stop_count = items
+        .iter()
+        .filter(|item| !matches!(item, GenericGradientItem::InterpolationHint(_)))
+        .count();
+
+    // The specified positions (if any) of each color stop
+    let mut positions: Vec<Option<f32>> = Vec::with_capacity(stop_count);
+    for item in items {
+        match item {
+            GenericGradientItem::SimpleColorStop(_) => positions.push(None),
+            GenericGradientItem::ComplexColorStop { position, .. } => {
+                positions.push(item_resolver(gradient_length, position))
+            }
+            GenericGradientItem::InterpolationHint(_) => {}
+        }
+    }
+
+    if let Some(first) = positions.first_mut() {
+        first.get_or_insert(0.0);
+    }
+    if let Some(last) = positions.last_mut() {
+        last.get_or_insert(1.0);
+    }
+
+    // Stops can't be positioned before the stops that precede them
+    let mut max_position = f32::NEG_INFINITY;
+    for position in positions.iter_mut().flatten() {
+        max_position = max_position.max(*position);
+        *position = max_position;
+    }
+
+    // Space runs of unpositioned stops evenly between their positioned neighbours
+    let mut idx = 0;
+    while idx < positions.len() {
+        if positions[idx].is_some() {
+            idx += 1;
+            continue;
+        }
+        let start = positions[idx - 1].unwrap();
+        let run_end = (idx..positions.len())
+            .find(|&i| positions[i].is_some())
+            .unwrap();
+        let end = positions[run_end].unwrap();
+        let step = (end - start) / (run_end - idx + 1) as f32;
+        for (n, position) in positions[idx..run_end].iter_mut().enumerate() {
+            *position = Some(start + step * (n + 1) as f32);
+        }
+        idx = run_end;
+    }
+
+    let mut positions = positions.into_iter();
+    items
+        .iter()
+        .map(|item| match item {
+            GenericGradientItem::InterpolationHint(_) => None,
+            _ => positions.next().flatten(),
+        })
+        .collect()
+}
+
 #[inline]
 fn resolve_angle_color_stops(
     current_color: &AbsoluteColor,
@@ -516,3 +650,51 @@ fn get_translation(
                 .px() as f64,
     )
 }
+
+#[test]
+fn unpositioned_stops_are_spaced_between_positioned_ones() {
+    let stop = |position: Option<f32>| match position {
+        Some(position) => GenericGradientItem::ComplexColorStop {
+            color: GenericColor::CurrentColor,
+            position,
+        },
+        None => GenericGradientItem::SimpleColorStop(GenericColor::CurrentColor),
+    };
+    let offsets = |items: &[GradientItem<f32>]| {
+        resolve_stop_offsets(items, CSSPixelLength::new(100.0), |_, position| {
+            Some(*position)
+        })
+    };
+
+    // By default stops are distributed evenly along the whole line
+    assert_eq!(
+        offsets(&[stop(None), stop(None), stop(None)]),
+        [Some(0.0), Some(0.5), Some(1.0)]
+    );
+
+    // Unpositioned stops are spaced between their neighbours, skipping over interpolation hints
+    assert_eq!(
+        offsets(&[
+            stop(None),
+            stop(None),
+            stop(Some(0.5)),
+            GenericGradientItem::InterpolationHint(0.625),
+            stop(None),
+            stop(None),
+        ]),
+        [
+            Some(0.0),
+            Some(0.25),
+            Some(0.5),
+            None,
+            Some(0.75),
+            Some(1.0)
+        ]
+    );
+
+    // Stops positioned before an earlier stop are moved up to it
+    assert_eq!(
+        offsets(&[stop(Some(0.5)), stop(Some(0.2)), stop(None)]),
+        [Some(0.5), Some(0.5), Some(1.0)]
+    );
+}
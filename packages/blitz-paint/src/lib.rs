@@ -2,18 +2,26 @@
 //! an impl [`anyrender::PaintScene`].
 
 mod color;
+mod crisp;
 mod debug_overlay;
+mod glyph_outlines;
 mod gradient;
-mod kurbo_css;
 mod layers;
+mod popup;
 mod render;
 mod sizing;
+mod stats;
 mod text;
+mod timing;
 
-use anyrender::PaintScene;
+use anyrender::{ImageRenderer, PaintScene};
 use blitz_dom::BaseDocument;
+use glyph_outlines::GlyphOutlineScene;
 use layers::reset_layer_stats;
-use render::BlitzDomPainter;
+
+pub use render::BlitzDomPainter;
+pub use stats::{PaintStats, PhaseStats, StatsScene};
+pub use timing::{PaintPhase, PaintTimings};
 
 /// Paint a [`blitz_dom::BaseDocument`] by pushing drawing commands into
 /// an impl [`anyrender::PaintScene`].
@@ -33,14 +41,7 @@ pub fn paint_scene(
 ) {
     reset_layer_stats();
 
-    let devtools = *dom.devtools();
-    let generator = BlitzDomPainter {
-        dom,
-        scale,
-        width,
-        height,
-        devtools,
-    };
+    let generator = BlitzDomPainter::new(dom, scale, width, height);
     generator.paint_scene(scene);
 
     // println!(
@@ -50,3 +51,263 @@ pub fn paint_scene(
     //     CLIPS_WANTED.load(atomic::Ordering::SeqCst)
     // );
 }
+
+/// Render a [`blitz_dom::BaseDocument`] offscreen (using the [`ImageRenderer`] `R`) and return the
+/// rendered image as RGBA8 pixels. Useful for thumbnails, screenshots, and printing.
+///
+/// `width` and `height` are the size of the image in physical pixels, and `scale` is the number of
+/// physical pixels per CSS pixel. See [`BlitzDomPainter::render_to_buffer`].
+///
+/// ```ignore
+/// let pixels = render_to_rgba::<VelloCpuImageRenderer>(&doc, 800, 600, 1.0);
+/// ```
+pub fn render_to_rgba<R: ImageRenderer>(
+    dom: &BaseDocument,
+    width: u32,
+    height: u32,
+    scale: f64,
+) -> Vec<u8> {
+    BlitzDomPainter::new(dom, scale, width, height).render_to_buffer::<R>()
+}
+
+/// Paint a single stacking context of a [`blitz_dom::BaseDocument`] into its own scene, so that it
+/// can be composited independently of the rest of the page (e.g. to promote an animating element to
+/// its own layer).
+///
+/// `node_id` should be the root of a stacking context (see [`blitz_dom::Node::is_stacking_context_root`]).
+/// Otherwise, positioned descendants which are hoisted into an ancestor stacking context will not be painted.
+///
+/// The contents are painted with the top-left corner of the node's border box at the origin of the scene,
+/// and `width` and `height` (the size of the layer in physical pixels) are used to cull content which is
+/// out of view. The layer is *not* clipped to the node's bounds, as descendants may overflow it.
+///
+/// To composite the layer the caller should draw it on top of the scene for the rest of the page,
+/// translated to the node's position in the viewport ([`blitz_dom::Node::absolute_position`] minus
+/// the viewport scroll, multiplied by `scale`). The node is still painted by [`paint_scene`], so callers
+/// which composite it separately are responsible for hiding it there (e.g. with `visibility: hidden`
+/// while the layer is promoted).
+pub fn paint_stacking_context(
+    scene: &mut impl PaintScene,
+    dom: &BaseDocument,
+    node_id: usize,
+    scale: f64,
+    width: u32,
+    height: u32,
+) {
+    reset_layer_stats();
+
+    let generator = BlitzDomPainter::new(dom, scale, width, height);
+    generator.paint_stacking_context(scene, node_id);
+}
+
+/// The number of pages that a [`blitz_dom::BaseDocument`] is split into when it is paginated into
+/// pages which are `height` physical pixels tall (see [`paint_page`]).
+pub fn page_count(dom: &BaseDocument, scale: f64, height: u32) -> usize {
+    dom.page_breaks((height as f64 / scale) as f32).len()
+}
+
+/// Paint a single page of a [`blitz_dom::BaseDocument`] which has been laid out for paged media (with a
+/// [`Viewport`](blitz_traits::shell::Viewport) of the page size and
+/// [`MediaType::Print`](blitz_traits::shell::MediaType::Print)).
+///
+/// `width` and `height` are the size of a page in physical pixels. The page is painted with its top-left
+/// corner at the origin of the scene and anything which falls outside of it (including content which
+/// was pushed onto the next page by a forced break) is clipped. Returns `false` (and paints nothing)
+/// if the document has fewer than `page_index + 1` pages.
+///
+/// As with [`paint_scene`], the styles and layout in the [`BaseDocument`] must already be resolved.
+pub fn paint_page(
+    scene: &mut impl PaintScene,
+    dom: &BaseDocument,
+    page_index: usize,
+    scale: f64,
+    width: u32,
+    height: u32,
+) -> bool {
+    let page_height = height as f64 / scale;
+    let breaks = dom.page_breaks(page_height as f32);
+    let Some(&page_start) = breaks.get(page_index) else {
+        return false;
+    };
+    let page_end = breaks
+        .get(page_index + 1)
+        .map(|&end| end as f64)
+        .unwrap_or(page_start as f64 + page_height);
+
+    reset_layer_stats();
+
+    let generator = BlitzDomPainter::new(dom, scale, width, height);
+    generator.paint_page(scene, page_start as f64, page_end - page_start as f64);
+    true
+}
+
+/// Paint a [`blitz_dom::BaseDocument`] (see [`paint_scene`]) while collecting statistics
+/// about the drawing commands that were issued. Useful for finding pages which are expensive to draw.
+pub fn paint_scene_with_stats(
+    scene: &mut impl PaintScene,
+    dom: &BaseDocument,
+    scale: f64,
+    width: u32,
+    height: u32,
+) -> PaintStats {
+    let mut scene = StatsScene::new(scene);
+    timing::track_phases(|| paint_scene(&mut scene, dom, scale, width, height));
+    scene.stats()
+}
+
+/// Paint a [`blitz_dom::BaseDocument`] (see [`paint_scene`]) while timing each phase of painting.
+/// Useful for finding out where the time goes when a frame is slow to paint.
+///
+/// Timings are only recorded by this function, so painting with [`paint_scene`] never reads the clock
+/// or tracks which phase is running.
+pub fn paint_scene_with_timings(
+    scene: &mut impl PaintScene,
+    dom: &BaseDocument,
+    scale: f64,
+    width: u32,
+    height: u32,
+) -> PaintTimings {
+    timing::record_timings(|| paint_scene(scene, dom, scale, width, height))
+}
+
+/// Paint a [`blitz_dom::BaseDocument`] (see [`paint_scene`]) with text drawn as filled glyph outlines
+/// (read from the font without hinting and snapped to the pixel grid) rather than being rasterized by
+/// the renderer. This makes rendered text identical across platforms, so that it can be compared
+/// against golden images in tests.
+///
+/// This is intended for testing only: the text is lower quality than that drawn by [`paint_scene`].
+pub fn paint_scene_with_glyph_outlines(
+    scene: &mut impl PaintScene,
+    dom: &BaseDocument,
+    scale: f64,
+    width: u32,
+    height: u32,
+) {
+    paint_scene(
+        &mut GlyphOutlineScene::new(scene),
+        dom,
+        scale,
+        width,
+        height,
+    );
+}
+
+#[test]
+fn render_to_rgba_paints_body_background() {
+    use crate::render::pixel_at;
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_traits::shell::{ColorScheme, Viewport};
+
+    let css = "body { background: rgb(255, 0, 0) }";
+    let (mut doc, ()) = doc_with_body(40, 30, css, |_, _| ());
+    doc.set_viewport(Viewport::new(40, 30, 2.0, ColorScheme::Light));
+    doc.resolve(0.0);
+
+    let pixels = render_to_rgba::<VelloCpuImageRenderer>(&doc, 40, 30, 2.0);
+    assert_eq!(pixels.len(), 40 * 30 * 4);
+
+    // The body's background propagates to the canvas, so it covers the whole image
+    for (x, y) in [(0, 0), (20, 15), (39, 29)] {
+        assert_eq!(pixel_at(&pixels, 40, x, y), [255, 0, 0, 255]);
+    }
+}
+
+#[test]
+fn paint_timings_cover_every_phase() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use std::time::Duration;
+
+    let (doc, ()) = doc_with_body(200, 200, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "background: red; border: 2px solid blue; clip-path: inset(2px)",
+        );
+        let text = mutr.create_text_node("Hello world");
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.append_children(div, &[text, input]);
+        mutr.append_children(body, &[div]);
+    });
+
+    let mut timings = PaintTimings::default();
+    anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| timings = paint_scene_with_timings(scene, &doc, 1.0, 200, 200),
+        200,
+        200,
+    );
+
+    // Phases can be too quick to measure where the clock is coarse, so check that each one was
+    // entered rather than that it took any time
+    let phases = timings.phases();
+    assert_eq!(phases.map(|(phase, _)| phase), PaintPhase::ALL);
+    for phase in PaintPhase::ALL {
+        assert!(timings.count(phase) > 0, "{phase:?} was never entered");
+    }
+    let sum: Duration = phases.iter().map(|(_, duration)| *duration).sum();
+    assert!(sum <= timings.total);
+}
+
+#[test]
+fn painter_render_to_buffer_matches_paint_scene() {
+    use crate::render::pixel_at;
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, ()) = doc_with_body(60, 40, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 20px; height: 10px; background: blue",
+        );
+        mutr.append_children(body, &[div]);
+    });
+
+    let pixels =
+        BlitzDomPainter::new(&doc, 1.0, 60, 40).render_to_buffer::<VelloCpuImageRenderer>();
+    let expected = anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| paint_scene(scene, &doc, 1.0, 60, 40),
+        60,
+        40,
+    );
+    assert_eq!(pixels.len(), 60 * 40 * 4);
+    assert_eq!(pixels, expected);
+
+    // The div is inside the body's 8px margin
+    assert_eq!(pixel_at(&pixels, 60, 10, 10), [0, 0, 255, 255]);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn render_to_png_encodes_the_rendered_buffer() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let css = "body { background: rgb(0, 128, 0) }";
+    let (doc, ()) = doc_with_body(30, 20, css, |_, _| ());
+
+    let painter = BlitzDomPainter::new(&doc, 1.0, 30, 20);
+    let mut bytes = Vec::new();
+    painter
+        .render_to_png::<VelloCpuImageRenderer>(&mut bytes)
+        .unwrap();
+
+    let mut reader = png::Decoder::new(std::io::Cursor::new(bytes))
+        .read_info()
+        .unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (30, 20));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+    assert_eq!(
+        pixels[..info.buffer_size()],
+        painter.render_to_buffer::<VelloCpuImageRenderer>()
+    );
+    assert_eq!(pixels[..4], [0, 128, 0, 255]);
+}
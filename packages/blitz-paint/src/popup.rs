@@ -0,0 +1,49 @@
+//! Rendering of popups which are drawn on top of the document (e.g. datalist suggestions)
+
+use anyrender::PaintScene;
+use blitz_dom::{BaseDocument, DATALIST_OPTION_HEIGHT};
+use kurbo::{Affine, Point, Rect, Stroke};
+use peniko::Fill;
+
+use crate::color::Color;
+
+const POPUP_BACKGROUND: Color = Color::from_rgba8(255, 255, 255, 255);
+const POPUP_BORDER: Color = Color::from_rgba8(118, 118, 118, 255);
+const POPUP_SELECTED: Color = Color::from_rgba8(209, 226, 250, 255);
+/// Horizontal padding between the edge of the popup and the option text (in CSS pixels)
+const OPTION_PADDING: f64 = 6.0;
+
+/// Renders the suggestion popup for the focussed `<input list="...">` (if any)
+pub(crate) fn render_datalist_popup(scene: &mut impl PaintScene, dom: &BaseDocument, scale: f64) {
+    let Some(popup) = dom.datalist_popup() else {
+        return;
+    };
+
+    let viewport_scroll = dom.viewport_scroll();
+    let (origin, width) = popup.origin_and_width(dom);
+    let x = f64::from(origin.x) - viewport_scroll.x;
+    let y = f64::from(origin.y) - viewport_scroll.y;
+    let row_height = f64::from(DATALIST_OPTION_HEIGHT);
+    let height = row_height * popup.options.len() as f64;
+
+    let transform = Affine::scale(scale);
+    let rect = Rect::new(x, y, x + f64::from(width), y + height);
+    scene.fill(Fill::NonZero, transform, POPUP_BACKGROUND, None, &rect);
+
+    for (idx, option) in popup.options.iter().enumerate() {
+        let row_y = y + row_height * idx as f64;
+        if popup.selected == Some(idx) {
+            let row = Rect::new(x, row_y, rect.x1, row_y + row_height);
+            scene.fill(Fill::NonZero, transform, POPUP_SELECTED, None, &row);
+        }
+
+        // Option layouts are in physical pixels, so they are unscaled to centre them in the row
+        let text_height = f64::from(option.layout.height()) / scale;
+        let pos = Point::new(x + OPTION_PADDING, row_y + (row_height - text_height) / 2.0);
+        let text_transform = Affine::translate(pos.to_vec2() * scale);
+        crate::text::stroke_text(scale, scene, option.layout.lines(), dom, text_transform);
+    }
+
+    let border = Stroke::new(1.0);
+    scene.stroke(&border, transform, POPUP_BORDER, None, &rect);
+}
@@ -1,25 +1,34 @@
 mod background;
 mod box_shadow;
 mod form_controls;
+mod scrollbar;
 
 use std::any::Any;
 
-use super::kurbo_css::{CssBox, Edge};
 use crate::color::{Color, ToColorColor};
-use crate::debug_overlay::render_debug_overlay;
-use crate::kurbo_css::NonUniformRoundedRectRadii;
-use crate::layers::maybe_with_layer;
+#[cfg(feature = "svg")]
+use crate::crisp::CrispEdgesScene;
+use crate::debug_overlay::{
+    GAP_OVERLAY_COLOR, GRID_LINE_COLOR, GapLayout, draw_grid_line_label, gap_regions,
+    grid_line_number, grid_track_extents, render_debug_overlay, render_paint_flash,
+};
+use crate::layers::{maybe_pop_layer, maybe_push_layer, maybe_with_layer, reset_layer_stats};
+use crate::popup::render_datalist_popup;
 use crate::sizing::compute_object_fit;
-use anyrender::{CustomPaint, Paint, PaintScene};
+use crate::timing::{PaintPhase, time_phase};
+use anyrender::{CustomPaint, ImageRenderer, Paint, PaintScene};
+use blitz_dom::kurbo_css::{CssBox, Edge, NonUniformRoundedRectRadii, create_css_rect};
 use blitz_dom::node::{
     ListItemLayout, ListItemLayoutPosition, Marker, NodeData, RasterImageData, TextInputData,
-    TextNodeData,
+    TextNodeData, flatten_transform,
 };
 use blitz_dom::{BaseDocument, ElementData, Node, local_name};
 use blitz_traits::devtools::DevtoolSettings;
+use blitz_traits::shell::ColorScheme;
 
-use euclid::Transform3D;
-use style::values::computed::BorderCornerRadius;
+use style::computed_values::direction::T as Direction;
+use style::values::computed::NonNegativeLengthPercentage;
+use style::values::generics::length::GenericLengthPercentageOrNormal;
 use style::{
     dom::TElement,
     properties::{
@@ -32,11 +41,26 @@ use style::{
     },
 };
 
-use kurbo::{self, Affine, Insets, Point, Rect, Stroke, Vec2};
+use kurbo::{self, Affine, BezPath, Insets, Point, Rect, Stroke, Vec2};
 use peniko::{self, Fill, ImageData, ImageSampler};
-use style::values::generics::color::GenericColor;
+use style::values::generics::color::{GenericColor, GenericColorOrAuto};
 use taffy::Layout;
 
+/// The background of selected text in text inputs
+///
+/// TODO: Use the styles of the `::selection` pseudo-element
+const SELECTION_COLOR: Color = color::palette::css::STEEL_BLUE;
+
+/// The width of the caret in text inputs (in CSS pixels)
+const CARET_WIDTH: f64 = 1.0;
+
+/// The default canvas background for documents with a dark `color-scheme`
+const DARK_CANVAS_COLOR: Color = Color::from_rgba8(18, 18, 18, 255);
+
+/// The opacity with which `placeholder` text is drawn when the styles of its `::placeholder`
+/// pseudo-element are unavailable (matching the UA stylesheet's `::placeholder` rule)
+const PLACEHOLDER_OPACITY: f32 = 0.54;
+
 /// A short-lived struct which holds a bunch of parameters for rendering a scene so
 /// that we don't have to pass them down as parameters
 pub struct BlitzDomPainter<'dom> {
@@ -48,6 +72,20 @@ pub struct BlitzDomPainter<'dom> {
     pub(crate) devtools: DevtoolSettings,
 }
 
+impl<'dom> BlitzDomPainter<'dom> {
+    /// Create a painter for `dom`, which paints into a surface of `width` x `height` physical pixels
+    /// with `scale` physical pixels per CSS pixel. The document's devtools settings are used.
+    pub fn new(dom: &'dom BaseDocument, scale: f64, width: u32, height: u32) -> Self {
+        Self {
+            dom,
+            scale,
+            width,
+            height,
+            devtools: *dom.devtools(),
+        }
+    }
+}
+
 impl BlitzDomPainter<'_> {
     fn node_position(&self, node: usize, location: Point) -> (Layout, Point) {
         let layout = self.layout(node);
@@ -66,64 +104,286 @@ impl BlitzDomPainter<'_> {
     /// This assumes styles are resolved and layout is complete.
     /// Make sure you do those before trying to render
     pub fn paint_scene(&self, scene: &mut impl PaintScene) {
-        // Simply render the document (the root element (note that this is not the same as the root node)))
         scene.reset();
         let viewport_scroll = self.dom.as_ref().viewport_scroll();
+        self.paint_document(scene, Point::new(viewport_scroll.x, viewport_scroll.y));
+
+        // Render popups on top of the document
+        render_datalist_popup(scene, self.dom, self.scale);
+
+        // Render debug overlay
+        if self.devtools.highlight_hover {
+            if let Some(node_id) = self.dom.as_ref().get_hover_node_id() {
+                render_debug_overlay(scene, self.dom, node_id, self.scale);
+            }
+        }
+
+        // Render paint flashing overlay
+        if self.devtools.paint_flash {
+            render_paint_flash(scene, self.dom, self.scale);
+        }
+    }
 
+    /// Render the document offscreen (using the [`ImageRenderer`] `R`) and return the rendered image as
+    /// RGBA8 pixels, `width` x `height` in size. Useful for thumbnails, printing, and snapshot tests.
+    ///
+    /// The document's background is painted across the whole image (areas without any background are
+    /// left transparent), and content is scaled by the painter's `scale`.
+    ///
+    /// As with [`paint_scene`](Self::paint_scene), this assumes styles are resolved and layout is complete.
+    ///
+    /// ```ignore
+    /// let pixels = BlitzDomPainter::new(&doc, 1.0, 800, 600).render_to_buffer::<VelloCpuImageRenderer>();
+    /// ```
+    pub fn render_to_buffer<R: ImageRenderer>(&self) -> Vec<u8> {
+        reset_layer_stats();
+        anyrender::render_to_buffer::<R, _>(
+            |scene| self.paint_scene(scene),
+            self.width,
+            self.height,
+        )
+    }
+
+    /// Render the document offscreen (see [`render_to_buffer`](Self::render_to_buffer)) and write the
+    /// rendered image to `writer` as a PNG.
+    #[cfg(feature = "png")]
+    pub fn render_to_png<R: ImageRenderer>(
+        &self,
+        writer: impl std::io::Write,
+    ) -> Result<(), png::EncodingError> {
+        let buffer = self.render_to_buffer::<R>();
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&buffer)?;
+        writer.finish()
+    }
+
+    /// Draw a single page of the document: the slice of the document which starts `page_start` CSS
+    /// pixels from the top of the document and is `page_height` CSS pixels tall (see
+    /// [`BaseDocument::page_breaks`]). Content beyond the end of the page is clipped.
+    ///
+    /// See [`crate::paint_page`]
+    pub fn paint_page(&self, scene: &mut impl PaintScene, page_start: f64, page_height: f64) {
+        scene.reset();
+        let page = Rect::new(0.0, 0.0, self.width as f64, page_height * self.scale);
+        maybe_with_layer(scene, true, 1.0, Affine::IDENTITY, &page, |scene| {
+            self.paint_document(scene, Point::new(0.0, page_start))
+        });
+    }
+
+    /// Draw the document (the canvas background, the root element and the top layer) scrolled by `scroll`
+    fn paint_document(&self, scene: &mut impl PaintScene, scroll: Point) {
+        // Simply render the document (the root element (note that this is not the same as the root node)))
         let root_element = self.dom.as_ref().root_element();
         let root_id = root_element.id;
         let bg_width = (self.width as f32).max(root_element.final_layout.size.width);
         let bg_height = (self.height as f32).max(root_element.final_layout.size.height);
 
-        let background_color = {
-            let html_color = root_element
-                .primary_styles()
-                .map(|s| s.clone_background_color())
-                .unwrap_or(GenericColor::TRANSPARENT_BLACK);
-            if html_color == GenericColor::TRANSPARENT_BLACK {
-                root_element
-                    .children
-                    .iter()
-                    .find_map(|id| {
-                        self.dom
-                            .as_ref()
-                            .get_node(*id)
-                            .filter(|node| node.data.is_element_with_tag_name(&local_name!("body")))
-                    })
-                    .and_then(|body| body.primary_styles())
-                    .map(|style| {
-                        let current_color = style.clone_color();
-                        style
-                            .clone_background_color()
-                            .resolve_to_absolute(&current_color)
-                    })
-            } else {
-                let current_color = root_element.primary_styles().unwrap().clone_color();
-                Some(html_color.resolve_to_absolute(&current_color))
-            }
+        let body = root_element.children.iter().find_map(|id| {
+            self.dom
+                .as_ref()
+                .get_node(*id)
+                .filter(|node| node.data.is_element_with_tag_name(&local_name!("body")))
+        });
+
+        // The background of the root element (or of the body if the root element has no background)
+        // is propagated to the canvas
+        let html_color = root_element
+            .primary_styles()
+            .map(|s| s.clone_background_color())
+            .unwrap_or(GenericColor::TRANSPARENT_BLACK);
+        let background_from_body = html_color == GenericColor::TRANSPARENT_BLACK;
+        let background_color = if background_from_body {
+            body.and_then(|body| body.primary_styles()).map(|style| {
+                let current_color = style.clone_color();
+                style
+                    .clone_background_color()
+                    .resolve_to_absolute(&current_color)
+            })
+        } else {
+            let current_color = root_element.primary_styles().unwrap().clone_color();
+            Some(html_color.resolve_to_absolute(&current_color))
         };
 
+        let rect = Rect::from_origin_size((0.0, 0.0), (bg_width as f64, bg_height as f64));
+
+        // Beneath any author background, the canvas is dark if the root element's `color-scheme` is
+        // dark (a light canvas is left to the renderer's base color)
+        let preferred_scheme = self.dom.viewport().color_scheme;
+        if root_element.used_color_scheme(preferred_scheme) == ColorScheme::Dark {
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                DARK_CANVAS_COLOR,
+                None,
+                &rect,
+            );
+        }
+
         if let Some(bg_color) = background_color {
             let bg_color = bg_color.as_srgb_color();
-            let rect = Rect::from_origin_size((0.0, 0.0), (bg_width as f64, bg_height as f64));
-            scene.fill(Fill::NonZero, Affine::IDENTITY, bg_color, None, &rect);
+
+            // Clip the canvas background to the `clip-path` of the element it was propagated from
+            // (the element tree itself is clipped by `render_element`)
+            let clip_path =
+                self.canvas_clip_path(root_id, body.filter(|_| background_from_body), scroll);
+            let (clip_transform, clip_path) = clip_path.unzip();
+            maybe_with_layer(
+                scene,
+                clip_path.is_some(),
+                1.0,
+                clip_transform.unwrap_or_default(),
+                &clip_path.unwrap_or_default(),
+                |scene| scene.fill(Fill::NonZero, Affine::IDENTITY, bg_color, None, &rect),
+            );
         }
 
         self.render_element(
             scene,
             root_id,
             Point {
-                x: -viewport_scroll.x,
-                y: -viewport_scroll.y,
+                x: -scroll.x,
+                y: -scroll.y,
             },
+            Affine::IDENTITY,
         );
 
-        // Render debug overlay
-        if self.devtools.highlight_hover {
-            if let Some(node_id) = self.dom.as_ref().get_hover_node_id() {
-                render_debug_overlay(scene, self.dom, node_id, self.scale);
+        // Render the top layer (e.g. modal dialogs) above the rest of the document
+        self.paint_top_layer(scene, scroll);
+    }
+
+    /// The `clip-path` of the root element, or (if the root element is not clipped) of the body
+    /// element (if provided) along with the transform into the clipping element's coordinate space.
+    fn canvas_clip_path(
+        &self,
+        root_id: usize,
+        body: Option<&Node>,
+        scroll: Point,
+    ) -> Option<(Affine, BezPath)> {
+        let root = &self.dom.as_ref().tree()[root_id];
+        let (root_layout, root_position) =
+            self.node_position(root_id, Point::new(-scroll.x, -scroll.y));
+        let root_cx = self.element_cx(root, root_layout, root_position, Affine::IDENTITY);
+        if let Some(clip_path) = root_cx.clip_path() {
+            return Some((root_cx.transform, clip_path));
+        }
+
+        let body = body?;
+        let location = Point {
+            x: root_position.x - root.scroll_offset.x,
+            y: root_position.y - root.scroll_offset.y,
+        };
+        let (body_layout, body_position) = self.node_position(body.id, location);
+        let body_cx = self.element_cx(
+            body,
+            body_layout,
+            body_position,
+            root_cx.children_transform(),
+        );
+        let clip_path = body_cx.clip_path()?;
+        Some((body_cx.transform, clip_path))
+    }
+
+    /// Draw a single stacking context: the element `node_id` and all of its descendants (including
+    /// descendants hoisted into the stacking context because of their `z-index`), with the top-left
+    /// corner of the element's border box at the origin of the scene.
+    ///
+    /// See [`crate::paint_stacking_context`]
+    pub fn paint_stacking_context(&self, scene: &mut impl PaintScene, node_id: usize) {
+        scene.reset();
+        let location = self.layout(node_id).location;
+        self.render_node(
+            scene,
+            node_id,
+            Point {
+                x: -location.x as f64,
+                y: -location.y as f64,
+            },
+            Affine::IDENTITY,
+        );
+    }
+
+    /// Paint the elements in the top layer (such as modal dialogs), each above a `::backdrop` that
+    /// covers the viewport.
+    ///
+    /// TODO: paint backdrops with the full set of background properties (not just `background-color`)
+    fn paint_top_layer(&self, scene: &mut impl PaintScene, scroll: Point) {
+        let viewport = Rect::new(0.0, 0.0, self.width as f64, self.height as f64);
+
+        for &node_id in self.dom.top_layer() {
+            if let Some(backdrop) = self.dom.backdrop_style(node_id) {
+                let current_color = backdrop.clone_color();
+                let color = backdrop
+                    .clone_background_color()
+                    .resolve_to_absolute(&current_color)
+                    .as_srgb_color();
+                scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &viewport);
+            }
+
+            // The element is laid out within its layout parent, so it is painted where the parent's
+            // children are painted (following the scroll offsets and transforms of its ancestors)
+            let node = &self.dom.as_ref().tree()[node_id];
+            let (location, parent_transform) = match node.layout_parent.get() {
+                Some(parent_id) => self.children_origin(parent_id, scroll),
+                None => (Point::new(-scroll.x, -scroll.y), Affine::IDENTITY),
+            };
+            self.paint_element(scene, node_id, location, parent_transform);
+        }
+    }
+
+    /// The `location` and `parent_transform` with which the children of `node_id` are painted when
+    /// the document is painted scrolled to `scroll`. This composes the positions, scroll offsets, and
+    /// transforms of `node_id` and its layout ancestors in the same way as [`Self::paint_element`].
+    fn children_origin(&self, node_id: usize, scroll: Point) -> (Point, Affine) {
+        let tree = self.dom.as_ref().tree();
+        let root_id = self.dom.root_element().id;
+
+        // The node and its ancestors up to the root element, innermost first
+        let mut ancestors = Vec::new();
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            ancestors.push(id);
+            if id == root_id {
+                break;
             }
+            current = tree[id].layout_parent.get();
         }
+
+        let mut location = Point::new(-scroll.x, -scroll.y);
+        let mut transform = Affine::IDENTITY;
+        for &id in ancestors.iter().rev() {
+            let node = &tree[id];
+            let (layout, box_position) = self.node_position(id, location);
+            let mut cx = self.element_cx(node, layout, box_position, transform);
+            cx.pos = Point {
+                x: cx.pos.x - node.scroll_offset.x,
+                y: cx.pos.y - node.scroll_offset.y,
+            };
+            cx.transform = cx.transform.pre_translate(Vec2 {
+                x: -node.scroll_offset.x * self.scale,
+                y: -node.scroll_offset.y * self.scale,
+            });
+            location = cx.pos;
+            transform = cx.children_transform();
+        }
+        (location, transform)
+    }
+
+    /// Renders a node which is guaranteed to be an element (unless it is in the top layer, in which
+    /// case it is painted by [`Self::paint_top_layer`] instead)
+    fn render_element(
+        &self,
+        scene: &mut impl PaintScene,
+        node_id: usize,
+        location: Point,
+        parent_transform: Affine,
+    ) {
+        if self.dom.is_in_top_layer(node_id) {
+            return;
+        }
+        self.paint_element(scene, node_id, location, parent_transform);
     }
 
     /// Renders a node, but is guaranteed that the node is an element
@@ -135,7 +395,16 @@ impl BlitzDomPainter<'_> {
     ///
     /// Approaching rendering this way guarantees we have all the styles we need when rendering text with not having
     /// to traverse back to the parent for its styles, or needing to pass down styles
-    fn render_element(&self, scene: &mut impl PaintScene, node_id: usize, location: Point) {
+    ///
+    /// `parent_transform` is the accumulated transform of the element's ancestors (see
+    /// [`ElementCx::parent_transform`]).
+    fn paint_element(
+        &self,
+        scene: &mut impl PaintScene,
+        node_id: usize,
+        location: Point,
+        parent_transform: Affine,
+    ) {
         let node = &self.dom.as_ref().tree()[node_id];
 
         // Early return if the element is hidden
@@ -148,13 +417,21 @@ impl BlitzDomPainter<'_> {
             return;
         }
 
+        // Hide `hidden="until-found"` subtrees. Their positioned descendants may be hoisted into an
+        // ancestor's stacking context (and may be `visibility: visible`), so ancestors are checked too.
+        if node.is_hidden_until_found() {
+            return;
+        }
+
         // Hide inputs with type=hidden
         // Implemented here rather than using the style engine for performance reasons
         if node.local_name() == "input" && node.attr(local_name!("type")) == Some("hidden") {
             return;
         }
 
-        // Hide elements with a visibility style other than visible
+        // Hide elements with a visibility style other than visible. Collapsed table rows are also
+        // removed from layout (see `build_table_context`), but other elements with `visibility: collapse`
+        // are treated like `visibility: hidden` and still take up space.
         if node
             .primary_styles()
             .unwrap()
@@ -165,7 +442,7 @@ impl BlitzDomPainter<'_> {
             return;
         }
 
-        // We can't fully support opacity yet, but we can hide elements with opacity 0
+        // Elements with opacity 0 don't need to be painted at all
         let opacity = node.primary_styles().unwrap().get_effects().opacity;
         if opacity == 0.0 {
             return;
@@ -204,9 +481,15 @@ impl BlitzDomPainter<'_> {
         };
 
         // Don't render things that are out of view
+        //
+        // Elements within transformed ancestors may be moved into view, so they are never culled. The
+        // parent transform is composed from scale and scroll translations which may not cancel out
+        // exactly, so it is compared to the identity with a tolerance.
         let scaled_y = box_position.y * self.scale;
         let scaled_content_height = content_size.height.max(size.height) as f64 * self.scale;
-        if scaled_y > self.height as f64 || scaled_y + scaled_content_height < 0.0 {
+        let is_out_of_view =
+            scaled_y > self.height as f64 || scaled_y + scaled_content_height < 0.0;
+        if is_out_of_view && is_approx_identity(parent_transform) {
             return;
         }
 
@@ -216,52 +499,102 @@ impl BlitzDomPainter<'_> {
             return;
         }
 
-        let mut cx = self.element_cx(node, layout, box_position);
-        cx.draw_outline(scene);
-        cx.draw_outset_box_shadow(scene);
-        cx.draw_background(scene);
-        cx.draw_border(scene);
+        let mut cx = self.element_cx(node, layout, box_position, parent_transform);
 
-        // TODO: allow layers with opacity to be unclipped (overflow: visible)
-        let wants_layer = should_clip | has_opacity;
-        let clip = &cx.frame.padding_box_path();
+        // Clip the element and its contents to its `clip-path` (if any)
+        let clip_path = time_phase(PaintPhase::ClipPath, || cx.clip_path());
+        let has_clip_path = clip_path.is_some();
+        let clip_path = clip_path.unwrap_or_default();
 
-        maybe_with_layer(scene, wants_layer, opacity, cx.transform, clip, |scene| {
-            cx.draw_inset_box_shadow(scene);
-            cx.stroke_devtools(scene);
+        // Group everything the element paints (including its background, border and descendants) into a
+        // single layer with the element's opacity, so that overlapping content is composited as a whole
+        // rather than each part blending separately. The layer covers the viewport so that it doesn't
+        // clip overflowing content.
+        let viewport = Rect::new(0.0, 0.0, self.width as f64, self.height as f64);
+        let opacity_layer =
+            maybe_push_layer(scene, has_opacity, opacity, Affine::IDENTITY, &viewport);
 
-            // Now that background has been drawn, offset pos and cx in order to draw our contents scrolled
-            let content_position = Point {
-                x: content_position.x - node.scroll_offset.x,
-                y: content_position.y - node.scroll_offset.y,
-            };
-            cx.pos = Point {
-                x: cx.pos.x - node.scroll_offset.x,
-                y: cx.pos.y - node.scroll_offset.y,
-            };
-            cx.transform = cx.transform.then_translate(Vec2 {
-                x: -node.scroll_offset.x,
-                y: -node.scroll_offset.y,
-            });
-            cx.draw_image(scene);
-            #[cfg(feature = "svg")]
-            cx.draw_svg(scene);
-            cx.draw_canvas(scene);
-            cx.draw_input(scene);
-
-            cx.draw_text_input_text(scene, content_position);
-            cx.draw_inline_layout(scene, content_position);
-            cx.draw_marker(scene, content_position);
-            cx.draw_children(scene);
-        });
+        maybe_with_layer(
+            scene,
+            has_clip_path,
+            1.0,
+            cx.transform,
+            &clip_path,
+            |scene| {
+                time_phase(PaintPhase::Background, || {
+                    cx.draw_outset_box_shadow(scene);
+                    cx.draw_background(scene);
+                    cx.draw_border(scene);
+                    cx.draw_resize_grip(scene);
+                });
+
+                // The overflow clip is nested within the `clip-path` layer, so contents are clipped
+                // to the intersection of the two.
+                let clip = &cx.frame.padding_box_path();
+                let transform = cx.transform;
+
+                maybe_with_layer(scene, should_clip, 1.0, cx.transform, clip, |scene| {
+                    time_phase(PaintPhase::Background, || cx.draw_inset_box_shadow(scene));
+                    cx.stroke_devtools(scene);
+
+                    // Now that background has been drawn, offset pos and cx in order to draw our contents scrolled
+                    let content_position = Point {
+                        x: content_position.x - node.scroll_offset.x,
+                        y: content_position.y - node.scroll_offset.y,
+                    };
+                    cx.pos = Point {
+                        x: cx.pos.x - node.scroll_offset.x,
+                        y: cx.pos.y - node.scroll_offset.y,
+                    };
+                    cx.transform = cx.transform.pre_translate(Vec2 {
+                        x: -node.scroll_offset.x * self.scale,
+                        y: -node.scroll_offset.y * self.scale,
+                    });
+                    time_phase(PaintPhase::Content, || {
+                        cx.draw_image(scene);
+                        cx.draw_image_alt_text(scene, content_position);
+                        #[cfg(feature = "svg")]
+                        cx.draw_svg(scene);
+                        cx.draw_canvas(scene);
+                        cx.draw_input(scene);
+                    });
+
+                    time_phase(PaintPhase::Text, || {
+                        cx.draw_text_input_text(scene, content_position);
+                        cx.draw_inline_layout(scene, content_position);
+                        cx.draw_marker(scene, content_position);
+                    });
+                    time_phase(PaintPhase::Children, || cx.draw_children(scene));
+                    cx.draw_gap_overlay(scene);
+                    cx.draw_grid_overlay(scene);
+                });
+
+                // Scrollbars and the outline are painted on top of the element's contents. They are
+                // clipped by `clip-path` but not by the overflow clip (which only clips the element's
+                // contents), and they don't scroll with the contents.
+                cx.transform = transform;
+                time_phase(PaintPhase::Background, || {
+                    cx.draw_scrollbars(scene);
+                    cx.draw_outline(scene);
+                });
+            },
+        );
+
+        maybe_pop_layer(scene, opacity_layer);
     }
 
-    fn render_node(&self, scene: &mut impl PaintScene, node_id: usize, location: Point) {
+    fn render_node(
+        &self,
+        scene: &mut impl PaintScene,
+        node_id: usize,
+        location: Point,
+        parent_transform: Affine,
+    ) {
         let node = &self.dom.as_ref().tree()[node_id];
 
         match &node.data {
             NodeData::Element(_) | NodeData::AnonymousBlock(_) => {
-                self.render_element(scene, node_id, location)
+                self.render_element(scene, node_id, location, parent_transform)
             }
             NodeData::Text(TextNodeData { .. }) => {
                 // Text nodes should never be rendered directly
@@ -279,6 +612,7 @@ impl BlitzDomPainter<'_> {
         node: &'w Node,
         layout: Layout,
         box_position: Point,
+        parent_transform: Affine,
     ) -> ElementCx<'w> {
         let style = node
             .stylo_element_data
@@ -298,50 +632,11 @@ impl BlitzDomPainter<'_> {
 
         // the bezpaths for every element are (potentially) cached (not yet, tbd)
         // By performing the transform, we prevent the cache from becoming invalid when the page shifts around
-        let mut transform = Affine::translate(box_position.to_vec2() * scale);
-
-        // Reference box for resolve percentage transforms
-        let reference_box = euclid::Rect::new(
-            euclid::Point2D::new(CSSPixelLength::new(0.0), CSSPixelLength::new(0.0)),
-            euclid::Size2D::new(
-                CSSPixelLength::new(frame.border_box.width() as f32),
-                CSSPixelLength::new(frame.border_box.height() as f32),
-            ),
-        );
-
-        // Apply CSS transform property (where transforms are 2d)
-        //
-        // TODO: Handle hit testing correctly for transformed nodes
-        // TODO: Implement nested transforms
-        let (t, has_3d) = &style
-            .get_box()
-            .transform
-            .to_transform_3d_matrix(Some(&reference_box))
-            .unwrap_or((Transform3D::default(), false));
-        if !has_3d {
-            // See: https://drafts.csswg.org/css-transforms-2/#two-dimensional-subset
-            // And https://docs.rs/kurbo/latest/kurbo/struct.Affine.html#method.new
-            let kurbo_transform =
-                Affine::new([t.m11, t.m12, t.m21, t.m22, t.m41, t.m42].map(|v| v as f64));
-
-            // Apply the transform origin by:
-            //   - Translating by the origin offset
-            //   - Applying our transform
-            //   - Translating by the inverse of the origin offset
-            let transform_origin = &style.get_box().transform_origin;
-            let origin_translation = Affine::translate(Vec2 {
-                x: transform_origin
-                    .horizontal
-                    .resolve(CSSPixelLength::new(frame.border_box.width() as f32))
-                    .px() as f64,
-                y: transform_origin
-                    .vertical
-                    .resolve(CSSPixelLength::new(frame.border_box.height() as f32))
-                    .px() as f64,
-            });
-            let kurbo_transform =
-                origin_translation * kurbo_transform * origin_translation.inverse();
+        let mut transform = parent_transform * Affine::translate(box_position.to_vec2() * scale);
 
+        // Apply CSS transform property (around the element's own `transform-origin`)
+        let matrix = node.transform_matrix(scale);
+        if let Some(kurbo_transform) = flatten_transform(&matrix, frame.border_box) {
             transform *= kurbo_transform;
         }
 
@@ -351,6 +646,7 @@ impl BlitzDomPainter<'_> {
             context: self,
             frame,
             scale,
+            parent_transform,
             style,
             pos: box_position,
             node,
@@ -402,6 +698,9 @@ struct ElementCx<'a> {
     node: &'a Node,
     element: &'a ElementData,
     transform: Affine,
+    /// The accumulated transform of the element's ancestors, which maps positions in the coordinate
+    /// space of `pos` (scaled to physical pixels) into the scene. `transform` includes this.
+    parent_transform: Affine,
     #[cfg(feature = "svg")]
     svg: Option<&'a usvg::Tree>,
     text_input: Option<&'a TextInputData>,
@@ -414,6 +713,17 @@ fn convert_rect(rect: &parley::BoundingBox) -> kurbo::Rect {
     peniko::kurbo::Rect::new(rect.x0, rect.y0, rect.x1, rect.y1)
 }
 
+/// Whether any of the shapes in an SVG should be anti-aliased. The SVG is only drawn with crisp
+/// edges if all of its shapes ask for them, as this can't (yet) be controlled per shape.
+#[cfg(feature = "svg")]
+fn svg_uses_shape_antialiasing(group: &usvg::Group) -> bool {
+    group.children().iter().any(|node| match node {
+        usvg::Node::Group(group) => svg_uses_shape_antialiasing(group),
+        usvg::Node::Path(path) => path.rendering_mode().use_shape_antialiasing(),
+        usvg::Node::Image(_) | usvg::Node::Text(_) => false,
+    })
+}
+
 impl ElementCx<'_> {
     fn draw_inline_layout(&self, scene: &mut impl PaintScene, pos: Point) {
         if self.node.flags.is_inline_root() {
@@ -430,7 +740,7 @@ impl ElementCx<'_> {
                 scene,
                 text_layout.layout.lines(),
                 self.context.dom,
-                pos,
+                self.transform_at(pos),
             );
         }
     }
@@ -438,7 +748,8 @@ impl ElementCx<'_> {
     fn draw_text_input_text(&self, scene: &mut impl PaintScene, pos: Point) {
         // Render the text in text inputs
         if let Some(input_data) = self.text_input {
-            let transform = Affine::translate((pos.x * self.scale, pos.y * self.scale));
+            // The selection and caret are transformed along with the text
+            let transform = self.transform_at(pos);
 
             if self.node.is_focussed() {
                 // Render selection/caret
@@ -446,14 +757,20 @@ impl ElementCx<'_> {
                     scene.fill(
                         Fill::NonZero,
                         transform,
-                        color::palette::css::STEEL_BLUE,
+                        SELECTION_COLOR,
                         None,
                         &convert_rect(rect),
                     );
                 }
-                if let Some(cursor) = input_data.editor.cursor_geometry(1.5) {
-                    // TODO: Use the `caret-color` attribute here if present.
-                    let color = self.style.get_inherited_text().color;
+                // TODO: Support `caret-shape` (block and underscore carets) once Stylo supports it
+                if let Some(cursor) = input_data.editor.cursor_geometry(caret_width(self.scale)) {
+                    let current_color = self.style.clone_color();
+                    let color = match self.style.get_inherited_ui().caret_color.0 {
+                        GenericColorOrAuto::Color(ref color) => {
+                            color.resolve_to_absolute(&current_color)
+                        }
+                        GenericColorOrAuto::Auto => current_color,
+                    };
 
                     scene.fill(
                         Fill::NonZero,
@@ -465,13 +782,42 @@ impl ElementCx<'_> {
                 };
             }
 
+            // Render the placeholder with the styles of the `::placeholder` pseudo-element. If those
+            // couldn't be resolved, fall back to a dimmed version of the input's own text styles.
+            if let Some(placeholder) = input_data.placeholder.as_deref() {
+                if input_data.is_empty() {
+                    let (placeholder_style, opacity) = match input_data.placeholder_style.as_deref()
+                    {
+                        Some(style) => (style, style.get_effects().opacity),
+                        None => (&*self.style, PLACEHOLDER_OPACITY),
+                    };
+                    let shape = self.frame.padding_box_path();
+                    maybe_with_layer(
+                        scene,
+                        opacity < 1.0,
+                        opacity,
+                        self.transform,
+                        &shape,
+                        |scene| {
+                            crate::text::stroke_text_with_styles(
+                                self.scale,
+                                scene,
+                                placeholder.lines(),
+                                placeholder_style,
+                                transform,
+                            );
+                        },
+                    );
+                }
+            }
+
             // Render text
             crate::text::stroke_text(
                 self.scale,
                 scene,
                 input_data.editor.try_layout().unwrap().lines(),
                 self.context.dom,
-                pos,
+                transform,
             );
         }
     }
@@ -482,12 +828,17 @@ impl ElementCx<'_> {
             position: ListItemLayoutPosition::Outside(layout),
         }) = self.list_item
         {
-            // Right align and pad the bullet when rendering outside
+            // Pad the bullet and place it outside the inline-start edge of the content box
             let x_padding = match marker {
                 Marker::Char(_) => 8.0,
                 Marker::String(_) => 0.0,
             };
-            let x_offset = -(layout.full_width() / layout.scale() + x_padding);
+            let x_offset = outside_marker_x_offset(
+                layout.full_width() / layout.scale(),
+                x_padding,
+                (self.frame.content_box.width() / self.scale) as f32,
+                self.style.clone_direction(),
+            );
 
             // Align the marker with the baseline of the first line of text in the list item
             let y_offset = if let Some(first_text_line) = &self
@@ -508,11 +859,42 @@ impl ElementCx<'_> {
                 y: pos.y + y_offset as f64,
             };
 
-            crate::text::stroke_text(self.scale, scene, layout.lines(), self.context.dom, pos);
+            crate::text::stroke_text(
+                self.scale,
+                scene,
+                layout.lines(),
+                self.context.dom,
+                self.transform_at(pos),
+            );
         }
     }
 
+    /// The shape that the element is clipped to by its `clip-path` property (if any)
+    fn clip_path(&self) -> Option<BezPath> {
+        self.node
+            .clip_path()
+            .map(|path| Affine::scale(self.scale) * path)
+    }
+
+    /// The transform for content positioned at `pos` (in the coordinate space of `self.pos`), which
+    /// includes the transforms of the element and its ancestors
+    fn transform_at(&self, pos: Point) -> Affine {
+        self.transform * Affine::translate((pos - self.pos) * self.scale)
+    }
+
+    /// The `parent_transform` of the element's children
+    fn children_transform(&self) -> Affine {
+        // Children which share the element's 3D rendering context already include its transform in
+        // their own (see `Node::transform_matrix`)
+        if self.node.preserves_3d() {
+            return self.parent_transform;
+        }
+        self.transform_at(Point::ORIGIN)
+    }
+
     fn draw_children(&self, scene: &mut impl PaintScene) {
+        let transform = self.children_transform();
+
         // Negative z_index hoisted nodes
         if let Some(hoisted) = &self.node.stacking_context {
             for hoisted_child in hoisted.neg_z_hoisted_children() {
@@ -520,14 +902,14 @@ impl ElementCx<'_> {
                     x: self.pos.x + hoisted_child.position.x as f64,
                     y: self.pos.y + hoisted_child.position.y as f64,
                 };
-                self.render_node(scene, hoisted_child.node_id, pos);
+                self.render_node(scene, hoisted_child.node_id, pos, transform);
             }
         }
 
         // Regular children
         if let Some(children) = &*self.node.paint_children.borrow() {
             for child_id in children {
-                self.render_node(scene, *child_id, self.pos);
+                self.render_node(scene, *child_id, self.pos, transform);
             }
         }
 
@@ -538,7 +920,7 @@ impl ElementCx<'_> {
                     x: self.pos.x + hoisted_child.position.x as f64,
                     y: self.pos.y + hoisted_child.position.y as f64,
                 };
-                self.render_node(scene, hoisted_child.node_id, pos);
+                self.render_node(scene, hoisted_child.node_id, pos, transform);
             }
         }
     }
@@ -585,11 +967,17 @@ impl ElementCx<'_> {
         let x_scale = paint_size.width as f64 / object_size.width as f64;
         let y_scale = paint_size.height as f64 / object_size.height as f64;
 
-        let transform =
-            Affine::translate((self.pos.x * self.scale + x, self.pos.y * self.scale + y))
-                .pre_scale_non_uniform(x_scale, y_scale);
-
-        anyrender_svg::render_svg_tree(scene, svg, transform);
+        let transform = self
+            .transform
+            .pre_scale_non_uniform(x_scale, y_scale)
+            .then_translate(Vec2 { x, y });
+
+        // `shape-rendering: crispEdges` (or `optimizeSpeed`) asks for shapes without anti-aliasing
+        if !svg_uses_shape_antialiasing(svg.root()) {
+            anyrender_svg::render_svg_tree(&mut CrispEdgesScene::new(scene), svg, transform);
+        } else {
+            anyrender_svg::render_svg_tree(scene, svg, transform);
+        }
     }
 
     fn draw_image(&self, scene: &mut impl PaintScene) {
@@ -636,10 +1024,30 @@ impl ElementCx<'_> {
         }
     }
 
+    /// Draw the `alt` text of an image which has no image to display, clipped to its content box
+    fn draw_image_alt_text(&self, scene: &mut impl PaintScene, pos: Point) {
+        let Some(layout) = self.element.alt_text_layout.as_deref() else {
+            return;
+        };
+        if !self.element.has_missing_image() {
+            return;
+        }
+
+        let clip = self.frame.content_box_path();
+        maybe_with_layer(scene, true, 1.0, self.transform, &clip, |scene| {
+            crate::text::stroke_text(
+                self.scale,
+                scene,
+                layout.lines(),
+                self.context.dom,
+                self.transform_at(pos),
+            );
+        });
+    }
+
     fn draw_canvas(&self, scene: &mut impl PaintScene) {
         if let Some(custom_paint_source) = self.element.canvas_data() {
-            let width = self.frame.content_box.width() as u32;
-            let height = self.frame.content_box.height() as u32;
+            let (width, height) = canvas_backing_size(&self.frame);
             let x = self.frame.content_box.origin().x;
             let y = self.frame.content_box.origin().y;
 
@@ -656,7 +1064,7 @@ impl ElementCx<'_> {
                     scale: self.scale,
                 } as &(dyn Any + Send + Sync)),
                 None,
-                &Rect::from_origin_size((0.0, 0.0), (width as f64, height as f64)),
+                &canvas_paint_shape(&self.frame),
             );
         }
     }
@@ -677,6 +1085,138 @@ impl ElementCx<'_> {
         }
     }
 
+    /// Shade the gaps between the items of a flex or grid container
+    fn draw_gap_overlay(&self, scene: &mut impl PaintScene) {
+        if !self.devtools.show_gaps {
+            return;
+        }
+        let layout = match self.node.style.display {
+            taffy::Display::Grid => GapLayout::Grid,
+            taffy::Display::Flex => match self.node.style.flex_direction {
+                taffy::FlexDirection::Row | taffy::FlexDirection::RowReverse => GapLayout::FlexRow,
+                taffy::FlexDirection::Column | taffy::FlexDirection::ColumnReverse => {
+                    GapLayout::FlexColumn
+                }
+            },
+            _ => return,
+        };
+
+        // Percentage gaps resolve against the (unscaled) content box
+        let content_box = self.frame.content_box;
+        let resolve_gap = |gap: &GenericLengthPercentageOrNormal<NonNegativeLengthPercentage>,
+                           basis: f64| match gap {
+            GenericLengthPercentageOrNormal::Normal => 0.0,
+            GenericLengthPercentageOrNormal::LengthPercentage(gap) => gap
+                .0
+                .resolve(CSSPixelLength::new((basis / self.scale) as f32))
+                .px(),
+        };
+        let position = self.style.get_position();
+        let has_column_gap = resolve_gap(&position.column_gap, content_box.width()) > 0.0;
+        let has_row_gap = resolve_gap(&position.row_gap, content_box.height()) > 0.0;
+        if !has_column_gap && !has_row_gap {
+            return;
+        }
+
+        // The border boxes of the in-flow items, relative to the container's border box
+        let items: Vec<Rect> = self
+            .node
+            .layout_children
+            .borrow()
+            .iter()
+            .flatten()
+            .map(|&child_id| &self.context.dom.as_ref().tree()[child_id])
+            .filter(|child| {
+                child.style.position != taffy::Position::Absolute
+                    && child.style.display != taffy::Display::None
+            })
+            .map(|child| {
+                let taffy::Layout { location, size, .. } = child.final_layout;
+                Rect::from_origin_size(
+                    (
+                        location.x as f64 * self.scale,
+                        location.y as f64 * self.scale,
+                    ),
+                    (
+                        size.width as f64 * self.scale,
+                        size.height as f64 * self.scale,
+                    ),
+                )
+            })
+            .collect();
+
+        for region in gap_regions(&items, content_box, layout, has_column_gap, has_row_gap) {
+            scene.fill(
+                Fill::NonZero,
+                self.transform,
+                GAP_OVERLAY_COLOR,
+                None,
+                &region,
+            );
+        }
+    }
+
+    /// Draw the lines of a grid container, labelled with their line numbers
+    fn draw_grid_overlay(&self, scene: &mut impl PaintScene) {
+        if !self.devtools.show_grid || self.node.style.display != taffy::Display::Grid {
+            return;
+        }
+        let Some(grid_info) = self.node.grid_info.as_deref() else {
+            return;
+        };
+
+        // TODO: outline and label named grid areas
+        let content_box = self.frame.content_box;
+        let columns = grid_track_extents(&grid_info.columns, content_box.x0, self.scale);
+        let rows = grid_track_extents(&grid_info.rows, content_box.y0, self.scale);
+        let (Some(first_row), Some(last_row)) = (rows.first(), rows.last()) else {
+            return;
+        };
+        let (Some(first_column), Some(last_column)) = (columns.first(), columns.last()) else {
+            return;
+        };
+        let (top, bottom) = (first_row.0, last_row.1);
+        let (left, right) = (first_column.0, last_column.1);
+
+        let stroke = Stroke::new(self.scale);
+        let mut lines = BezPath::new();
+        for &(start, end) in &columns {
+            lines.move_to((start, top));
+            lines.line_to((start, bottom));
+            lines.move_to((end, top));
+            lines.line_to((end, bottom));
+        }
+        for &(start, end) in &rows {
+            lines.move_to((left, start));
+            lines.line_to((right, start));
+            lines.move_to((left, end));
+            lines.line_to((right, end));
+        }
+        scene.stroke(&stroke, self.transform, GRID_LINE_COLOR, None, &lines);
+
+        // Label each line at the top (for columns) or left (for rows) of the grid
+        let line_starts = |extents: &[(f64, f64)]| -> Vec<f64> {
+            let last_end = extents.last().map(|extent| extent.1);
+            extents
+                .iter()
+                .map(|extent| extent.0)
+                .chain(last_end)
+                .collect()
+        };
+        for (idx, x) in line_starts(&columns).into_iter().enumerate() {
+            if let Some(number) = grid_line_number(&grid_info.columns, idx) {
+                let origin = Point::new(x, top);
+                draw_grid_line_label(scene, self.transform, origin, number, self.scale);
+            }
+        }
+        for (idx, y) in line_starts(&rows).into_iter().enumerate() {
+            if let Some(number) = grid_line_number(&grid_info.rows, idx) {
+                let origin = Point::new(left, y);
+                draw_grid_line_label(scene, self.transform, origin, number, self.scale);
+            }
+        }
+    }
+
     /// Stroke a border
     ///
     /// The border-style property specifies what kind of border to display.
@@ -686,10 +1226,10 @@ impl ElementCx<'_> {
     /// ❌ dashed - Defines a dashed border
     /// ✅ solid - Defines a solid border
     /// ❌ double - Defines a double border
-    /// ❌ groove - Defines a 3D grooved border.
-    /// ❌ ridge - Defines a 3D ridged border.
-    /// ❌ inset - Defines a 3D inset border.
-    /// ❌ outset - Defines a 3D outset border.
+    /// ✅ groove - Defines a 3D grooved border.
+    /// ✅ ridge - Defines a 3D ridged border.
+    /// ✅ inset - Defines a 3D inset border.
+    /// ✅ outset - Defines a 3D outset border.
     /// ✅ none - Defines no border
     /// ✅ hidden - Defines a hidden border
     ///
@@ -709,10 +1249,10 @@ impl ElementCx<'_> {
     /// - ❌ dashed: Defines a dashed border
     /// - ✅ solid: Defines a solid border
     /// - ❌ double: Defines a double border
-    /// - ❌ groove: Defines a 3D grooved border*
-    /// - ❌ ridge: Defines a 3D ridged border*
-    /// - ❌ inset: Defines a 3D inset border*
-    /// - ❌ outset: Defines a 3D outset border*
+    /// - ✅ groove: Defines a 3D grooved border*
+    /// - ✅ ridge: Defines a 3D ridged border*
+    /// - ✅ inset: Defines a 3D inset border*
+    /// - ✅ outset: Defines a 3D outset border*
     /// - ✅ none: Defines no border
     /// - ✅ hidden: Defines a hidden border
     ///
@@ -743,9 +1283,57 @@ impl ElementCx<'_> {
         };
 
         let alpha = color.components[3];
-        if alpha != 0.0 {
-            sb.fill(Fill::NonZero, self.transform, color, None, &path);
+        if alpha == 0.0 {
+            return;
         }
+
+        let border_style = match edge {
+            Edge::Top => border.border_top_style,
+            Edge::Right => border.border_right_style,
+            Edge::Bottom => border.border_bottom_style,
+            Edge::Left => border.border_left_style,
+        };
+
+        // The 3D border styles are painted in a lighter and a darker shade of the border color. The top
+        // and left edges are shaded the opposite way to the bottom and right edges, and the edges meet
+        // diagonally at the corners.
+        let top_left = matches!(edge, Edge::Top | Edge::Left);
+        let light = shade(color, LIGHTEN_FACTOR);
+        let dark = shade(color, DARKEN_FACTOR);
+        let (outer, inner) = match border_style {
+            // Inset borders look sunken (a darker top and left) and outset borders look raised
+            BorderStyle::Inset | BorderStyle::Outset => {
+                let lighter = top_left == (border_style == BorderStyle::Outset);
+                let color = if lighter { light } else { dark };
+                sb.fill(Fill::NonZero, self.transform, color, None, &path);
+                return;
+            }
+            // Groove and ridge borders are split into an outer and an inner half, one of each shade
+            BorderStyle::Groove | BorderStyle::Ridge => {
+                match top_left == (border_style == BorderStyle::Ridge) {
+                    true => (light, dark),
+                    false => (dark, light),
+                }
+            }
+            // TODO: Implement the other border styles (which are painted as solid borders for now)
+            _ => {
+                sb.fill(Fill::NonZero, self.transform, color, None, &path);
+                return;
+            }
+        };
+
+        let half_width = Insets {
+            x0: self.frame.border_width.x0 / 2.0,
+            y0: self.frame.border_width.y0 / 2.0,
+            x1: self.frame.border_width.x1 / 2.0,
+            y1: self.frame.border_width.y1 / 2.0,
+        };
+        let outer_path = self
+            .frame
+            .with_border_width(half_width)
+            .border_edge_shape(edge);
+        sb.fill(Fill::NonZero, self.transform, inner, None, &path);
+        sb.fill(Fill::NonZero, self.transform, outer, None, &outer_path);
     }
 
     /// ❌ dotted - Defines a dotted border
@@ -796,42 +1384,1255 @@ impl<'a> std::ops::Deref for ElementCx<'a> {
     }
 }
 
-fn insets_from_taffy_rect(input: taffy::Rect<f64>) -> Insets {
-    Insets {
-        x0: input.left,
-        y0: input.top,
-        x1: input.right,
-        y1: input.bottom,
+/// The width of the caret in device pixels. It is never thinner than one device pixel so that it
+/// doesn't disappear at low scale factors.
+fn caret_width(scale: f64) -> f32 {
+    (CARET_WIDTH * scale).max(1.0) as f32
+}
+
+/// The factor by which the lighter shade of a 3D border style scales the border color
+const LIGHTEN_FACTOR: f32 = 1.3;
+/// The factor by which the darker shade of a 3D border style scales the border color
+const DARKEN_FACTOR: f32 = 0.7;
+
+/// Lighten or darken a color (for the 3D border styles) by scaling its sRGB components by `factor`
+fn shade(color: Color, factor: f32) -> Color {
+    let [r, g, b, alpha] = color.components;
+    Color::new([
+        (r * factor).clamp(0.0, 1.0),
+        (g * factor).clamp(0.0, 1.0),
+        (b * factor).clamp(0.0, 1.0),
+        alpha,
+    ])
+}
+
+/// Whether `transform` is the identity, allowing for floating point error. The linear part must match
+/// closely, whereas the translation (in physical pixels) only needs to be well below a pixel.
+fn is_approx_identity(transform: Affine) -> bool {
+    let [a, b, c, d, e, f] = transform.as_coeffs();
+    let linear_error = (a - 1.0)
+        .abs()
+        .max(b.abs())
+        .max(c.abs())
+        .max((d - 1.0).abs());
+    linear_error < 1e-6 && e.abs() < 1e-3 && f.abs() < 1e-3
+}
+
+/// The horizontal offset of an outside list marker from the left edge of the list item's content box.
+///
+/// The marker sits just outside the inline-start edge of the content box (the left edge for `ltr`
+/// and the right edge for `rtl`). It doesn't move with `text-align`, so it stays beside the list
+/// item rather than overlapping centered or end-aligned contents.
+fn outside_marker_x_offset(
+    marker_width: f32,
+    padding: f32,
+    content_width: f32,
+    direction: Direction,
+) -> f32 {
+    match direction {
+        Direction::Ltr => -(marker_width + padding),
+        Direction::Rtl => content_width + padding,
     }
 }
 
-/// Convert Stylo and Taffy types into Kurbo types
-fn create_css_rect(style: &ComputedValues, layout: &Layout, scale: f64) -> CssBox {
-    // Resolve and rescale
-    // We have to scale since document pixels are not same same as rendered pixels
-    let width: f64 = layout.size.width as f64;
-    let height: f64 = layout.size.height as f64;
-    let border_box = Rect::new(0.0, 0.0, width * scale, height * scale);
-    let border = insets_from_taffy_rect(layout.border.map(|p| p as f64 * scale));
-    let padding = insets_from_taffy_rect(layout.padding.map(|p| p as f64 * scale));
-    let outline_width = style.get_outline().outline_width.to_f64_px() * scale;
+/// The area painted by a canvas's custom paint source, relative to the origin of its content box.
+///
+/// As with the content of other replaced elements, this is the content box clipped to the inner
+/// border radius (so rounded canvases have rounded contents).
+///
+/// TODO: apply `object-fit` and `object-position` once custom paint sources can report an intrinsic size
+fn canvas_paint_shape(frame: &CssBox) -> BezPath {
+    Affine::translate(-frame.content_box.origin().to_vec2()) * frame.content_box_path()
+}
 
-    // Resolve the radii to a length. need to downscale since the radii are in document pixels
-    let resolve_w = CSSPixelLength::new(width as _);
-    let resolve_h = CSSPixelLength::new(height as _);
-    let resolve_radii = |radius: &BorderCornerRadius| -> Vec2 {
-        Vec2 {
-            x: scale * radius.0.width.0.resolve(resolve_w).px() as f64,
-            y: scale * radius.0.height.0.resolve(resolve_h).px() as f64,
-        }
+/// The size (in device pixels) of the texture that a canvas's custom paint source should render: its
+/// (already scaled) content box size rounded to the nearest whole pixel.
+///
+/// Rounding (rather than truncating) avoids leaving a 1px seam at fractional scale factors.
+fn canvas_backing_size(frame: &CssBox) -> (u32, u32) {
+    let size = frame.content_box.size();
+    (size.width.round() as u32, size.height.round() as u32)
+}
+
+#[test]
+fn transform_lengths_scale_with_the_document() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, ()) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let container = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            container,
+            qual_name!("style"),
+            "margin: 100px; width: 100px; height: 100px; perspective: 100px",
+        );
+        let child = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            child,
+            qual_name!("style"),
+            "height: 100px; background: blue; transform: translateZ(50px)",
+        );
+        mutr.append_children(container, &[child]);
+        mutr.append_children(body, &[container]);
+    });
+
+    let child_bounds = |scale: f64| {
+        let painter = BlitzDomPainter::new(&doc, scale, 800, 600);
+        let mut scene = ClipRecorder::default();
+        painter.paint_scene(&mut scene);
+        let blue = Some(Color::from_rgb8(0, 0, 255));
+        let idx = scene.fill_colors.iter().position(|c| *c == blue).unwrap();
+        scene.fills[idx].0.bounding_box()
     };
-    let s_border = style.get_border();
-    let border_radii = NonUniformRoundedRectRadii {
-        top_left: resolve_radii(&s_border.border_top_left_radius),
-        top_right: resolve_radii(&s_border.border_top_right_radius),
-        bottom_right: resolve_radii(&s_border.border_bottom_right_radius),
-        bottom_left: resolve_radii(&s_border.border_bottom_left_radius),
+
+    // Moving halfway to the viewer doubles the child's size (around the perspective origin at its center)
+    let bounds = child_bounds(1.0);
+    assert!((bounds.width() - 200.0).abs() < 0.01, "{bounds:?}");
+    assert!((bounds.center() - Point::new(150.0, 150.0)).hypot() < 0.01);
+
+    // The same happens in physical pixels when the document is scaled
+    let bounds = child_bounds(2.0);
+    assert!((bounds.width() - 400.0).abs() < 0.01, "{bounds:?}");
+    assert!((bounds.center() - Point::new(300.0, 300.0)).hypot() < 0.01);
+}
+
+#[test]
+fn drifted_parent_transforms_still_allow_culling() {
+    // Composing scales and scroll translations can leave tiny rounding errors
+    let drifted = Affine::new([1.0 + 1e-12, 0.0, 0.0, 1.0 - 1e-12, 1e-9, -1e-9]);
+    assert!(is_approx_identity(drifted));
+    assert!(!is_approx_identity(Affine::translate((1.0, 0.0))));
+    assert!(!is_approx_identity(Affine::rotate(0.01)));
+}
+
+#[test]
+fn nested_transforms_compose() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, ()) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let outer = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            outer,
+            qual_name!("style"),
+            "margin: 100px; padding: 90px 50px; width: 100px; height: 20px; transform: rotate(45deg)",
+        );
+        let inner = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            inner,
+            qual_name!("style"),
+            "width: 100px; height: 20px; background: blue; transform: rotate(45deg)",
+        );
+        mutr.append_children(outer, &[inner]);
+        mutr.append_children(body, &[outer]);
+    });
+
+    let scene = record_scene(&doc);
+
+    // The wide inner box is rotated by 90deg in total (around the center of both boxes), so it stands upright
+    let blue = Color::from_rgb8(0, 0, 255);
+    let idx = scene
+        .fill_colors
+        .iter()
+        .position(|color| *color == Some(blue))
+        .unwrap();
+    let bounds = scene.fills[idx].0.bounding_box();
+    assert!((bounds.width() - 20.0).abs() < 0.01, "{bounds:?}");
+    assert!((bounds.height() - 100.0).abs() < 0.01, "{bounds:?}");
+    assert!((bounds.center() - Point::new(200.0, 200.0)).hypot() < 0.01);
+}
+
+#[test]
+fn body_clip_path_clips_canvas_background() {
+    use kurbo::Shape;
+
+    let css = "html, body { margin: 0; width: 100%; height: 100% } \
+               body { background: red; clip-path: circle(50%) }";
+    let (doc, body) = doc_with_body(800, 600, css, |_, body| body);
+
+    let painter = BlitzDomPainter::new(&doc, 1.0, 800, 600);
+    let root_id = doc.root_element().id;
+    let (transform, clip_path) = painter
+        .canvas_clip_path(root_id, doc.get_node(body), Point::ZERO)
+        .expect("the canvas background should be clipped to the body's clip-path");
+
+    // The corners of the viewport are outside of the circle, so the background is transparent there
+    let clip_path = transform * clip_path;
+    assert!(clip_path.contains(Point::new(400.0, 300.0)));
+    for corner in [(1.0, 1.0), (799.0, 1.0), (1.0, 599.0), (799.0, 599.0)] {
+        assert!(!clip_path.contains(Point::from(corner)));
+    }
+}
+
+#[test]
+fn percentage_radii_resolve_against_aspect_ratio_size() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, div) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 200px; aspect-ratio: 2 / 1; border-radius: 50%",
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+
+    // The height of the box comes from its aspect-ratio, and the vertical radii must resolve against it
+    let node = doc.get_node(div).unwrap();
+    assert_eq!(node.final_layout.size.height, 100.0);
+    let styles = node.primary_styles().unwrap();
+    for scale in [1.0, 2.0] {
+        let frame = create_css_rect(&styles, &node.final_layout, scale);
+        assert_eq!(frame.border_radii.top_left, Vec2::new(100.0, 50.0) * scale);
+        assert_eq!(
+            frame.border_radii.bottom_right,
+            Vec2::new(100.0, 50.0) * scale
+        );
+
+        // The box is painted as an ellipse, so its corners are cut off
+        let path = frame.border_box_path();
+        assert!(path.contains((Vec2::new(100.0, 50.0) * scale).to_point()));
+        assert!(path.contains((Vec2::new(100.0, 2.0) * scale).to_point()));
+        assert!(!path.contains((Vec2::new(10.0, 10.0) * scale).to_point()));
+        assert!(!path.contains((Vec2::new(190.0, 90.0) * scale).to_point()));
+    }
+}
+
+#[test]
+fn rounded_canvas_clips_custom_paint() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, canvas) = doc_with_body(800, 600, "", |mutr, body| {
+        let canvas = mutr.create_element(qual_name!("canvas", html), vec![]);
+        mutr.set_attribute(canvas, qual_name!("src"), "1");
+        mutr.set_attribute(
+            canvas,
+            qual_name!("style"),
+            "display: block; width: 100px; height: 100px; padding: 10px; border-radius: 30px",
+        );
+        mutr.append_children(body, &[canvas]);
+        canvas
+    });
+
+    let node = doc.get_node(canvas).unwrap();
+    assert!(node.element_data().unwrap().canvas_data().is_some());
+    let styles = node.primary_styles().unwrap();
+    let frame = create_css_rect(&styles, &node.final_layout, 1.0);
+    let shape = canvas_paint_shape(&frame);
+
+    // The paint covers the content box (relative to its origin), but its corners are rounded by the
+    // inner border radius (30px - 10px padding)
+    for inside in [(50.0, 50.0), (10.0, 10.0), (50.0, 0.5), (99.5, 50.0)] {
+        assert!(shape.contains(Point::from(inside)));
+    }
+    for outside in [(50.0, -0.5), (100.5, 50.0), (50.0, 110.0)] {
+        assert!(!shape.contains(Point::from(outside)));
+    }
+    for corner in [(1.0, 1.0), (99.0, 1.0), (1.0, 99.0), (99.0, 99.0)] {
+        assert!(!shape.contains(Point::from(corner)));
+    }
+}
+
+#[test]
+fn canvas_backing_size_rounds_scaled_content_box() {
+    use blitz_dom::qual_name;
+
+    let (doc, canvas) = doc_with_body(800, 600, "", |mutr, body| {
+        let canvas = mutr.create_element(qual_name!("canvas", html), vec![]);
+        mutr.set_attribute(canvas, qual_name!("src"), "1");
+        mutr.set_attribute(
+            canvas,
+            qual_name!("style"),
+            "display: block; width: 101px; height: 33px; padding: 3px",
+        );
+        mutr.append_children(body, &[canvas]);
+        canvas
+    });
+
+    let node = doc.get_node(canvas).unwrap();
+    let styles = node.primary_styles().unwrap();
+    for scale in [1.0, 1.5, 2.0] {
+        let frame = create_css_rect(&styles, &node.final_layout, scale);
+        assert_eq!(
+            canvas_backing_size(&frame),
+            (
+                (101.0 * scale).round() as u32,
+                (33.0 * scale).round() as u32
+            )
+        );
+    }
+}
+
+/// A scene which records the clips that are active when each shape is filled
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct ClipRecorder {
+    clips: Vec<BezPath>,
+    fills: Vec<(BezPath, Vec<BezPath>)>,
+    /// The color of each fill (`None` for fills which aren't a solid color)
+    fill_colors: Vec<Option<Color>>,
+    /// The (transformed) positions of the glyphs in each glyph run
+    glyph_runs: Vec<Vec<Point>>,
+}
+
+#[cfg(test)]
+impl PaintScene for ClipRecorder {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+    fn push_layer(
+        &mut self,
+        _blend: impl Into<peniko::BlendMode>,
+        _alpha: f32,
+        transform: Affine,
+        clip: &impl kurbo::Shape,
+    ) {
+        self.clips.push(transform * clip.to_path(0.1));
+    }
+    fn pop_layer(&mut self) {
+        self.clips.pop();
+    }
+    fn stroke<'a>(
+        &mut self,
+        _style: &Stroke,
+        _transform: Affine,
+        _brush: impl Into<anyrender::PaintRef<'a>>,
+        _brush_transform: Option<Affine>,
+        _shape: &impl kurbo::Shape,
+    ) {
+    }
+    fn fill<'a>(
+        &mut self,
+        _style: Fill,
+        transform: Affine,
+        brush: impl Into<anyrender::PaintRef<'a>>,
+        _brush_transform: Option<Affine>,
+        shape: &impl kurbo::Shape,
+    ) {
+        let shape = transform * shape.to_path(0.1);
+        self.fills.push((shape, self.clips.clone()));
+        self.fill_colors.push(match brush.into() {
+            Paint::Solid(color) => Some(color),
+            _ => None,
+        });
+    }
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        _font: &'a peniko::FontData,
+        _font_size: f32,
+        _hint: bool,
+        _normalized_coords: &'a [anyrender::NormalizedCoord],
+        _style: impl Into<peniko::StyleRef<'a>>,
+        _brush: impl Into<anyrender::PaintRef<'a>>,
+        _brush_alpha: f32,
+        transform: Affine,
+        _glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = anyrender::Glyph>,
+    ) {
+        let positions = glyphs
+            .map(|glyph| transform * Point::new(glyph.x as f64, glyph.y as f64))
+            .collect();
+        self.glyph_runs.push(positions);
+    }
+    fn draw_box_shadow(
+        &mut self,
+        _transform: Affine,
+        _rect: Rect,
+        _brush: peniko::Color,
+        _radius: f64,
+        _std_dev: f64,
+    ) {
+    }
+}
+
+#[cfg(test)]
+use blitz_dom::test_utils::doc_with_body;
+
+/// Paint `doc` at the size and scale of its viewport, recording the scene
+#[cfg(test)]
+pub(crate) fn record_scene(doc: &BaseDocument) -> ClipRecorder {
+    let viewport = doc.viewport();
+    let (width, height) = viewport.window_size;
+    let mut scene = ClipRecorder::default();
+    BlitzDomPainter::new(doc, viewport.scale_f64(), width, height).paint_scene(&mut scene);
+    scene
+}
+
+/// Render `doc` at the size and scale of its viewport, returning its RGBA8 pixels
+#[cfg(test)]
+pub(crate) fn render_pixels(doc: &BaseDocument) -> Vec<u8> {
+    let viewport = doc.viewport();
+    let (width, height) = viewport.window_size;
+    BlitzDomPainter::new(doc, viewport.scale_f64(), width, height)
+        .render_to_buffer::<anyrender_vello_cpu::VelloCpuImageRenderer>()
+}
+
+/// The RGBA8 color of the pixel at (`x`, `y`) in `pixels` (as returned by [`render_pixels`]), which
+/// are `width` pixels wide
+#[cfg(test)]
+pub(crate) fn pixel_at(pixels: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+    let idx = (y * width + x) * 4;
+    pixels[idx..idx + 4].try_into().unwrap()
+}
+
+#[test]
+fn clip_path_intersects_overflow_clip() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        .clipped { margin: 100px; width: 100px; height: 100px; overflow: hidden; clip-path: circle(60%) }
+        .child { margin: -100px; width: 300px; height: 300px; background: red }";
+    let (doc, ()) = doc_with_body(800, 600, css, |mutr, body| {
+        let clipped = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(clipped, qual_name!("class", html), "clipped");
+        let child = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(child, qual_name!("class", html), "child");
+        mutr.append_children(clipped, &[child]);
+        mutr.append_children(body, &[clipped]);
+    });
+    let scene = record_scene(&doc);
+
+    let (_, clips) = scene
+        .fills
+        .iter()
+        .find(|(shape, _)| shape.bounding_box() == Rect::new(0.0, 0.0, 300.0, 300.0))
+        .expect("the child's background should be painted");
+    let is_visible = |x: f64, y: f64| clips.iter().all(|clip| clip.contains(Point::new(x, y)));
+
+    // The center of the box is visible
+    assert!(is_visible(150.0, 150.0));
+    // The corner of the box is inside the overflow clip, but outside of the circle
+    assert!(!is_visible(105.0, 105.0));
+    // Just above the box is inside the circle (which has a radius of 60px), but outside of the overflow clip
+    assert!(!is_visible(150.0, 95.0));
+    // And outside of both is clipped
+    assert!(!is_visible(20.0, 20.0));
+}
+
+#[test]
+fn overflow_clip_follows_border_radius() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        .rounded { margin: 100px; width: 100px; height: 100px; border: 10px solid black;
+                   border-radius: 40px; overflow: hidden }
+        .child { margin: -100px; width: 300px; height: 300px; background: red }";
+    let (doc, ()) = doc_with_body(800, 600, css, |mutr, body| {
+        let rounded = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(rounded, qual_name!("class", html), "rounded");
+        let child = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(child, qual_name!("class", html), "child");
+        mutr.append_children(rounded, &[child]);
+        mutr.append_children(body, &[rounded]);
+    });
+    let scene = record_scene(&doc);
+
+    // The padding box spans (110, 110) to (210, 210) and the child is offset into it by -100px
+    let (_, clips) = scene
+        .fills
+        .iter()
+        .find(|(shape, _)| shape.bounding_box() == Rect::new(10.0, 10.0, 310.0, 310.0))
+        .expect("the child's background should be painted");
+    let is_visible = |x: f64, y: f64| clips.iter().all(|clip| clip.contains(Point::new(x, y)));
+
+    assert!(is_visible(160.0, 160.0));
+    // Near the middle of each edge of the padding box the child is visible
+    for (x, y) in [
+        (160.0, 111.0),
+        (209.0, 160.0),
+        (160.0, 209.0),
+        (111.0, 160.0),
+    ] {
+        assert!(is_visible(x, y));
+    }
+    // But the corners of the padding box are rounded off (with an inner radius of 30px)
+    for (x, y) in [
+        (112.0, 112.0),
+        (208.0, 112.0),
+        (208.0, 208.0),
+        (112.0, 208.0),
+    ] {
+        assert!(!is_visible(x, y));
+    }
+    // And nothing is visible outside of the padding box
+    assert!(!is_visible(105.0, 160.0));
+}
+
+#[test]
+fn modal_dialog_paints_above_document_and_backdrop() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        .page { height: 100px; background: red }
+        dialog { width: 200px; height: 100px; padding: 0; border: none; background: blue }";
+    let (mut doc, dialog) = doc_with_body(800, 600, css, |mutr, body| {
+        let dialog = mutr.create_element(qual_name!("dialog", html), vec![]);
+        let page = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(page, qual_name!("class", html), "page");
+        // The dialog comes first in the tree, so it would be painted beneath the page if it wasn't in the top layer
+        mutr.append_children(body, &[dialog, page]);
+        dialog
+    });
+    assert!(doc.show_modal(dialog));
+    doc.resolve(0.0);
+    let scene = record_scene(&doc);
+
+    let bounds: Vec<Rect> = scene
+        .fills
+        .iter()
+        .map(|(shape, _)| shape.bounding_box())
+        .collect();
+    let page_idx = bounds
+        .iter()
+        .position(|rect| *rect == Rect::new(0.0, 0.0, 800.0, 100.0))
+        .expect("the page's background should be painted");
+    let backdrop_idx = bounds
+        .iter()
+        .rposition(|rect| *rect == Rect::new(0.0, 0.0, 800.0, 600.0))
+        .expect("the backdrop should be painted");
+    let dialog_idx = bounds
+        .iter()
+        .position(|rect| rect.width() == 200.0 && rect.height() == 100.0)
+        .expect("the dialog's background should be painted");
+
+    assert!(page_idx < backdrop_idx);
+    assert!(backdrop_idx < dialog_idx);
+}
+
+#[test]
+fn modal_dialog_follows_ancestor_transforms_and_scroll() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        .container { position: relative; height: 300px; overflow: auto }
+        dialog { width: 200px; height: 100px; padding: 0; border: none; background: blue }";
+    let dialog_bounds = |container_style: &str, scroll_y: f64| {
+        let (mut doc, [container, dialog]) = doc_with_body(800, 600, css, |mutr, body| {
+            let container = mutr.create_element(qual_name!("div", html), vec![]);
+            mutr.set_attribute(container, qual_name!("class", html), "container");
+            mutr.set_attribute(container, qual_name!("style"), container_style);
+            let dialog = mutr.create_element(qual_name!("dialog", html), vec![]);
+            mutr.append_children(container, &[dialog]);
+            mutr.append_children(body, &[container]);
+            [container, dialog]
+        });
+        assert!(doc.show_modal(dialog));
+        doc.resolve(0.0);
+        doc.get_node_mut(container).unwrap().scroll_offset.y = scroll_y;
+
+        record_scene(&doc)
+            .fills
+            .iter()
+            .map(|(shape, _)| shape.bounding_box())
+            .find(|rect| {
+                (rect.size() - kurbo::Size::new(200.0, 100.0))
+                    .to_vec2()
+                    .hypot()
+                    < 1e-3
+            })
+            .expect("the dialog's background should be painted")
+    };
+
+    let untransformed = dialog_bounds("", 0.0);
+    let translated = dialog_bounds("transform: translate(100px, 50px)", 0.0);
+    let scrolled = dialog_bounds("", 30.0);
+    let offset = |rect: Rect| rect.origin() - untransformed.origin();
+    assert!((offset(translated) - Vec2::new(100.0, 50.0)).hypot() < 1e-3);
+    assert!((offset(scrolled) - Vec2::new(0.0, -30.0)).hypot() < 1e-3);
+}
+
+#[test]
+fn outside_markers_stay_on_the_inline_start_side() {
+    use blitz_dom::qual_name;
+
+    let css = "body { margin: 0 }
+        ul { margin: 0; padding: 0 40px; width: 200px }
+        .right { text-align: right }
+        .rtl { direction: rtl }";
+    let (doc, ()) = doc_with_body(800, 600, css, |mutr, body| {
+        let list = mutr.create_element(qual_name!("ul", html), vec![]);
+        let items: Vec<usize> = ["right", "rtl"]
+            .into_iter()
+            .map(|class| {
+                let item = mutr.create_element(qual_name!("li", html), vec![]);
+                mutr.set_attribute(item, qual_name!("class", html), class);
+                let text = mutr.create_text_node("Item");
+                mutr.append_children(item, &[text]);
+                item
+            })
+            .collect();
+        mutr.append_children(list, &items);
+        mutr.append_children(body, &[list]);
+    });
+    let scene = record_scene(&doc);
+
+    // Each list item paints its text and then its marker. The content boxes span x = 40 to 240.
+    let runs: Vec<&Vec<Point>> = scene
+        .glyph_runs
+        .iter()
+        .filter(|run| !run.is_empty())
+        .collect();
+    assert_eq!(runs.len(), 4);
+    let (right_text, right_marker, rtl_marker) = (runs[0], runs[1], runs[3]);
+
+    // Right-aligned text moves to the right, but the marker stays to the left of the content box
+    assert!(right_text.iter().all(|glyph| glyph.x > 140.0));
+    assert!(right_marker.iter().all(|glyph| glyph.x < 40.0));
+
+    // In right-to-left text the marker is to the right of the content box
+    assert!(rtl_marker.iter().all(|glyph| glyph.x >= 240.0));
+}
+
+#[test]
+fn missing_image_paints_alt_text() {
+    use blitz_dom::qual_name;
+
+    let (doc, img) = doc_with_body(800, 600, "", |mutr, body| {
+        let img = mutr.create_element(qual_name!("img", html), vec![]);
+        mutr.set_attribute(img, qual_name!("alt"), "description");
+        mutr.append_children(body, &[img]);
+        img
+    });
+
+    // The image is sized to fit its alt text
+    let layout = doc.get_node(img).unwrap().final_layout;
+    assert!(layout.size.width > 0.0 && layout.size.height > 0.0);
+
+    let scene = record_scene(&doc);
+
+    let runs: Vec<&Vec<Point>> = scene
+        .glyph_runs
+        .iter()
+        .filter(|run| !run.is_empty())
+        .collect();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].len(), "description".len());
+}
+
+#[test]
+fn paint_page_paints_a_slice_of_the_document() {
+    use blitz_dom::qual_name;
+    use blitz_traits::shell::{MediaType, Viewport};
+    use kurbo::Shape;
+
+    let (mut doc, ()) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let first = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(first, qual_name!("style"), "height: 400px; background: red");
+        let second = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            second,
+            qual_name!("style"),
+            "height: 400px; background: lime; break-before: page",
+        );
+        mutr.append_children(body, &[first, second]);
+    });
+    doc.set_viewport(
+        Viewport::new(800, 600, 1.0, ColorScheme::Light).with_media_type(MediaType::Print),
+    );
+    doc.resolve(0.0);
+    assert_eq!(crate::page_count(&doc, 1.0, 600), 2);
+
+    // The second page starts at the forced break, so the second block is at the top of the page
+    let mut scene = ClipRecorder::default();
+    assert!(crate::paint_page(&mut scene, &doc, 1, 1.0, 800, 600));
+    assert!(
+        scene
+            .fills
+            .iter()
+            .any(|(shape, _)| shape.bounding_box() == Rect::new(0.0, 0.0, 800.0, 400.0))
+    );
+
+    // Everything is clipped to the part of the document which belongs to the page
+    for (_, clips) in &scene.fills {
+        assert_eq!(clips[0].bounding_box(), Rect::new(0.0, 0.0, 800.0, 400.0));
+    }
+
+    assert!(!crate::paint_page(&mut scene, &doc, 2, 1.0, 800, 600));
+}
+
+#[test]
+fn dark_color_scheme_paints_dark_controls_and_canvas() {
+    use self::form_controls::{DARK_CONTROL_PALETTE, LIGHT_CONTROL_PALETTE};
+    use blitz_dom::qual_name;
+
+    let css = "html { color-scheme: dark }";
+    let (doc, ()) = doc_with_body(800, 600, css, |mutr, body| {
+        let checkbox = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(checkbox, qual_name!("type"), "checkbox");
+        mutr.append_children(body, &[checkbox]);
+    });
+    let scene = record_scene(&doc);
+
+    // The canvas is dark even though the page has no background of its own
+    assert_eq!(scene.fill_colors[0], Some(DARK_CANVAS_COLOR));
+
+    // The (inherited) dark scheme gives the checkbox a dark face
+    assert!(
+        scene
+            .fill_colors
+            .contains(&Some(DARK_CONTROL_PALETTE.background))
+    );
+    assert!(
+        !scene
+            .fill_colors
+            .contains(&Some(LIGHT_CONTROL_PALETTE.background))
+    );
+}
+
+#[test]
+fn placeholder_is_painted_with_placeholder_pseudo_element_styles() {
+    use blitz_dom::qual_name;
+
+    let css = "body { margin: 0 } ::placeholder { color: rgb(255, 0, 0); opacity: 1 }";
+    let render = |value: Option<&str>| {
+        let (doc, ()) = doc_with_body(120, 40, css, |mutr, body| {
+            let input = mutr.create_element(qual_name!("input", html), vec![]);
+            mutr.set_attribute(input, qual_name!("placeholder"), "MMMM");
+            mutr.set_attribute(input, qual_name!("style"), "font-size: 24px; width: 100px");
+            if let Some(value) = value {
+                mutr.set_attribute(input, qual_name!("value"), value);
+            }
+            mutr.append_children(body, &[input]);
+        });
+        render_pixels(&doc)
+    };
+    let has_red = |pixels: &[u8]| {
+        pixels
+            .chunks(4)
+            .any(|pixel| pixel[0] > 200 && pixel[1] < 60 && pixel[2] < 60)
+    };
+
+    // The placeholder is red, but only shown while the input is empty
+    assert!(has_red(&render(None)));
+    assert!(!has_red(&render(Some("MMMM"))));
+}
+
+#[test]
+fn clipped_away_corners_are_not_hit() {
+    use blitz_dom::qual_name;
+
+    let (doc, [behind, image]) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let behind = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(behind, qual_name!("style"), "width: 200px; height: 200px");
+        let image = mutr.create_element(qual_name!("img", html), vec![]);
+        mutr.set_attribute(
+            image,
+            qual_name!("style"),
+            "position: absolute; top: 0; left: 0; width: 100px; height: 100px; clip-path: circle()",
+        );
+        mutr.append_children(body, &[behind, image]);
+        [behind, image]
+    });
+
+    // The clip path is resolved for hit testing without the document being painted first. The
+    // middle of the circle hits the image, but its corners hit what is behind it.
+    assert_eq!(doc.hit(50.0, 50.0).unwrap().node_id, image);
+    assert_eq!(doc.hit(5.0, 5.0).unwrap().node_id, behind);
+    assert_eq!(doc.hit(95.0, 95.0).unwrap().node_id, behind);
+}
+
+#[test]
+fn groove_and_ridge_borders_are_shaded() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        div { width: 100px; height: 50px; border: 4px groove rgb(100, 100, 100) }
+        .ridge { border-style: ridge }";
+    let (mut doc, div) = doc_with_body(800, 600, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(body, &[div]);
+        div
+    });
+
+    // The shades of the outer and inner halves of the top edge
+    let top_edge_shades = |doc: &BaseDocument| {
+        let scene = record_scene(doc);
+        let shade_of = |bounds: Rect| {
+            let idx = scene
+                .fills
+                .iter()
+                .position(|(shape, _)| shape.bounding_box() == bounds)
+                .expect("the top edge should be painted");
+            let color = scene.fill_colors[idx].unwrap().to_rgba8();
+            (color.r, color.g, color.b)
+        };
+        (
+            shade_of(Rect::new(0.0, 0.0, 108.0, 2.0)),
+            shade_of(Rect::new(0.0, 0.0, 108.0, 4.0)),
+        )
     };
 
-    CssBox::new(border_box, border, padding, outline_width, border_radii)
+    let light = (130, 130, 130);
+    let dark = (70, 70, 70);
+    assert_eq!(top_edge_shades(&doc), (dark, light));
+
+    doc.mutate()
+        .set_attribute(div, qual_name!("class", html), "ridge");
+    doc.resolve(0.0);
+    assert_eq!(top_edge_shades(&doc), (light, dark));
+}
+
+#[test]
+fn outset_border_is_lighter_on_top_and_left() {
+    use blitz_dom::qual_name;
+
+    let (doc, ()) = doc_with_body(200, 100, "body { margin: 0 }", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 100px; height: 50px; border: 4px outset silver",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let buffer = render_pixels(&doc);
+    // The red channel of the pixel in the middle of each edge (the border box is 108x58)
+    let red_at = |x: usize, y: usize| pixel_at(&buffer, 200, x, y)[0];
+    let (top, left) = (red_at(54, 2), red_at(2, 29));
+    let (bottom, right) = (red_at(54, 55), red_at(105, 29));
+
+    assert_eq!(top, left);
+    assert_eq!(bottom, right);
+    assert!(top > 192 && bottom < 192);
+}
+
+#[test]
+fn caret_is_at_least_one_device_pixel_wide() {
+    use blitz_dom::qual_name;
+    use blitz_traits::shell::Viewport;
+    use kurbo::Shape;
+
+    let caret_color = Color::from_rgb8(1, 2, 3);
+    for scale in [0.5, 3.0] {
+        let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+            let input = mutr.create_element(qual_name!("input", html), vec![]);
+            mutr.set_attribute(input, qual_name!("style"), "color: rgb(1, 2, 3)");
+            mutr.append_children(body, &[input]);
+            input
+        });
+        doc.set_viewport(Viewport::new(800, 600, scale, ColorScheme::Light));
+        doc.resolve(0.0);
+        doc.focus(input);
+        doc.resolve(0.0);
+
+        let mut scene = ClipRecorder::default();
+        let (width, height) = ((800.0 * scale) as u32, (600.0 * scale) as u32);
+        BlitzDomPainter::new(&doc, scale as f64, width, height).paint_scene(&mut scene);
+
+        let caret_idx = scene
+            .fill_colors
+            .iter()
+            .position(|color| *color == Some(caret_color))
+            .expect("the caret should be painted");
+        let caret = scene.fills[caret_idx].0.bounding_box();
+        assert!(caret.width() >= 1.0, "caret is {caret:?} at scale {scale}");
+    }
+}
+
+#[test]
+fn caret_follows_caret_color() {
+    use blitz_dom::qual_name;
+
+    let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+        let input = mutr.create_element(qual_name!("input", html), vec![]);
+        mutr.set_attribute(input, qual_name!("style"), "caret-color: green");
+        mutr.append_children(body, &[input]);
+        input
+    });
+    doc.focus(input);
+    doc.resolve(0.0);
+
+    let scene = record_scene(&doc);
+
+    assert!(
+        scene
+            .fill_colors
+            .contains(&Some(Color::from_rgb8(0, 128, 0)))
+    );
+}
+
+#[test]
+fn caret_is_transformed_with_its_input() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    // Paints a focussed input with the given style and returns the bounds of its caret
+    let caret_bounds = |style: &str| {
+        let (mut doc, input) = doc_with_body(800, 600, "", |mutr, body| {
+            let input = mutr.create_element(qual_name!("input", html), vec![]);
+            let style = format!("caret-color: rgb(1, 2, 3); margin: 100px; {style}");
+            mutr.set_attribute(input, qual_name!("style"), &style);
+            mutr.append_children(body, &[input]);
+            input
+        });
+        doc.focus(input);
+        doc.resolve(0.0);
+
+        let scene = record_scene(&doc);
+        let caret_idx = scene
+            .fill_colors
+            .iter()
+            .position(|color| *color == Some(Color::from_rgb8(1, 2, 3)))
+            .expect("the caret should be painted");
+        scene.fills[caret_idx].0.bounding_box()
+    };
+
+    let caret = caret_bounds("");
+    let scaled_caret = caret_bounds("transform: scale(3)");
+    assert!(
+        (scaled_caret.width() - caret.width() * 3.0).abs() < 0.01,
+        "{scaled_caret:?} is not 3 times as wide as {caret:?}"
+    );
+    assert!(
+        (scaled_caret.height() - caret.height() * 3.0).abs() < 0.01,
+        "{scaled_caret:?} is not 3 times as tall as {caret:?}"
+    );
+
+    // The caret moves with the input's text (rather than staying where the input is laid out)
+    let translated_caret = caret_bounds("transform: translate(50px, 20px)");
+    assert_eq!(translated_caret, caret + kurbo::Vec2::new(50.0, 20.0));
+}
+
+#[test]
+fn selection_scrolls_with_its_text() {
+    use blitz_dom::{EventDriver, NoopEventHandler, qual_name};
+    use blitz_traits::events::{
+        BlitzMouseButtonEvent, MouseEventButton, MouseEventButtons, UiEvent,
+    };
+    use kurbo::Shape;
+
+    let (mut doc, container) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let container = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            container,
+            qual_name!("style"),
+            "height: 100px; overflow: auto",
+        );
+        let textarea = mutr.create_element(qual_name!("textarea", html), vec![]);
+        mutr.set_attribute(
+            textarea,
+            qual_name!("style"),
+            "display: block; margin: 0; width: 200px; height: 300px; font-size: 10px; line-height: 20px",
+        );
+        let text: Vec<String> = (0..15).map(|line| format!("line {line}")).collect();
+        mutr.set_attribute(textarea, qual_name!("value"), &text.join("\n"));
+        mutr.append_children(container, &[textarea]);
+        mutr.append_children(body, &[container]);
+        container
+    });
+
+    // Drag to select the first few lines
+    let mouse_event = |y, buttons| BlitzMouseButtonEvent {
+        x: 10.0,
+        y,
+        mods: Default::default(),
+        button: MouseEventButton::Main,
+        buttons,
+    };
+    let mut driver = EventDriver::new(doc.mutate(), NoopEventHandler);
+    driver.handle_ui_event(UiEvent::MouseDown(mouse_event(
+        10.0,
+        MouseEventButtons::Primary,
+    )));
+    driver.handle_ui_event(UiEvent::MouseMove(mouse_event(
+        50.0,
+        MouseEventButtons::Primary,
+    )));
+    driver.handle_ui_event(UiEvent::MouseUp(mouse_event(50.0, MouseEventButtons::None)));
+    drop(driver);
+    doc.resolve(0.0);
+
+    // The bounds of the painted selection
+    let selection_bounds = |doc: &BaseDocument| {
+        let scene = record_scene(doc);
+        scene
+            .fills
+            .iter()
+            .zip(&scene.fill_colors)
+            .filter(|(_, color)| **color == Some(SELECTION_COLOR))
+            .map(|((path, _), _)| path.bounding_box())
+            .reduce(|a, b| a.union(b))
+            .expect("the selection should be painted")
+    };
+
+    let selection = selection_bounds(&doc);
+    doc.scroll_node_by(container, 0.0, -60.0);
+    assert_eq!(
+        selection_bounds(&doc),
+        selection - kurbo::Vec2::new(0.0, 60.0)
+    );
+}
+
+#[test]
+fn changing_opacity_keeps_sibling_paint_caches() {
+    use blitz_dom::qual_name;
+
+    let css = "div { width: 100px; height: 100px; clip-path: circle(50%) }";
+    let (mut doc, [changed, sibling]) = doc_with_body(800, 600, css, |mutr, body| {
+        let changed = mutr.create_element(qual_name!("div", html), vec![]);
+        let sibling = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.append_children(body, &[changed, sibling]);
+        [changed, sibling]
+    });
+    let cache_is_valid = |doc: &BaseDocument, node_id: usize| {
+        let node = doc.get_node(node_id).unwrap();
+        node.clip_path_cache.is_valid_for(node)
+    };
+
+    record_scene(&doc);
+    assert!(cache_is_valid(&doc, changed));
+    assert!(cache_is_valid(&doc, sibling));
+
+    // Only the restyled element's cached clip path is invalidated
+    doc.mutate().set_style_property(changed, "opacity", "0.5");
+    doc.resolve(0.0);
+    assert!(!cache_is_valid(&doc, changed));
+    assert!(cache_is_valid(&doc, sibling));
+
+    record_scene(&doc);
+    assert!(cache_is_valid(&doc, changed));
+}
+
+#[test]
+fn opacity_composites_overlapping_children_as_one_layer() {
+    use blitz_dom::qual_name;
+
+    let css = "html { background: white }
+        body { margin: 0 }
+        .group { opacity: 0.5; position: relative; height: 60px }
+        .box { position: absolute; top: 0; width: 60px; height: 60px; background: black }";
+    let (doc, ()) = doc_with_body(200, 100, css, |mutr, body| {
+        let group = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(group, qual_name!("class", html), "group");
+        let boxes: Vec<usize> = [0, 30]
+            .into_iter()
+            .map(|left| {
+                let child = mutr.create_element(qual_name!("div", html), vec![]);
+                mutr.set_attribute(child, qual_name!("class", html), "box");
+                mutr.set_attribute(child, qual_name!("style"), &format!("left: {left}px"));
+                child
+            })
+            .collect();
+        mutr.append_children(group, &boxes);
+        mutr.append_children(body, &[group]);
+    });
+    let buffer = render_pixels(&doc);
+    let red_at = |x: usize, y: usize| pixel_at(&buffer, 200, x, y)[0];
+
+    // Only one box covers x=10 and both cover x=45, but the group is blended as a whole so both are
+    // half way between black and the root's white background
+    let single = red_at(10, 30);
+    let overlap = red_at(45, 30);
+    assert!(single.abs_diff(128) <= 2, "{single}");
+    assert_eq!(single, overlap);
+}
+
+#[test]
+fn relative_offsets_move_painted_boxes() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let css = "body { margin: 0 }
+        div { height: 50px }
+        span { display: inline-block; width: 50px; height: 50px }
+        .relative { position: relative; top: 20px; background: rgb(255, 0, 0) }
+        .inline { position: relative; inset: 20px auto auto 10px; background: rgb(0, 255, 0) }
+        .static { top: 20px; background: rgb(0, 0, 255) }";
+    let (doc, boxes) = doc_with_body(800, 600, css, |mutr, body| {
+        let boxes: Vec<usize> = ["relative", "static"]
+            .into_iter()
+            .map(|class| {
+                let div = mutr.create_element(qual_name!("div", html), vec![]);
+                mutr.set_attribute(div, qual_name!("class", html), class);
+                div
+            })
+            .collect();
+        let line = mutr.create_element(qual_name!("div", html), vec![]);
+        let inline = mutr.create_element(qual_name!("span", html), vec![]);
+        mutr.set_attribute(inline, qual_name!("class", html), "inline");
+        mutr.append_children(line, &[inline]);
+        mutr.append_children(body, &boxes);
+        mutr.append_children(body, &[line]);
+        boxes
+    });
+
+    let scene = record_scene(&doc);
+    let painted_bounds = |color: Color| {
+        let idx = scene
+            .fill_colors
+            .iter()
+            .position(|fill| *fill == Some(color))
+            .expect("the box should be painted");
+        scene.fills[idx].0.bounding_box()
+    };
+
+    // The relatively positioned block is shifted down by 20px, and so is what it hit tests against
+    let relative = painted_bounds(Color::from_rgb8(255, 0, 0));
+    assert_eq!(relative, Rect::new(0.0, 20.0, 800.0, 70.0));
+    assert_ne!(doc.hit(5.0, 10.0).unwrap().node_id, boxes[0]);
+    assert_eq!(doc.hit(5.0, 30.0).unwrap().node_id, boxes[0]);
+
+    // Insets don't apply to statically positioned boxes
+    let unpositioned = painted_bounds(Color::from_rgb8(0, 0, 255));
+    assert_eq!(unpositioned, Rect::new(0.0, 50.0, 800.0, 100.0));
+
+    // Relatively positioned inline-level boxes are offset too (the `inset` shorthand sets `top` and `left`)
+    let inline = painted_bounds(Color::from_rgb8(0, 255, 0));
+    assert_eq!((inline.x0, inline.y0), (10.0, 120.0));
+}
+
+#[test]
+fn text_shadows_are_painted_beneath_text_in_reverse_order() {
+    use blitz_dom::qual_name;
+
+    let (doc, ()) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "text-shadow: 2px 3px red, 4px 6px blue",
+        );
+        let text = mutr.create_text_node("Shadow");
+        mutr.append_children(div, &[text]);
+        mutr.append_children(body, &[div]);
+    });
+    let scene = record_scene(&doc);
+
+    // The last shadow is painted first and the text itself is painted last (on top)
+    let runs: Vec<&Vec<Point>> = scene
+        .glyph_runs
+        .iter()
+        .filter(|run| !run.is_empty())
+        .collect();
+    assert_eq!(runs.len(), 3);
+    let (last_shadow, first_shadow, text) = (runs[0], runs[1], runs[2]);
+    assert_eq!(last_shadow[0] - text[0], Vec2::new(4.0, 6.0));
+    assert_eq!(first_shadow[0] - text[0], Vec2::new(2.0, 3.0));
+}
+
+#[test]
+fn outline_is_not_clipped_by_overflow() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, ()) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "margin: 20px; width: 100px; height: 100px; overflow: hidden; outline: 5px solid rgb(255, 0, 0)",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let scene = record_scene(&doc);
+
+    let outline_idx = scene
+        .fill_colors
+        .iter()
+        .position(|color| *color == Some(Color::from_rgb8(255, 0, 0)))
+        .expect("the outline should be painted");
+    let (outline, clips) = &scene.fills[outline_idx];
+    let outline = outline.bounding_box();
+    assert_eq!(outline.size(), kurbo::Size::new(110.0, 110.0));
+
+    // None of the layers it is painted in clip it (the element's overflow clip only applies to its contents)
+    assert!(
+        clips
+            .iter()
+            .all(|clip| clip.bounding_box().union(outline) == clip.bounding_box())
+    );
+}
+
+#[test]
+fn outline_offset_leaves_a_gap_around_the_border() {
+    use blitz_dom::qual_name;
+    use kurbo::Shape;
+
+    let (doc, ()) = doc_with_body(800, 600, "body { margin: 0 }", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "margin: 20px; width: 100px; height: 100px; outline: 2px solid red; outline-offset: 5px",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let scene = record_scene(&doc);
+    let outline_idx = scene
+        .fill_colors
+        .iter()
+        .position(|color| *color == Some(Color::from_rgb8(255, 0, 0)))
+        .expect("the outline should be painted");
+    let outline = &scene.fills[outline_idx].0;
+
+    // The border box is at 20..120, so the outline covers 13..15 (and 125..127)
+    assert_eq!(outline.bounding_box(), Rect::new(13.0, 13.0, 127.0, 127.0));
+    for (x, painted) in [(12.5, false), (14.0, true), (16.0, false), (19.5, false)] {
+        assert_eq!(outline.contains(Point::new(x, 70.0)), painted, "at x = {x}");
+        let y = 140.0 - x;
+        assert_eq!(outline.contains(Point::new(70.0, y)), painted, "at y = {y}");
+    }
+}
+
+#[test]
+fn hidden_elements_are_not_painted() {
+    use blitz_dom::qual_name;
+
+    let css = "body { margin: 0 } \
+               #descendant { position: absolute; visibility: visible; width: 10px; height: 10px; \
+               background: yellow }";
+    let (mut doc, [hidden, sibling, descendant]) = doc_with_body(800, 600, css, |mutr, body| {
+        let [hidden, until_found, sibling] =
+            [("red", "hidden"), ("lime", "until-found"), ("blue", "")].map(|(color, hidden)| {
+                let div = mutr.create_element(qual_name!("div", html), vec![]);
+                let style = format!("height: 50px; background: {color}");
+                mutr.set_attribute(div, qual_name!("style"), &style);
+                if !hidden.is_empty() {
+                    mutr.set_attribute(div, qual_name!("hidden"), hidden);
+                }
+                div
+            });
+        mutr.set_attribute(until_found, qual_name!("id"), "found");
+        let descendant = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(descendant, qual_name!("id"), "descendant");
+        mutr.append_children(until_found, &[descendant]);
+        mutr.append_children(body, &[hidden, until_found, sibling]);
+        [hidden, sibling, descendant]
+    });
+
+    let painted_colors = |doc: &BaseDocument| record_scene(doc).fill_colors;
+    let red = Some(Color::from_rgb8(255, 0, 0));
+    let lime = Some(Color::from_rgb8(0, 255, 0));
+    let blue = Some(Color::from_rgb8(0, 0, 255));
+    let yellow = Some(Color::from_rgb8(255, 255, 0));
+
+    // `hidden` elements are removed from layout, whereas `hidden="until-found"` elements keep their space
+    assert_eq!(doc.get_node(hidden).unwrap().final_layout.size.height, 0.0);
+    assert_eq!(doc.get_node(sibling).unwrap().final_layout.location.y, 50.0);
+    let colors = painted_colors(&doc);
+    assert!(colors.contains(&blue));
+    assert!(!colors.contains(&red));
+    assert!(!colors.contains(&lime));
+
+    // Descendants can't override it, even when they are hoisted out of the hidden element
+    assert!(!colors.contains(&yellow));
+    assert_ne!(doc.hit(5.0, 5.0).map(|hit| hit.node_id), Some(descendant));
+
+    // Navigating to a `hidden="until-found"` element reveals it
+    doc.navigate_to_fragment("found");
+    doc.resolve(0.0);
+    let colors = painted_colors(&doc);
+    assert!(colors.contains(&lime));
+    assert!(colors.contains(&yellow));
+    assert!(!colors.contains(&red));
+    assert_eq!(doc.hit(5.0, 5.0).map(|hit| hit.node_id), Some(descendant));
 }
@@ -2,8 +2,10 @@ use super::{ElementCx, to_image_quality, to_peniko_image};
 use crate::color::{Color, ToColorColor};
 use crate::gradient::to_peniko_gradient;
 use crate::layers::maybe_with_layer;
+use crate::sizing::compute_object_fit;
 use anyrender::PaintScene;
 use blitz_dom::node::ImageData;
+use blitz_dom::util::select_image_set_candidate;
 use kurbo::{self, BezPath, Point, Rect, Shape, Size, Vec2};
 use peniko::{self, Fill};
 use style::{
@@ -59,7 +61,8 @@ impl ElementCx<'_> {
                 self.transform,
                 &background_clip_path,
                 |scene| {
-                    match segment {
+                    let (image, resolution) = select_image_set_candidate(segment);
+                    match image {
                         None => {
                             // Do nothing
                         }
@@ -67,9 +70,9 @@ impl ElementCx<'_> {
                             self.draw_gradient_bg(scene, gradient, idx, *background_clip)
                         }
                         Url(_) => {
-                            self.draw_raster_bg_image(scene, idx);
+                            self.draw_raster_bg_image(scene, idx, resolution);
                             #[cfg(feature = "svg")]
-                            self.draw_svg_bg_image(scene, idx);
+                            self.draw_svg_bg_image(scene, idx, resolution);
                         }
                         LightDark(_) => {
                             #[cfg(feature = "tracing")]
@@ -84,8 +87,7 @@ impl ElementCx<'_> {
                             warn!("Implement background drawing for Image::CrossFade")
                         }
                         ImageSet(_) => {
-                            #[cfg(feature = "tracing")]
-                            warn!("Implement background drawing for Image::ImageSet")
+                            // An `image-set()` without any candidate that could be selected
                         }
                     }
                 },
@@ -107,55 +109,63 @@ impl ElementCx<'_> {
     }
 
     #[cfg(feature = "svg")]
-    fn draw_svg_bg_image(&self, scene: &mut impl PaintScene, idx: usize) {
+    fn draw_svg_bg_image(&self, scene: &mut impl PaintScene, idx: usize, resolution: f32) {
         let bg_image = self.element.background_images.get(idx);
 
         let Some(Some(bg_image)) = bg_image.as_ref() else {
             return;
         };
-        let ImageData::Svg(svg) = &bg_image.image else {
+        // Monochrome icons which use `currentColor` are tinted with the element's color
+        let current_color = self.style.clone_color().as_srgb_color();
+        let Some(svg) = bg_image.svg_image(current_color) else {
             return;
         };
 
         let bg_styles = &self.style.get_background();
 
-        let frame_w = self.frame.padding_box.width() as f32;
-        let frame_h = self.frame.padding_box.height() as f32;
+        let background_origin = get_cyclic(&bg_styles.background_origin.0, idx);
+        let origin_rect = match background_origin {
+            StyloBackgroundOrigin::BorderBox => self.frame.border_box,
+            StyloBackgroundOrigin::PaddingBox => self.frame.padding_box,
+            StyloBackgroundOrigin::ContentBox => self.frame.content_box,
+        };
 
         let svg_size = svg.size();
-        let bg_size = compute_background_size(
+        let (bg_pos, bg_size) = compute_background_position_and_background_size(
             bg_styles,
-            frame_w,
-            frame_h,
+            origin_rect.width() / self.scale,
+            origin_rect.height() / self.scale,
             idx,
             BackgroundSizeComputeMode::Size(
-                svg_size.width() / self.scale as f32,
-                svg_size.height() / self.scale as f32,
+                svg_size.width() / resolution,
+                svg_size.height() / resolution,
             ),
-            self.scale as f32,
         );
-        let bg_size = bg_size * self.scale;
 
-        let x_ratio = bg_size.width as f64 / svg_size.width() as f64;
-        let y_ratio = bg_size.height as f64 / svg_size.height() as f64;
-
-        let bg_pos = compute_background_position(
-            bg_styles,
-            idx,
-            frame_w - bg_size.width as f32,
-            frame_h - bg_size.height as f32,
-        );
-
-        let transform = kurbo::Affine::translate((
-            (self.pos.x * self.scale) + bg_pos.x,
-            (self.pos.y * self.scale) + bg_pos.y,
-        ))
-        .pre_scale_non_uniform(x_ratio, y_ratio);
+        // Images which are scaled down to nothing aren't drawn
+        if bg_size.is_zero_area() {
+            return;
+        }
 
-        anyrender_svg::render_svg_tree(scene, svg, transform);
+        let bg_size = bg_size * self.scale;
+        let x_ratio = bg_size.width / svg_size.width() as f64;
+        let y_ratio = bg_size.height / svg_size.height() as f64;
+
+        // TODO: repeat SVG backgrounds (they are currently always drawn once)
+        let transform = self
+            .transform
+            .then_translate(Vec2 {
+                x: origin_rect.x0 + bg_pos.x * self.scale,
+                y: origin_rect.y0 + bg_pos.y * self.scale,
+            })
+            .pre_scale_non_uniform(x_ratio, y_ratio);
+
+        anyrender_svg::render_svg_tree(scene, &svg, transform);
     }
 
-    fn draw_raster_bg_image(&self, scene: &mut impl PaintScene, idx: usize) {
+    /// `resolution` is the number of image pixels per CSS pixel (which is not `1.0` for images selected
+    /// from an `image-set()`)
+    fn draw_raster_bg_image(&self, scene: &mut impl PaintScene, idx: usize, resolution: f32) {
         use BackgroundRepeatKeyword::*;
 
         let bg_image = self.element.background_images.get(idx);
@@ -187,9 +197,17 @@ impl ElementCx<'_> {
             origin_rect.width() / self.scale,
             origin_rect.height() / self.scale,
             idx,
-            BackgroundSizeComputeMode::Size(image_width as f32, image_height as f32),
+            BackgroundSizeComputeMode::Size(
+                image_width as f32 / resolution,
+                image_height as f32 / resolution,
+            ),
         );
 
+        // Images which are scaled down to nothing aren't drawn
+        if bg_size.is_zero_area() {
+            return;
+        }
+
         let bg_pos_x = bg_pos.x * self.scale;
         let bg_pos_y = bg_pos.y * self.scale;
         let bg_size = bg_size * self.scale;
@@ -200,19 +218,22 @@ impl ElementCx<'_> {
         let BackgroundRepeat(repeat_x, repeat_y) = get_cyclic(&bg_styles.background_repeat.0, idx);
 
         let transform = self.transform.pre_scale_non_uniform(x_ratio, y_ratio);
+        // Repeated images are tiled across the whole border box (the largest area that backgrounds can
+        // be clipped to), starting from a tile which is aligned with the positioned image
+        let border_box = self.frame.border_box;
         let (origin_rect, transform) = match repeat_x {
             Repeat | Round => {
-                let extend_width = extend(bg_pos_x, bg_size.width);
+                let (start, length) = tile_span(
+                    origin_rect.x0 + bg_pos_x,
+                    bg_size.width,
+                    border_box.x0,
+                    border_box.x1,
+                );
 
-                let transform = transform.then_translate(Vec2 {
-                    x: origin_rect.x0 - extend_width,
-                    y: 0.0,
-                });
+                let transform = transform.then_translate(Vec2 { x: start, y: 0.0 });
 
-                let origin_rect = origin_rect.with_size(Size::new(
-                    (origin_rect.width() + extend_width) / x_ratio,
-                    origin_rect.height(),
-                ));
+                let origin_rect =
+                    origin_rect.with_size(Size::new(length / x_ratio, origin_rect.height()));
 
                 (origin_rect, transform)
             }
@@ -231,17 +252,17 @@ impl ElementCx<'_> {
         };
         let (origin_rect, transform) = match repeat_y {
             Repeat | Round => {
-                let extend_height = extend(bg_pos_y, bg_size.height);
+                let (start, length) = tile_span(
+                    origin_rect.y0 + bg_pos_y,
+                    bg_size.height,
+                    border_box.y0,
+                    border_box.y1,
+                );
 
-                let transform = transform.then_translate(Vec2 {
-                    x: 0.0,
-                    y: origin_rect.y0 - extend_height,
-                });
+                let transform = transform.then_translate(Vec2 { x: 0.0, y: start });
 
-                let origin_rect = origin_rect.with_size(Size::new(
-                    origin_rect.width(),
-                    (origin_rect.height() + extend_height) / y_ratio,
-                ));
+                let origin_rect =
+                    origin_rect.with_size(Size::new(origin_rect.width(), length / y_ratio));
 
                 (origin_rect, transform)
             }
@@ -365,6 +386,11 @@ impl ElementCx<'_> {
             BackgroundSizeComputeMode::Auto,
         );
 
+        // Gradients which are sized to nothing aren't drawn
+        if bg_size.is_zero_area() {
+            return;
+        }
+
         let bg_pos_x = bg_pos.x * self.scale;
         let bg_pos_y = bg_pos.y * self.scale;
         let bg_size = bg_size * self.scale;
@@ -617,38 +643,39 @@ fn compute_background_position_and_background_size(
         container_h as f32,
         bg_idx,
         size_mode,
-        1.0,
-    );
-
-    let bg_pos = compute_background_position(
-        background,
-        bg_idx,
-        (container_w - bg_size.width) as f32,
-        (container_h - bg_size.height) as f32,
     );
 
     let BackgroundRepeat(repeat_x, repeat_y) = get_cyclic(&background.background_repeat.0, bg_idx);
 
+    // Rounded images are scaled so that a whole number of them (and at least one) fit
     let bg_size = if matches!(repeat_x, Round) && matches!(repeat_y, Round) {
-        let count = (container_w / bg_size.width).round();
+        let count = (container_w / bg_size.width).round().max(1.0);
         let width = container_w / count;
 
-        let count = (container_h / bg_size.height).round();
+        let count = (container_h / bg_size.height).round().max(1.0);
         let height = container_h / count;
 
         Size::new(width, height)
     } else if matches!(repeat_x, Round) {
-        let count = (container_w / bg_size.width).round();
+        let count = (container_w / bg_size.width).round().max(1.0);
         let width = container_w / count;
         Size::new(width, bg_size.height)
     } else if matches!(repeat_y, Round) {
-        let count = (container_h / bg_size.height).round();
+        let count = (container_h / bg_size.height).round().max(1.0);
         let height = container_h / count;
         Size::new(bg_size.width, height)
     } else {
         bg_size
     };
 
+    // Images are positioned after they have been rounded
+    let bg_pos = compute_background_position(
+        background,
+        bg_idx,
+        (container_w - bg_size.width) as f32,
+        (container_h - bg_size.height) as f32,
+    );
+
     (bg_pos, bg_size)
 }
 
@@ -677,8 +704,8 @@ fn compute_background_size(
     container_h: f32,
     bg_idx: usize,
     mode: BackgroundSizeComputeMode,
-    scale: f32,
 ) -> kurbo::Size {
+    use style::properties::generated::longhands::object_fit::computed_value::T as ObjectFit;
     use style::values::computed::{BackgroundSize, Length};
     use style::values::generics::length::GenericLengthPercentageOrAuto as Lpa;
 
@@ -716,38 +743,30 @@ fn compute_background_size(
                 }
                 (Lpa::Auto, Lpa::Auto) => match mode {
                     BackgroundSizeComputeMode::Auto => (container_w, container_h),
-                    BackgroundSizeComputeMode::Size(bg_w, bg_h) => (bg_w * scale, bg_h * scale),
+                    BackgroundSizeComputeMode::Size(bg_w, bg_h) => (bg_w, bg_h),
                 },
             }
         }
-        BackgroundSize::Cover => match mode {
-            BackgroundSizeComputeMode::Auto => (container_w, container_h),
-            BackgroundSizeComputeMode::Size(bg_w, bg_h) => {
-                let x_ratio = container_w / bg_w;
-                let y_ratio = container_h / bg_h;
-
-                let ratio = if x_ratio < 1.0 || y_ratio < 1.0 {
-                    x_ratio.min(y_ratio)
-                } else {
-                    x_ratio.max(y_ratio)
-                };
-
-                (bg_w * ratio, bg_h * ratio)
-            }
-        },
-        BackgroundSize::Contain => match mode {
+        // `cover` and `contain` scale the image like the matching `object-fit` values
+        BackgroundSize::Cover | BackgroundSize::Contain => match mode {
             BackgroundSizeComputeMode::Auto => (container_w, container_h),
             BackgroundSizeComputeMode::Size(bg_w, bg_h) => {
-                let x_ratio = container_w / bg_w;
-                let y_ratio = container_h / bg_h;
-
-                let ratio = if x_ratio < 1.0 || y_ratio < 1.0 {
-                    x_ratio.max(y_ratio)
-                } else {
-                    x_ratio.min(y_ratio)
+                let object_fit = match bg_size {
+                    BackgroundSize::Cover => ObjectFit::Cover,
+                    _ => ObjectFit::Contain,
                 };
-
-                (bg_w * ratio, bg_h * ratio)
+                let size = compute_object_fit(
+                    taffy::Size {
+                        width: container_w,
+                        height: container_h,
+                    },
+                    Some(taffy::Size {
+                        width: bg_w,
+                        height: bg_h,
+                    }),
+                    object_fit,
+                );
+                (size.width, size.height)
             }
         },
     };
@@ -780,6 +799,13 @@ fn get_cyclic<T>(values: &[T], layer_index: usize) -> &T {
     &values[layer_index % values.len()]
 }
 
+/// The start and length of a run of tiles of size `tile_size` which covers `start..end`, where one of
+/// the tiles starts at `tile_origin`
+fn tile_span(tile_origin: f64, tile_size: f64, start: f64, end: f64) -> (f64, f64) {
+    let first_tile = tile_origin - ((tile_origin - start) / tile_size).ceil() * tile_size;
+    (first_tile, end - first_tile)
+}
+
 fn extend(offset: f64, length: f64) -> f64 {
     let extend_length = offset % length;
     if extend_length > 0.0 {
@@ -788,3 +814,302 @@ fn extend(offset: f64, length: f64) -> f64 {
         -extend_length
     }
 }
+
+#[test]
+fn linear_gradient_runs_from_left_to_right() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, ()) = doc_with_body(100, 100, "body { margin: 0 }", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 100px; height: 100px; background-image: linear-gradient(90deg, red, blue)",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let buffer = render_pixels(&doc);
+
+    for y in [0, 50, 99] {
+        let left = pixel_at(&buffer, 100, 0, y);
+        let [r, _, b, a] = left;
+        assert!(r > 240 && b < 15 && a == 255, "{left:?}");
+        let right = pixel_at(&buffer, 100, 99, y);
+        let [r, _, b, a] = right;
+        assert!(b > 240 && r < 15 && a == 255, "{right:?}");
+    }
+
+    // The colors are blended in between
+    let middle = pixel_at(&buffer, 100, 50, 50);
+    let [r, _, b, _] = middle;
+    assert!(r > 64 && b > 64, "{middle:?}");
+}
+
+#[test]
+fn gradient_interpolates_in_specified_color_space() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    // The color in the middle of a 100px wide red to blue gradient interpolated in `color_space`
+    let midpoint = |color_space: &str| {
+        let (doc, ()) = doc_with_body(100, 10, "body { margin: 0 }", |mutr, body| {
+            let div = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!(
+                "width: 100px; height: 10px; background-image: linear-gradient(to right {color_space}, red, blue)"
+            );
+            mutr.set_attribute(div, qual_name!("style"), &style);
+            mutr.append_children(body, &[div]);
+        });
+        let [r, g, b, _] = pixel_at(&render_pixels(&doc), 100, 50, 5);
+        [r, g, b]
+    };
+
+    // Without a color space, gradients between legacy colors interpolate in sRGB, whose midpoint is
+    // a dark purple
+    let srgb = midpoint("in srgb");
+    assert_eq!(midpoint(""), srgb);
+    assert!(srgb.iter().all(|&c| c < 160), "{srgb:?}");
+
+    // Interpolating in Oklch keeps the lightness and chroma up, so the midpoint is a brighter magenta
+    let oklch = midpoint("in oklch");
+    let brightness = |[r, g, b]: [u8; 3]| r as u32 + g as u32 + b as u32;
+    assert!(
+        brightness(oklch) > brightness(srgb) + 60,
+        "{oklch:?} vs {srgb:?}"
+    );
+    assert!(oklch[0] > 160 && oklch[2] > 160, "{oklch:?}");
+}
+
+#[test]
+fn radial_gradient_is_sized_in_css_pixels() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_traits::shell::{ColorScheme, Viewport};
+
+    let (mut doc, ()) = doc_with_body(100, 200, "body { margin: 0 }", |mutr, body| {
+        let circle = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            circle,
+            qual_name!("style"),
+            "width: 50px; height: 50px; background-image: radial-gradient(circle 25px, red, blue)",
+        );
+        let degenerate = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            degenerate,
+            qual_name!("style"),
+            "width: 50px; height: 50px; background-image: radial-gradient(circle 0px, red, blue)",
+        );
+        mutr.append_children(body, &[circle, degenerate]);
+    });
+    doc.set_viewport(Viewport::new(100, 200, 2.0, ColorScheme::Light));
+    doc.resolve(0.0);
+
+    let buffer = render_pixels(&doc);
+
+    // The circle's 25px radius is 50 device pixels, so it reaches the edges of the box. 20 device
+    // pixels from the center the gradient is still mostly red, and it is blue past the radius.
+    let center = pixel_at(&buffer, 100, 50, 50);
+    let [r, _, b, _] = center;
+    assert!(r > 240 && b < 15, "{center:?}");
+    let inside = pixel_at(&buffer, 100, 70, 50);
+    let [r, _, b, _] = inside;
+    assert!(r > b, "{inside:?}");
+    let outside = pixel_at(&buffer, 100, 2, 2);
+    let [r, _, b, _] = outside;
+    assert!(b > 240 && r < 15, "{outside:?}");
+
+    // A gradient with a zero radius is painted with its last color
+    for (x, y) in [(50, 150), (2, 102), (97, 197)] {
+        assert_eq!(pixel_at(&buffer, 100, x, y), [0, 0, 255, 255]);
+    }
+}
+
+#[test]
+fn color_mix_background_is_blended() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, ()) = doc_with_body(20, 20, "body { margin: 0 }", |mutr, body| {
+        let mixed = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            mixed,
+            qual_name!("style"),
+            "height: 10px; background: color-mix(in oklch, red, blue 30%)",
+        );
+        // Mixes with `currentColor` can only be resolved once the element's `color` is known
+        let current = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            current,
+            qual_name!("style"),
+            "height: 10px; color: blue; background: color-mix(in srgb, currentColor, white)",
+        );
+        mutr.append_children(body, &[mixed, current]);
+    });
+    let buffer = render_pixels(&doc);
+    let assert_close = |actual: [u8; 4], expected: [u8; 4]| {
+        let close = actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 3);
+        assert!(close, "{actual:?} != {expected:?}");
+    };
+
+    // 70% red and 30% blue, mixed in Oklch (the hue takes the shorter path, through pink) and
+    // clipped to the sRGB gamut
+    assert_close(pixel_at(&buffer, 20, 10, 5), [225, 0, 138, 255]);
+    assert_close(pixel_at(&buffer, 20, 10, 15), [128, 128, 255, 255]);
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg_background_follows_current_color() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::net::Resource;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_dom::util::ImageType;
+    use blitz_traits::net::Bytes;
+
+    const ICON: &[u8] = br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+        <rect width="10" height="10" fill="currentColor"/>
+    </svg>"#;
+
+    let (mut doc, icons) = doc_with_body(10, 20, "body { margin: 0 }", |mutr, body| {
+        let icons = ["red", "blue"].map(|color| {
+            let icon = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!(
+                "width: 10px; height: 10px; color: {color}; \
+                 background-image: url(https://example.com/icon.svg)"
+            );
+            mutr.set_attribute(icon, qual_name!("style"), &style);
+            icon
+        });
+        mutr.append_children(body, &icons);
+        icons
+    });
+
+    // Both elements are painted with the same image
+    for icon in icons {
+        let tree = usvg::Tree::from_data(ICON, &usvg::Options::default()).unwrap();
+        doc.load_resource(Resource::Svg(
+            icon,
+            ImageType::Background(0),
+            Box::new(tree),
+            Some(Bytes::from_static(ICON)),
+        ));
+    }
+    doc.resolve(0.0);
+
+    let buffer = render_pixels(&doc);
+    assert_eq!(pixel_at(&buffer, 10, 5, 5), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&buffer, 10, 5, 15), [0, 0, 255, 255]);
+}
+
+#[test]
+fn repeat_x_tiles_only_horizontally() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::net::Resource;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_dom::util::ImageType;
+    use std::sync::Arc;
+
+    let (mut doc, div) = doc_with_body(40, 30, "body { margin: 0 }", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 40px; height: 30px; background-image: url(https://example.com/tile.png); \
+             background-repeat: repeat-x; background-position: 5px 10px",
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+
+    // A solid red 10x10 image
+    let red = [255, 0, 0, 255].repeat(10 * 10);
+    doc.load_resource(Resource::Image(
+        div,
+        ImageType::Background(0),
+        10,
+        10,
+        Arc::new(red),
+    ));
+    doc.resolve(0.0);
+
+    let buffer = render_pixels(&doc);
+    let is_red = |x: usize, y: usize| pixel_at(&buffer, 40, x, y) == [255, 0, 0, 255];
+
+    // The row of tiles at `y = 10` spans the whole width of the box (including before the first
+    // tile at `x = 5`), but isn't repeated above or below it
+    for x in [0, 4, 5, 20, 39] {
+        assert!(is_red(x, 10) && is_red(x, 19), "x = {x}");
+        assert!(!is_red(x, 9) && !is_red(x, 20), "x = {x}");
+    }
+}
+
+#[test]
+fn background_size_scales_images() {
+    use super::render_pixels;
+    use blitz_dom::net::Resource;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_dom::util::ImageType;
+    use std::sync::Arc;
+
+    // Paint a solid red 10x20 image into a 40x30 box, returning which pixels are red
+    let render = |background_size: &str| {
+        let (mut doc, div) = doc_with_body(40, 30, "body { margin: 0 }", |mutr, body| {
+            let div = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!(
+                "width: 40px; height: 30px; background-image: url(https://example.com/tall.png); \
+                 background-repeat: no-repeat; background-position: center; \
+                 background-size: {background_size}"
+            );
+            mutr.set_attribute(div, qual_name!("style"), &style);
+            mutr.append_children(body, &[div]);
+            div
+        });
+
+        let red = [255, 0, 0, 255].repeat(10 * 20);
+        doc.load_resource(Resource::Image(
+            div,
+            ImageType::Background(0),
+            10,
+            20,
+            Arc::new(red),
+        ));
+        doc.resolve(0.0);
+
+        render_pixels(&doc)
+            .chunks(4)
+            .map(|pixel| pixel == [255, 0, 0, 255])
+            .collect::<Vec<bool>>()
+    };
+    let is_red = |pixels: &[bool], x: usize, y: usize| pixels[y * 40 + x];
+
+    // `cover` scales the image up to 40x80, overflowing (and so covering) the box vertically
+    let cover = render("cover");
+    assert!(cover.iter().all(|&red| red));
+
+    // `contain` scales it to 15x30, centered horizontally
+    let contain = render("contain");
+    for y in [0, 15, 29] {
+        assert!(is_red(&contain, 14, y) && is_red(&contain, 25, y));
+        assert!(!is_red(&contain, 11, y) && !is_red(&contain, 28, y));
+    }
+
+    // An `auto` height follows the image's aspect ratio (so this matches `cover`)
+    assert_eq!(render("100% auto"), cover);
+
+    // Explicit sizes are positioned too
+    let explicit = render("50% 10px");
+    assert!(is_red(&explicit, 10, 10) && is_red(&explicit, 29, 19));
+    assert!(!is_red(&explicit, 9, 10) && !is_red(&explicit, 10, 9));
+    assert!(!is_red(&explicit, 30, 19) && !is_red(&explicit, 29, 20));
+
+    // Images which are sized to nothing aren't drawn
+    assert!(render("0px auto").iter().all(|&red| !red));
+}
@@ -4,113 +4,319 @@ use crate::{
     layers::maybe_with_layer,
 };
 use anyrender::PaintScene;
+use blitz_dom::kurbo_css::CssBox;
 use kurbo::{Rect, Vec2};
+use peniko::{BlendMode, Compose, Fill, Mix};
+use style::color::AbsoluteColor;
+use style::values::computed::BoxShadow;
+
+/// A single `box-shadow` resolved to the (scaled) geometry that it is painted with
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ShadowLayer {
+    pub(super) color: Color,
+    pub(super) offset: Vec2,
+    /// For outset shadows the box that casts the shadow. For inset shadows the hole in the middle
+    /// of the shadow. In both cases before the shadow's offset is applied.
+    pub(super) rect: Rect,
+    pub(super) radius: f64,
+    /// The standard deviation of the shadow's Gaussian blur. CSS blur radii are twice the standard
+    /// deviation (<https://drafts.csswg.org/css-backgrounds/#shadow-blur>).
+    pub(super) std_dev: f64,
+}
 
 impl ElementCx<'_> {
     pub(super) fn draw_outset_box_shadow(&self, scene: &mut impl PaintScene) {
         let box_shadow = &self.style.get_effects().box_shadow.0;
-
-        // TODO: Only apply clip if element has transparency
-        let has_outset_shadow = box_shadow.iter().any(|s| !s.inset);
-        if !has_outset_shadow {
+        let current_color = self.style.clone_color();
+        let layers = shadow_layers(box_shadow, false, &self.frame, &current_color, self.scale);
+        if layers.is_empty() {
             return;
         }
 
-        let current_color = self.style.clone_color();
-        let max_shadow_rect = box_shadow.iter().fold(Rect::ZERO, |prev, shadow| {
-            let x = shadow.base.horizontal.px() as f64 * self.scale;
-            let y = shadow.base.vertical.px() as f64 * self.scale;
-            let blur = shadow.base.blur.px() as f64 * self.scale;
-            let spread = shadow.spread.px() as f64 * self.scale;
-            let offset = spread + blur * 2.5;
-
-            let rect = self.frame.border_box.inflate(offset, offset) + Vec2::new(x, y);
-
-            prev.union(rect)
+        // TODO: Only apply clip if element has transparency
+        let max_shadow_rect = layers.iter().fold(Rect::ZERO, |prev, layer| {
+            // The blur fades out to (almost) nothing within three standard deviations
+            let blur = layer.std_dev * 3.0;
+            prev.union(layer.rect.inflate(blur, blur) + layer.offset)
         });
 
         maybe_with_layer(
             scene,
-            has_outset_shadow,
+            true,
             1.0,
             self.transform,
             &self.frame.shadow_clip(max_shadow_rect),
             |scene| {
-                for shadow in box_shadow.iter().filter(|s| !s.inset).rev() {
-                    let shadow_color = shadow
-                        .base
-                        .color
-                        .resolve_to_absolute(&current_color)
-                        .as_srgb_color();
-
-                    let alpha = shadow_color.components[3];
-                    if alpha != 0.0 {
-                        let transform = self.transform.then_translate(Vec2 {
-                            x: shadow.base.horizontal.px() as f64 * self.scale,
-                            y: shadow.base.vertical.px() as f64 * self.scale,
-                        });
-
-                        // TODO draw shadows with matching individual radii instead of averaging
-                        let radius = self.frame.border_radii.average();
-
-                        let spread = shadow.spread.px() as f64 * self.scale;
-                        let rect = self.frame.border_box.inflate(spread, spread);
-
-                        // Fill the color
-                        scene.draw_box_shadow(
-                            transform,
-                            rect,
-                            shadow_color,
-                            radius,
-                            shadow.base.blur.px() as f64,
-                        );
-                    }
+                for layer in &layers {
+                    scene.draw_box_shadow(
+                        self.transform.then_translate(layer.offset),
+                        layer.rect,
+                        layer.color,
+                        layer.radius,
+                        layer.std_dev,
+                    );
                 }
             },
         )
     }
 
     pub(super) fn draw_inset_box_shadow(&self, scene: &mut impl PaintScene) {
-        let current_color = self.style.clone_color();
         let box_shadow = &self.style.get_effects().box_shadow.0;
-        let has_inset_shadow = box_shadow.iter().any(|s| s.inset);
-        if !has_inset_shadow {
+        let current_color = self.style.clone_color();
+        let layers = shadow_layers(box_shadow, true, &self.frame, &current_color, self.scale);
+        if layers.is_empty() {
             return;
         }
 
-        maybe_with_layer(
-            scene,
-            has_inset_shadow,
-            1.0,
-            self.transform,
-            &self.frame.padding_box_path(),
-            |scene| {
-                for shadow in box_shadow.iter().filter(|s| s.inset) {
-                    let shadow_color = shadow
-                        .base
-                        .color
-                        .resolve_to_absolute(&current_color)
-                        .as_srgb_color();
-                    if shadow_color != Color::TRANSPARENT {
-                        let transform = self.transform.then_translate(Vec2 {
-                            x: shadow.base.horizontal.px() as f64,
-                            y: shadow.base.vertical.px() as f64,
-                        });
-
-                        //TODO draw shadows with matching individual radii instead of averaging
-                        let radius = self.frame.border_radii.average();
-
-                        // Fill the color
-                        scene.draw_box_shadow(
-                            transform,
-                            self.frame.border_box,
-                            shadow_color,
-                            radius,
-                            shadow.base.blur.px() as f64 * self.scale,
-                        );
-                    }
-                }
-            },
-        );
+        let padding_box = self.frame.padding_box_path();
+        maybe_with_layer(scene, true, 1.0, self.transform, &padding_box, |scene| {
+            for layer in &layers {
+                // Fill the padding box with the shadow color and then cut the (offset and blurred) hole
+                // out of it. Each shadow is painted in its own layer so that cutting the hole doesn't
+                // also erase the background or the shadows beneath it.
+                scene.push_layer(Mix::Normal, 1.0, self.transform, &padding_box);
+                scene.fill(
+                    Fill::NonZero,
+                    self.transform,
+                    layer.color,
+                    None,
+                    &padding_box,
+                );
+                scene.push_layer(
+                    BlendMode::new(Mix::Normal, Compose::DestOut),
+                    1.0,
+                    self.transform,
+                    &padding_box,
+                );
+                scene.draw_box_shadow(
+                    self.transform.then_translate(layer.offset),
+                    layer.rect,
+                    Color::BLACK,
+                    layer.radius,
+                    layer.std_dev,
+                );
+                scene.pop_layer();
+                scene.pop_layer();
+            }
+        });
     }
 }
+
+/// Resolve either the inset or the outset shadows of a `box-shadow` list to the geometry that they
+/// are painted with, in painting (back-to-front) order.
+///
+/// The first shadow in the list is painted on top, so the list is painted in reverse. Shadows that
+/// are fully transparent are skipped.
+pub(super) fn shadow_layers(
+    box_shadow: &[BoxShadow],
+    inset: bool,
+    frame: &CssBox,
+    current_color: &AbsoluteColor,
+    scale: f64,
+) -> Vec<ShadowLayer> {
+    box_shadow
+        .iter()
+        .rev()
+        .filter(|shadow| shadow.inset == inset)
+        .filter_map(|shadow| {
+            let color = shadow
+                .base
+                .color
+                .resolve_to_absolute(current_color)
+                .as_srgb_color();
+            if color.components[3] == 0.0 {
+                return None;
+            }
+
+            let spread = shadow.spread.px() as f64 * scale;
+            let rect = if inset {
+                // The hole shrinks as the spread grows (and can't shrink past nothing)
+                let rect = frame.padding_box.inflate(-spread, -spread);
+                Rect::from_center_size(
+                    rect.center(),
+                    (rect.width().max(0.0), rect.height().max(0.0)),
+                )
+            } else {
+                frame.border_box.inflate(spread, spread)
+            };
+
+            Some(ShadowLayer {
+                color,
+                offset: Vec2 {
+                    x: shadow.base.horizontal.px() as f64 * scale,
+                    y: shadow.base.vertical.px() as f64 * scale,
+                },
+                rect,
+                // TODO draw shadows with matching individual radii instead of averaging
+                radius: frame.border_radii.average(),
+                std_dev: shadow.base.blur.px() as f64 * scale / 2.0,
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn mixed_shadows_paint_in_list_order() {
+    use blitz_dom::kurbo_css::create_css_rect;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, div) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "width: 100px; height: 100px; box-shadow: 0 0 0 10px red, inset 0 0 0 5px lime, \
+             0 0 0 20px blue, inset 0 0 0 15px yellow",
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+
+    let node = doc.get_node(div).unwrap();
+    let styles = node.primary_styles().unwrap();
+    let frame = create_css_rect(&styles, &node.final_layout, 2.0);
+    let box_shadow = &styles.get_effects().box_shadow.0;
+    let current_color = styles.clone_color();
+    let red = Color::from_rgb8(255, 0, 0);
+    let lime = Color::from_rgb8(0, 255, 0);
+    let blue = Color::from_rgb8(0, 0, 255);
+    let yellow = Color::from_rgb8(255, 255, 0);
+
+    // The first outset shadow in the list (red) is painted last so that it is on top of the larger blue one
+    let outset = shadow_layers(box_shadow, false, &frame, &current_color, 2.0);
+    assert_eq!(
+        outset.iter().map(|l| l.color).collect::<Vec<_>>(),
+        [blue, red]
+    );
+    assert_eq!(outset[0].rect, frame.border_box.inflate(40.0, 40.0));
+    assert_eq!(outset[1].rect, frame.border_box.inflate(20.0, 20.0));
+
+    // Likewise the first inset shadow (lime) is painted on top of the wider yellow one
+    let inset = shadow_layers(box_shadow, true, &frame, &current_color, 2.0);
+    assert_eq!(
+        inset.iter().map(|l| l.color).collect::<Vec<_>>(),
+        [yellow, lime]
+    );
+    assert_eq!(inset[0].rect, frame.padding_box.inflate(-30.0, -30.0));
+    assert_eq!(inset[1].rect, frame.padding_box.inflate(-10.0, -10.0));
+}
+
+#[test]
+fn current_color_shadow_and_outline_follow_color() {
+    use super::record_scene;
+    use blitz_dom::kurbo_css::create_css_rect;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_dom::{BaseDocument, qual_name};
+
+    let css = "width: 100px; height: 100px; outline: 2px solid currentColor; \
+               box-shadow: 0 0 5px currentColor, inset 0 0 5px currentColor";
+    let (mut doc, div) = doc_with_body(800, 600, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            &format!("{css}; color: rgb(255, 0, 0)"),
+        );
+        mutr.append_children(body, &[div]);
+        div
+    });
+
+    let layer_colors = |doc: &BaseDocument, inset: bool| {
+        let node = doc.get_node(div).unwrap();
+        let styles = node.primary_styles().unwrap();
+        let frame = create_css_rect(&styles, &node.final_layout, 1.0);
+        let current_color = styles.clone_color();
+        let box_shadow = &styles.get_effects().box_shadow.0;
+        shadow_layers(box_shadow, inset, &frame, &current_color, 1.0)
+            .iter()
+            .map(|layer| layer.color)
+            .collect::<Vec<_>>()
+    };
+    let outline_colors = |doc: &BaseDocument| record_scene(doc).fill_colors;
+
+    let red = Color::from_rgb8(255, 0, 0);
+    let blue = Color::from_rgb8(0, 0, 255);
+    assert_eq!(layer_colors(&doc, false), [red]);
+    assert_eq!(layer_colors(&doc, true), [red]);
+    assert!(outline_colors(&doc).contains(&Some(red)));
+
+    // Changing `color` changes the color of the shadows and the outline
+    doc.mutate().set_attribute(
+        div,
+        qual_name!("style"),
+        &format!("{css}; color: rgb(0, 0, 255)"),
+    );
+    doc.resolve(0.0);
+    assert_eq!(layer_colors(&doc, false), [blue]);
+    assert_eq!(layer_colors(&doc, true), [blue]);
+    let colors = outline_colors(&doc);
+    assert!(colors.contains(&Some(blue)));
+    assert!(!colors.contains(&Some(red)));
+}
+
+#[test]
+fn blurred_shadow_fades_out_gradually() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let css = "html { background: white } body { margin: 0 }";
+    let (doc, ()) = doc_with_body(200, 200, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "margin: 50px; width: 100px; height: 100px; background: white; box-shadow: 0 0 20px black",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let buffer = render_pixels(&doc);
+
+    // Walk right from the box's right edge (at x = 150) along its middle row. The shadow is painted
+    // over the root's white background, so it gets lighter (or stays the same) with every step
+    // outwards.
+    let red_at = |x: usize| pixel_at(&buffer, 200, x, 100)[0];
+    let shades: Vec<u8> = (150..200).map(red_at).collect();
+    assert!(
+        shades.windows(2).all(|pair| pair[0] <= pair[1]),
+        "{shades:?}"
+    );
+
+    // The falloff is soft: there are several distinct shades of grey rather than a hard edge
+    let mut distinct = shades.clone();
+    distinct.dedup();
+    assert!(shades[0] < 255);
+    assert_eq!(*shades.last().unwrap(), 255);
+    assert!(distinct.len() > 5, "{shades:?}");
+}
+
+#[test]
+fn multiple_shadows_are_all_painted() {
+    use super::{pixel_at, render_pixels};
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let css = "body { margin: 0; background: white }";
+    let (doc, ()) = doc_with_body(200, 200, css, |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "margin: 50px; width: 100px; height: 100px; box-shadow: 0 0 5px red, 0 0 15px blue",
+        );
+        mutr.append_children(body, &[div]);
+    });
+    let buffer = render_pixels(&doc);
+
+    // Just outside the box's right edge (at x = 150) the red shadow is painted on top of the blue one
+    let inner = pixel_at(&buffer, 200, 151, 100);
+    let [r, _, b, _] = inner;
+    assert!(r > b, "{inner:?}");
+
+    // Beyond the reach of the small red shadow, the larger blue shadow is still visible (it isn't
+    // clipped to the extent of the red one)
+    let outer = pixel_at(&buffer, 200, 160, 100);
+    let [r, _, b, _] = outer;
+    assert!(b > r && r < 255, "{outer:?}");
+}
@@ -2,6 +2,8 @@ use super::ElementCx;
 use crate::color::{Color, ToColorColor as _};
 use anyrender::PaintScene;
 use blitz_dom::local_name;
+use blitz_dom::node::RESIZE_GRIP_SIZE;
+use blitz_traits::shell::ColorScheme;
 use kurbo::{Affine, BezPath, Cap, Circle, Join, Point, RoundedRect, Stroke, Vec2};
 use peniko::Fill;
 use style::dom::TElement as _;
@@ -24,6 +26,7 @@ impl ElementCx<'_> {
         } else {
             self.style.clone_color().as_srgb_color()
         };
+        let palette = self.control_palette();
 
         let width = self.frame.border_box.width();
         let height = self.frame.border_box.height();
@@ -34,23 +37,95 @@ impl ElementCx<'_> {
 
         match type_attr {
             Some("checkbox") => {
-                draw_checkbox(scene, checked, frame, self.transform, accent_color, scale);
+                draw_checkbox(
+                    scene,
+                    checked,
+                    frame,
+                    self.transform,
+                    accent_color,
+                    palette,
+                    scale,
+                );
             }
             Some("radio") => {
                 let center = frame.center();
-                draw_radio_button(scene, checked, center, self.transform, accent_color, scale);
+                let transform = self.transform;
+                draw_radio_button(
+                    scene,
+                    checked,
+                    center,
+                    transform,
+                    accent_color,
+                    palette,
+                    scale,
+                );
             }
             _ => {}
         }
     }
+
+    /// Draw the grip in the bottom-right corner of resizable elements (e.g. textareas)
+    pub(super) fn draw_resize_grip(&self, scene: &mut impl PaintScene) {
+        if !self.node.is_resizable() {
+            return;
+        }
+
+        let size = RESIZE_GRIP_SIZE as f64 * self.scale;
+        let corner = self.frame.padding_box.origin()
+            + Vec2::new(
+                self.frame.padding_box.width(),
+                self.frame.padding_box.height(),
+            );
+
+        // Two diagonal lines, like the grips drawn by browsers
+        let mut path = BezPath::new();
+        for inset in [0.25, 0.6] {
+            path.move_to(corner - Vec2::new(size * (1.0 - inset), 0.0));
+            path.line_to(corner - Vec2::new(0.0, size * (1.0 - inset)));
+        }
+
+        let stroke = Stroke::new(self.scale).with_caps(Cap::Round);
+        scene.stroke(&stroke, self.transform, RESIZE_GRIP_COLOR, None, &path);
+    }
+
+    /// The default colors of native form control widgets for the element's used `color-scheme`
+    pub(super) fn control_palette(&self) -> ControlPalette {
+        let preferred = self.context.dom.viewport().color_scheme;
+        match self.node.used_color_scheme(preferred) {
+            ColorScheme::Light => LIGHT_CONTROL_PALETTE,
+            ColorScheme::Dark => DARK_CONTROL_PALETTE,
+        }
+    }
+}
+
+/// The UA colors that native form control widgets are drawn with (other than the accent color)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ControlPalette {
+    /// The fill of the control's face (and of the checkmark drawn over the accent color)
+    pub(super) background: Color,
+    /// The outline of unchecked controls which aren't outlined with the accent color
+    pub(super) border: Color,
 }
 
+pub(super) const LIGHT_CONTROL_PALETTE: ControlPalette = ControlPalette {
+    background: Color::WHITE,
+    border: color::palette::css::GRAY,
+};
+
+pub(super) const DARK_CONTROL_PALETTE: ControlPalette = ControlPalette {
+    background: Color::from_rgba8(59, 59, 59, 255),
+    border: Color::from_rgba8(133, 133, 133, 255),
+};
+
+const RESIZE_GRIP_COLOR: Color = Color::from_rgba8(128, 128, 128, 255);
+
 fn draw_checkbox(
     scene: &mut impl PaintScene,
     checked: bool,
     frame: RoundedRect,
     transform: Affine,
     accent_color: Color,
+    palette: ControlPalette,
     scale: f64,
 ) {
     if checked {
@@ -73,9 +148,9 @@ fn draw_checkbox(
             dash_offset: 0.0,
         };
 
-        scene.stroke(&style, transform, Color::WHITE, None, &path);
+        scene.stroke(&style, transform, palette.background, None, &path);
     } else {
-        scene.fill(Fill::NonZero, transform, Color::WHITE, None, &frame);
+        scene.fill(Fill::NonZero, transform, palette.background, None, &frame);
         scene.stroke(&Stroke::default(), transform, accent_color, None, &frame);
     }
 }
@@ -86,6 +161,7 @@ fn draw_radio_button(
     center: Point,
     transform: Affine,
     accent_color: Color,
+    palette: ControlPalette,
     scale: f64,
 ) {
     let outer_ring = Circle::new(center, 8.0 * scale);
@@ -93,11 +169,10 @@ fn draw_radio_button(
     let inner_circle = Circle::new(center, 4.0 * scale);
     if checked {
         scene.fill(Fill::NonZero, transform, accent_color, None, &outer_ring);
-        scene.fill(Fill::NonZero, transform, Color::WHITE, None, &gap);
+        scene.fill(Fill::NonZero, transform, palette.background, None, &gap);
         scene.fill(Fill::NonZero, transform, accent_color, None, &inner_circle);
     } else {
-        const GRAY: Color = color::palette::css::GRAY;
-        scene.fill(Fill::NonZero, transform, GRAY, None, &outer_ring);
-        scene.fill(Fill::NonZero, transform, Color::WHITE, None, &gap);
+        scene.fill(Fill::NonZero, transform, palette.border, None, &outer_ring);
+        scene.fill(Fill::NonZero, transform, palette.background, None, &gap);
     }
 }
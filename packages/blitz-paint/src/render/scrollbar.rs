@@ -0,0 +1,196 @@
+use super::ElementCx;
+use crate::color::Color;
+use anyrender::PaintScene;
+use blitz_dom::Node;
+use kurbo::{Rect, RoundedRect};
+use peniko::Fill;
+use style::values::computed::Overflow;
+
+/// The thickness of scrollbars (in CSS pixels). No space is reserved for scrollbars in layout, so
+/// they are drawn over the inside edges of the padding box.
+const SCROLLBAR_SIZE: f64 = 8.0;
+/// The minimum length of a scrollbar's thumb (in CSS pixels), so that it stays easy to grab
+const MIN_THUMB_LENGTH: f64 = 16.0;
+
+const SCROLLBAR_TRACK_COLOR: Color = Color::from_rgba8(0, 0, 0, 20);
+const SCROLLBAR_THUMB_COLOR: Color = Color::from_rgba8(128, 128, 128, 200);
+
+/// Whether the element shows a scrollbar along each axis (as `(horizontal, vertical)`).
+/// `overflow: scroll` always shows a scrollbar, whereas `overflow: auto` only shows one when the
+/// content overflows the padding box along that axis.
+fn scrollbar_axes(node: &Node) -> (bool, bool) {
+    let Some(styles) = node.primary_styles() else {
+        return (false, false);
+    };
+    let box_styles = styles.get_box();
+    let layout = &node.final_layout;
+    let shows_scrollbar = |overflow: Overflow, scroll_extent: f32| match overflow {
+        Overflow::Scroll => true,
+        Overflow::Auto => scroll_extent > 0.0,
+        _ => false,
+    };
+    (
+        shows_scrollbar(box_styles.overflow_x, layout.scroll_width()),
+        shows_scrollbar(box_styles.overflow_y, layout.scroll_height()),
+    )
+}
+
+/// The start and length of a scrollbar's thumb within a track. The thumb's length is the proportion
+/// of the content which is visible in the `viewport`, and its position follows the `scroll` offset.
+fn thumb_extent(
+    track_start: f64,
+    track_length: f64,
+    viewport: f64,
+    max_scroll: f64,
+    scroll: f64,
+    min_length: f64,
+) -> (f64, f64) {
+    let length = (track_length * viewport / (viewport + max_scroll))
+        .max(min_length)
+        .min(track_length);
+    let progress = match max_scroll > 0.0 {
+        true => (scroll / max_scroll).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+    (track_start + (track_length - length) * progress, length)
+}
+
+impl ElementCx<'_> {
+    /// Draw the scrollbars of scroll containers. They aren't scrolled along with the contents.
+    pub(super) fn draw_scrollbars(&self, scene: &mut impl PaintScene) {
+        let (horizontal, vertical) = scrollbar_axes(self.node);
+        if !horizontal && !vertical {
+            return;
+        }
+
+        let layout = &self.node.final_layout;
+        let scroll = self.node.scroll_offset;
+        let padding_box = self.frame.padding_box;
+        let size = SCROLLBAR_SIZE * self.scale;
+        let min_length = MIN_THUMB_LENGTH * self.scale;
+
+        // When both scrollbars are shown, each stops short of the corner where they would overlap
+        let corner_x = if vertical { size } else { 0.0 };
+        let corner_y = if horizontal { size } else { 0.0 };
+
+        let mut draw_scrollbar = |track: Rect, thumb: Rect| {
+            scene.fill(
+                Fill::NonZero,
+                self.transform,
+                SCROLLBAR_TRACK_COLOR,
+                None,
+                &track,
+            );
+            // The thumb is inset within its track
+            let thumb = RoundedRect::from_rect(thumb.inset(-self.scale), size / 2.0);
+            scene.fill(
+                Fill::NonZero,
+                self.transform,
+                SCROLLBAR_THUMB_COLOR,
+                None,
+                &thumb,
+            );
+        };
+
+        if vertical {
+            let track = Rect::new(
+                padding_box.x1 - size,
+                padding_box.y0,
+                padding_box.x1,
+                padding_box.y1 - corner_y,
+            );
+            let viewport = (layout.size.height - layout.border.top - layout.border.bottom) as f64;
+            let (start, length) = thumb_extent(
+                track.y0,
+                track.height(),
+                viewport,
+                layout.scroll_height() as f64,
+                scroll.y,
+                min_length,
+            );
+            draw_scrollbar(track, Rect::new(track.x0, start, track.x1, start + length));
+        }
+
+        if horizontal {
+            let track = Rect::new(
+                padding_box.x0,
+                padding_box.y1 - size,
+                padding_box.x1 - corner_x,
+                padding_box.y1,
+            );
+            let viewport = (layout.size.width - layout.border.left - layout.border.right) as f64;
+            let (start, length) = thumb_extent(
+                track.x0,
+                track.width(),
+                viewport,
+                layout.scroll_width() as f64,
+                scroll.x,
+                min_length,
+            );
+            draw_scrollbar(track, Rect::new(start, track.y0, start + length, track.y1));
+        }
+    }
+}
+
+#[test]
+fn overflow_auto_only_shows_scrollbars_when_overflowing() {
+    use super::record_scene;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, containers) = doc_with_body(800, 600, "", |mutr, body| {
+        let containers = [
+            ("auto", "50px"),
+            ("scroll", "50px"),
+            ("auto", "200px"),
+            ("hidden", "200px"),
+        ]
+        .map(|(overflow, content_height)| {
+            let container = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!("width: 100px; height: 100px; overflow: {overflow}");
+            mutr.set_attribute(container, qual_name!("style"), &style);
+            let content = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!("width: 50px; height: {content_height}");
+            mutr.set_attribute(content, qual_name!("style"), &style);
+            mutr.append_children(container, &[content]);
+            container
+        });
+        mutr.append_children(body, &containers);
+        containers
+    });
+
+    let axes = containers.map(|id| scrollbar_axes(doc.get_node(id).unwrap()));
+    assert_eq!(
+        axes,
+        [(false, false), (true, true), (false, true), (false, false)]
+    );
+
+    // Only the containers which show a scrollbar paint a thumb
+    let thumbs = record_scene(&doc)
+        .fill_colors
+        .iter()
+        .filter(|color| **color == Some(SCROLLBAR_THUMB_COLOR))
+        .count();
+    assert_eq!(thumbs, 3);
+}
+
+#[test]
+fn overflow_scroll_always_shows_scrollbars() {
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+
+    let (doc, containers) = doc_with_body(800, 600, "", |mutr, body| {
+        let containers = ["scroll", "scroll hidden", "hidden scroll"].map(|overflow| {
+            // Empty containers have nothing to scroll
+            let container = mutr.create_element(qual_name!("div", html), vec![]);
+            let style = format!("width: 100px; height: 100px; overflow: {overflow}");
+            mutr.set_attribute(container, qual_name!("style"), &style);
+            container
+        });
+        mutr.append_children(body, &containers);
+        containers
+    });
+
+    let axes = containers.map(|id| scrollbar_axes(doc.get_node(id).unwrap()));
+    assert_eq!(axes, [(true, true), (true, false), (false, true)]);
+}
@@ -0,0 +1,264 @@
+//! A [`PaintScene`] decorator which counts the drawing commands that pass through it
+
+use crate::timing::{PaintPhase, current_phase};
+use anyrender::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene};
+use kurbo::{Affine, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+
+/// The tolerance used when flattening shapes to count their path segments
+const TOLERANCE: f64 = 0.1;
+
+/// Counts of the drawing commands issued while painting a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaintStats {
+    /// Number of shapes filled (not including images)
+    pub fills: usize,
+    /// Number of shapes stroked
+    pub strokes: usize,
+    /// Number of images drawn
+    pub images: usize,
+    /// Number of glyph runs drawn
+    pub glyph_runs: usize,
+    /// Number of individual glyphs drawn
+    pub glyphs: usize,
+    /// Number of box shadows drawn
+    pub box_shadows: usize,
+    /// Number of layers pushed
+    pub layers: usize,
+    /// Total number of path segments in all filled, stroked, and clip shapes
+    pub path_segments: usize,
+    /// The counts for each phase of painting, indexed by [`PaintPhase`]
+    phases: [PhaseStats; 5],
+}
+
+/// Counts of the drawing commands issued during one [`PaintPhase`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStats {
+    pub fills: usize,
+    pub strokes: usize,
+    pub images: usize,
+    pub glyph_runs: usize,
+    pub box_shadows: usize,
+    pub layers: usize,
+}
+
+impl PaintStats {
+    /// The drawing commands issued during `phase`. Commands issued outside of any phase (such as the
+    /// layers pushed for opacity and overflow clips) are only counted in the totals.
+    ///
+    /// Phases are only tracked when painting with
+    /// [`paint_scene_with_stats`](crate::paint_scene_with_stats) or
+    /// [`paint_scene_with_timings`](crate::paint_scene_with_timings), so a [`StatsScene`] passed to
+    /// [`paint_scene`](crate::paint_scene) only collects the totals.
+    pub fn get(&self, phase: PaintPhase) -> PhaseStats {
+        self.phases[phase as usize]
+    }
+
+    /// The drawing commands issued during each phase
+    pub fn phases(&self) -> [(PaintPhase, PhaseStats); 5] {
+        PaintPhase::ALL.map(|phase| (phase, self.get(phase)))
+    }
+
+    /// The counts for the phase of painting that is currently running (if any)
+    fn current_phase_mut(&mut self) -> Option<&mut PhaseStats> {
+        current_phase().map(|phase| &mut self.phases[phase as usize])
+    }
+}
+
+/// Wraps any [`PaintScene`], forwarding every command to it while collecting [`PaintStats`]
+pub struct StatsScene<'s, S: PaintScene> {
+    inner: &'s mut S,
+    stats: PaintStats,
+}
+
+impl<'s, S: PaintScene> StatsScene<'s, S> {
+    pub fn new(inner: &'s mut S) -> Self {
+        Self {
+            inner,
+            stats: PaintStats::default(),
+        }
+    }
+
+    /// The statistics collected since this scene was created (or last reset)
+    pub fn stats(&self) -> PaintStats {
+        self.stats
+    }
+
+    fn count_segments(&mut self, shape: &impl Shape) {
+        self.stats.path_segments += shape.path_elements(TOLERANCE).count();
+    }
+}
+
+impl<S: PaintScene> PaintScene for StatsScene<'_, S> {
+    fn reset(&mut self) {
+        self.stats = PaintStats::default();
+        self.inner.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.stats.layers += 1;
+        if let Some(phase) = self.stats.current_phase_mut() {
+            phase.layers += 1;
+        }
+        self.count_segments(clip);
+        self.inner.push_layer(blend, alpha, transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.inner.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.stats.strokes += 1;
+        if let Some(phase) = self.stats.current_phase_mut() {
+            phase.strokes += 1;
+        }
+        self.count_segments(shape);
+        self.inner
+            .stroke(style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        // Images are drawn by filling their bounds with an image brush
+        let brush = brush.into();
+        let is_image = matches!(brush, Paint::Image(_));
+        match is_image {
+            true => self.stats.images += 1,
+            false => self.stats.fills += 1,
+        }
+        if let Some(phase) = self.stats.current_phase_mut() {
+            match is_image {
+                true => phase.images += 1,
+                false => phase.fills += 1,
+            }
+        }
+        self.count_segments(shape);
+        self.inner
+            .fill(style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs<'a, 's2: 'a>(
+        &'s2 mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        self.stats.glyph_runs += 1;
+        if let Some(phase) = self.stats.current_phase_mut() {
+            phase.glyph_runs += 1;
+        }
+        let glyph_count = &mut self.stats.glyphs;
+        self.inner.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs.inspect(|_| *glyph_count += 1),
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.stats.box_shadows += 1;
+        if let Some(phase) = self.stats.current_phase_mut() {
+            phase.box_shadows += 1;
+        }
+        self.inner
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+}
+
+#[test]
+fn stats_are_broken_down_by_phase() {
+    use anyrender_vello_cpu::VelloCpuImageRenderer;
+    use blitz_dom::net::Resource;
+    use blitz_dom::qual_name;
+    use blitz_dom::test_utils::doc_with_body;
+    use blitz_dom::util::ImageType;
+    use std::sync::Arc;
+
+    let (mut doc, img) = doc_with_body(200, 200, "", |mutr, body| {
+        let div = mutr.create_element(qual_name!("div", html), vec![]);
+        mutr.set_attribute(
+            div,
+            qual_name!("style"),
+            "background: red; border: 2px solid blue",
+        );
+        let text = mutr.create_text_node("Hi");
+        mutr.append_children(div, &[text]);
+        let img = mutr.create_element(qual_name!("img", html), vec![]);
+        mutr.append_children(body, &[div, img]);
+        img
+    });
+    doc.load_resource(Resource::Image(
+        img,
+        ImageType::Image,
+        2,
+        2,
+        Arc::new(vec![255; 16]),
+    ));
+    doc.resolve(0.0);
+
+    let mut stats = PaintStats::default();
+    anyrender::render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| stats = crate::paint_scene_with_stats(scene, &doc, 1.0, 200, 200),
+        200,
+        200,
+    );
+
+    // The image is counted separately from other fills
+    assert_eq!(stats.images, 1);
+    let content = stats.get(PaintPhase::Content);
+    assert_eq!((content.images, content.fills), (1, 0));
+
+    // The text is a single glyph run
+    assert_eq!(stats.glyph_runs, 1);
+    let text = stats.get(PaintPhase::Text);
+    assert_eq!((text.glyph_runs, text.fills), (1, 0));
+
+    // The background and border are filled in the background phase
+    let background = stats.get(PaintPhase::Background);
+    assert!(background.fills >= 2);
+    assert_eq!(background.glyph_runs, 0);
+
+    let phase_fills: usize = stats.phases().iter().map(|(_, phase)| phase.fills).sum();
+    assert!(phase_fills <= stats.fills);
+}
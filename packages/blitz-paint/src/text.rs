@@ -1,91 +1,155 @@
 use anyrender::PaintScene;
 use blitz_dom::{BaseDocument, node::TextBrush, util::ToColorColor};
-use kurbo::{Affine, Point, Stroke};
-use parley::{Line, PositionedLayoutItem};
-use peniko::Fill;
+use kurbo::{Affine, Stroke, Vec2};
+use parley::{GlyphRun, Line, PositionedLayoutItem};
+use peniko::{Color, Fill};
+use std::ops::Deref;
+use style::properties::ComputedValues;
 use style::values::computed::TextDecorationLine;
 
 pub(crate) fn stroke_text<'a>(
     scale: f64,
     scene: &mut impl PaintScene,
-    lines: impl Iterator<Item = Line<'a, TextBrush>>,
+    lines: impl Iterator<Item = Line<'a, TextBrush>> + Clone,
     doc: &BaseDocument,
-    pos: Point,
+    transform: Affine,
 ) {
-    let transform = Affine::translate((pos.x * scale, pos.y * scale));
+    stroke_lines(scale, scene, lines, transform, |brush| {
+        doc.get_node(brush.id).unwrap().primary_styles().unwrap()
+    });
+}
+
+/// Like [`stroke_text`], but paints every glyph run with `styles` rather than the styles of the node
+/// that the run belongs to (e.g. to paint text with the styles of a pseudo-element)
+pub(crate) fn stroke_text_with_styles<'a>(
+    scale: f64,
+    scene: &mut impl PaintScene,
+    lines: impl Iterator<Item = Line<'a, TextBrush>> + Clone,
+    styles: &ComputedValues,
+    transform: Affine,
+) {
+    stroke_lines(scale, scene, lines, transform, |_| styles);
+}
+
+fn stroke_lines<'a, S: Deref<Target = ComputedValues>>(
+    scale: f64,
+    scene: &mut impl PaintScene,
+    lines: impl Iterator<Item = Line<'a, TextBrush>> + Clone,
+    transform: Affine,
+    styles_for: impl Fn(&TextBrush) -> S,
+) {
+    // Text shadows are painted beneath all of the text (not just beneath the run which casts them)
+    for line in lines.clone() {
+        for item in line.items() {
+            if let PositionedLayoutItem::GlyphRun(glyph_run) = item {
+                let styles = styles_for(&glyph_run.style().brush);
+                let itext_styles = styles.get_inherited_text();
+
+                // The first shadow is painted on top, so shadows are painted in reverse order
+                //
+                // TODO: blur shadows (`shadow.blur`). `PaintScene` can't blur glyphs, so shadows are
+                // always painted sharp.
+                for shadow in itext_styles.text_shadow.0.iter().rev() {
+                    let color = shadow
+                        .color
+                        .resolve_to_absolute(&itext_styles.color)
+                        .as_color_color();
+                    let offset = Vec2::new(
+                        shadow.horizontal.px() as f64 * scale,
+                        shadow.vertical.px() as f64 * scale,
+                    );
+                    draw_glyph_run(
+                        scene,
+                        &glyph_run,
+                        &styles,
+                        transform.pre_translate(offset),
+                        Some(color),
+                    );
+                }
+            }
+        }
+    }
+
     for line in lines {
         for item in line.items() {
             if let PositionedLayoutItem::GlyphRun(glyph_run) = item {
-                let run = glyph_run.run();
-                let font = run.font();
-                let font_size = run.font_size();
-                let metrics = run.metrics();
-                let style = glyph_run.style();
-                let synthesis = run.synthesis();
-                let glyph_xform = synthesis
-                    .skew()
-                    .map(|angle| Affine::skew(angle.to_radians().tan() as f64, 0.0));
+                let styles = styles_for(&glyph_run.style().brush);
+                draw_glyph_run(scene, &glyph_run, &styles, transform, None);
+            }
+        }
+    }
+}
 
-                // Styles
-                let styles = doc
-                    .get_node(style.brush.id)
-                    .unwrap()
-                    .primary_styles()
-                    .unwrap();
-                let itext_styles = styles.get_inherited_text();
-                let text_styles = styles.get_text();
-                let text_color = itext_styles.color.as_color_color();
-                let text_decoration_color = text_styles
-                    .text_decoration_color
-                    .as_absolute()
-                    .map(ToColorColor::as_color_color)
-                    .unwrap_or(text_color);
-                let text_decoration_brush = anyrender::Paint::from(text_decoration_color);
-                let text_decoration_line = text_styles.text_decoration_line;
-                let has_underline = text_decoration_line.contains(TextDecorationLine::UNDERLINE);
-                let has_strikethrough =
-                    text_decoration_line.contains(TextDecorationLine::LINE_THROUGH);
+/// Draw a run of glyphs along with its text decorations. When painting a text shadow, `shadow_color`
+/// replaces the colors of both the glyphs and the decorations.
+fn draw_glyph_run(
+    scene: &mut impl PaintScene,
+    glyph_run: &GlyphRun<'_, TextBrush>,
+    styles: &ComputedValues,
+    transform: Affine,
+    shadow_color: Option<Color>,
+) {
+    let run = glyph_run.run();
+    let font = run.font();
+    let font_size = run.font_size();
+    let metrics = run.metrics();
+    let synthesis = run.synthesis();
+    let glyph_xform = synthesis
+        .skew()
+        .map(|angle| Affine::skew(angle.to_radians().tan() as f64, 0.0));
 
-                scene.draw_glyphs(
-                    font,
-                    font_size,
-                    true, // hint
-                    run.normalized_coords(),
-                    Fill::NonZero,
-                    &anyrender::Paint::from(text_color),
-                    1.0, // alpha
-                    transform,
-                    glyph_xform,
-                    glyph_run.positioned_glyphs().map(|glyph| anyrender::Glyph {
-                        id: glyph.id as _,
-                        x: glyph.x,
-                        y: glyph.y,
-                    }),
-                );
+    // Styles
+    let itext_styles = styles.get_inherited_text();
+    let text_styles = styles.get_text();
+    let text_color = shadow_color.unwrap_or_else(|| itext_styles.color.as_color_color());
+    // `text-decoration-color` may be relative to `currentColor` (either directly or through `color-mix()`)
+    let text_decoration_color = shadow_color.unwrap_or_else(|| {
+        text_styles
+            .text_decoration_color
+            .resolve_to_absolute(&itext_styles.color)
+            .as_color_color()
+    });
+    let text_decoration_brush = anyrender::Paint::from(text_decoration_color);
+    let text_decoration_line = text_styles.text_decoration_line;
+    let has_underline = text_decoration_line.contains(TextDecorationLine::UNDERLINE);
+    let has_strikethrough = text_decoration_line.contains(TextDecorationLine::LINE_THROUGH);
 
-                let mut draw_decoration_line =
-                    |offset: f32, size: f32, brush: &anyrender::Paint| {
-                        let x = glyph_run.offset() as f64;
-                        let w = glyph_run.advance() as f64;
-                        let y = (glyph_run.baseline() - offset + size / 2.0) as f64;
-                        let line = kurbo::Line::new((x, y), (x + w, y));
-                        scene.stroke(&Stroke::new(size as f64), transform, brush, None, &line)
-                    };
+    scene.draw_glyphs(
+        font,
+        font_size,
+        true, // hint
+        run.normalized_coords(),
+        Fill::NonZero,
+        &anyrender::Paint::from(text_color),
+        1.0, // alpha
+        transform,
+        glyph_xform,
+        glyph_run.positioned_glyphs().map(|glyph| anyrender::Glyph {
+            id: glyph.id as _,
+            x: glyph.x,
+            y: glyph.y,
+        }),
+    );
 
-                if has_underline {
-                    let offset = metrics.underline_offset;
-                    let size = metrics.underline_size;
+    let mut draw_decoration_line = |offset: f32, size: f32, brush: &anyrender::Paint| {
+        let x = glyph_run.offset() as f64;
+        let w = glyph_run.advance() as f64;
+        let y = (glyph_run.baseline() - offset + size / 2.0) as f64;
+        let line = kurbo::Line::new((x, y), (x + w, y));
+        scene.stroke(&Stroke::new(size as f64), transform, brush, None, &line)
+    };
 
-                    // TODO: intercept line when crossing an descending character like "gqy"
-                    draw_decoration_line(offset, size, &text_decoration_brush);
-                }
-                if has_strikethrough {
-                    let offset = metrics.strikethrough_offset;
-                    let size = metrics.strikethrough_size;
+    if has_underline {
+        let offset = metrics.underline_offset;
+        let size = metrics.underline_size;
 
-                    draw_decoration_line(offset, size, &text_decoration_brush);
-                }
-            }
-        }
+        // TODO: intercept line when crossing an descending character like "gqy"
+        draw_decoration_line(offset, size, &text_decoration_brush);
+    }
+    if has_strikethrough {
+        let offset = metrics.strikethrough_offset;
+        let size = metrics.strikethrough_size;
+
+        draw_decoration_line(offset, size, &text_decoration_brush);
     }
 }
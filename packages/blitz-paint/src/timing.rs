@@ -0,0 +1,175 @@
+//! Per-phase timing of the work done while painting a frame
+//!
+//! Phases are only tracked while a frame is painted by [`paint_scene_with_timings`](crate::paint_scene_with_timings)
+//! (or by [`paint_scene_with_stats`](crate::paint_scene_with_stats), which breaks its counts down by
+//! phase). Otherwise each phase just checks a thread-local flag and runs.
+//!
+//! Phases may be nested (an element's children are painted from within its own paint), so time is
+//! attributed exclusively: while a nested phase is running, the enclosing phase's timer is paused.
+
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+/// A phase of painting which is timed (and has its drawing commands counted) separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaintPhase {
+    /// Resolving `clip-path`s (including looking them up in the paint cache)
+    ClipPath,
+    /// Painting box shadows, backgrounds, borders and outlines
+    Background,
+    /// Painting replaced elements (images, SVGs and canvases) and form controls
+    Content,
+    /// Painting text, including inline layouts, text inputs and list markers
+    Text,
+    /// Traversing the children of elements, not including the time spent painting them
+    Children,
+}
+
+impl PaintPhase {
+    /// Every phase, in the order they are listed in [`PaintTimings::phases`]
+    pub const ALL: [PaintPhase; 5] = [
+        PaintPhase::ClipPath,
+        PaintPhase::Background,
+        PaintPhase::Content,
+        PaintPhase::Text,
+        PaintPhase::Children,
+    ];
+}
+
+/// The time spent in each phase while painting a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaintTimings {
+    pub clip_path: Duration,
+    pub background: Duration,
+    pub content: Duration,
+    pub text: Duration,
+    pub children: Duration,
+    /// The wall-clock time taken to paint the whole frame. This is at least the sum of the phases, as
+    /// work which isn't part of any phase (such as culling and pushing layers) is only counted here.
+    pub total: Duration,
+    /// The number of times each phase was entered, indexed by [`PaintPhase`]
+    counts: [usize; 5],
+}
+
+impl PaintTimings {
+    /// The time spent in `phase`
+    pub fn get(&self, phase: PaintPhase) -> Duration {
+        match phase {
+            PaintPhase::ClipPath => self.clip_path,
+            PaintPhase::Background => self.background,
+            PaintPhase::Content => self.content,
+            PaintPhase::Text => self.text,
+            PaintPhase::Children => self.children,
+        }
+    }
+
+    fn get_mut(&mut self, phase: PaintPhase) -> &mut Duration {
+        match phase {
+            PaintPhase::ClipPath => &mut self.clip_path,
+            PaintPhase::Background => &mut self.background,
+            PaintPhase::Content => &mut self.content,
+            PaintPhase::Text => &mut self.text,
+            PaintPhase::Children => &mut self.children,
+        }
+    }
+
+    /// The number of times `phase` was entered (roughly once per painted element). This is non-zero
+    /// for phases which ran even if they were too quick for the clock to measure.
+    pub fn count(&self, phase: PaintPhase) -> usize {
+        self.counts[phase as usize]
+    }
+
+    /// The time spent in each phase
+    pub fn phases(&self) -> [(PaintPhase, Duration); 5] {
+        PaintPhase::ALL.map(|phase| (phase, self.get(phase)))
+    }
+}
+
+struct Recorder {
+    timings: PaintTimings,
+    /// The phases which are currently running (innermost last), each with the time it was last resumed
+    running: Vec<(PaintPhase, Instant)>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+    static CURRENT_PHASE: Cell<Option<PaintPhase>> = const { Cell::new(None) };
+    static TRACKING_PHASES: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Track the phase of painting that is running for everything painted by `paint` (on this thread)
+pub(crate) fn track_phases<R>(paint: impl FnOnce() -> R) -> R {
+    let was_tracking = TRACKING_PHASES.replace(true);
+    let result = paint();
+    TRACKING_PHASES.set(was_tracking);
+    result
+}
+
+/// Record timings for everything painted by `paint` (on this thread) and return them
+pub(crate) fn record_timings(paint: impl FnOnce()) -> PaintTimings {
+    let start = Instant::now();
+    let previous = RECORDER.with_borrow_mut(|recorder| {
+        recorder.replace(Recorder {
+            timings: PaintTimings::default(),
+            running: Vec::new(),
+        })
+    });
+
+    track_phases(paint);
+
+    let recorder = RECORDER.with_borrow_mut(|recorder| std::mem::replace(recorder, previous));
+    let mut timings = recorder.unwrap().timings;
+    timings.total = start.elapsed();
+    timings
+}
+
+/// The phase of painting that is currently running on this thread (if phases are being tracked)
+pub(crate) fn current_phase() -> Option<PaintPhase> {
+    CURRENT_PHASE.get()
+}
+
+/// Run `f` as part of `phase`, attributing the time it takes to `phase` if timings are being recorded
+pub(crate) fn time_phase<R>(phase: PaintPhase, f: impl FnOnce() -> R) -> R {
+    if !TRACKING_PHASES.get() {
+        return f();
+    }
+
+    let outer_phase = CURRENT_PHASE.replace(Some(phase));
+    let result = record_phase_time(phase, f);
+    CURRENT_PHASE.set(outer_phase);
+    result
+}
+
+fn record_phase_time<R>(phase: PaintPhase, f: impl FnOnce() -> R) -> R {
+    let recording = RECORDER.with_borrow_mut(|recorder| {
+        let Some(recorder) = recorder else {
+            return false;
+        };
+        let now = Instant::now();
+        if let Some((outer, resumed)) = recorder.running.last() {
+            *recorder.timings.get_mut(*outer) += now - *resumed;
+        }
+        recorder.running.push((phase, now));
+        recorder.timings.counts[phase as usize] += 1;
+        true
+    });
+    if !recording {
+        return f();
+    }
+
+    let result = f();
+
+    RECORDER.with_borrow_mut(|recorder| {
+        let Some(recorder) = recorder else {
+            return;
+        };
+        let now = Instant::now();
+        if let Some((phase, resumed)) = recorder.running.pop() {
+            *recorder.timings.get_mut(phase) += now - resumed;
+        }
+        if let Some((_, resumed)) = recorder.running.last_mut() {
+            *resumed = now;
+        }
+    });
+    result
+}
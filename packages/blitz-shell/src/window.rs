@@ -14,7 +14,7 @@ use winit::keyboard::PhysicalKey;
 use std::sync::Arc;
 use std::task::Waker;
 use std::time::Instant;
-use winit::event::{ElementState, MouseButton};
+use winit::event::{ElementState, MouseButton, TouchPhase};
 use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
 use winit::window::{Theme, WindowAttributes, WindowId};
 use winit::{event::Modifiers, event::WindowEvent, keyboard::KeyCode, window::Window};
@@ -320,6 +320,18 @@ impl<Rend: WindowRenderer> View<Rend> {
                                 self.doc.devtools_mut().toggle_highlight_hover();
                                 self.request_redraw();
                             }
+                            KeyCode::KeyP => {
+                                self.doc.devtools_mut().toggle_paint_flash();
+                                self.request_redraw();
+                            }
+                            KeyCode::KeyG => {
+                                self.doc.devtools_mut().toggle_show_gaps();
+                                self.request_redraw();
+                            }
+                            KeyCode::KeyL => {
+                                self.doc.devtools_mut().toggle_show_grid();
+                                self.request_redraw();
+                            }
                             KeyCode::KeyT => self.doc.print_taffy_tree(),
                             _ => {}
                         };
@@ -342,7 +354,7 @@ impl<Rend: WindowRenderer> View<Rend> {
 
             // Mouse/pointer events
             WindowEvent::CursorEntered { /*device_id*/.. } => {}
-            WindowEvent::CursorLeft { /*device_id*/.. } => {}
+            WindowEvent::CursorLeft { /*device_id*/.. } => self.doc.hide_tooltip(),
             WindowEvent::CursorMoved { position, .. } => {
                 let winit::dpi::LogicalPosition::<f32> { x, y } = position.to_logical(self.window.scale_factor());
                 self.mouse_pos = (x, y);
@@ -382,18 +394,29 @@ impl<Rend: WindowRenderer> View<Rend> {
                 self.doc.handle_ui_event(event);
                 self.request_redraw();
             }
-            WindowEvent::MouseWheel { delta, .. } => {
+            WindowEvent::MouseWheel { delta, phase, .. } => {
                 let (scroll_x, scroll_y)= match delta {
                     winit::event::MouseScrollDelta::LineDelta(x, y) => (x as f64 * 20.0, y as f64 * 20.0),
                     winit::event::MouseScrollDelta::PixelDelta(offsets) => (offsets.x, offsets.y)
                 };
 
-                let has_changed = if let Some(hover_node_id) = self.doc.get_hover_node_id() {
+                let hover_node_id = self.doc.get_hover_node_id();
+                let mut has_changed = if let Some(hover_node_id) = hover_node_id {
                     self.doc.scroll_node_by_has_changed(hover_node_id, scroll_x, scroll_y)
                 } else {
                     self.doc.scroll_viewport_by_has_changed(scroll_x, scroll_y)
                 };
 
+                // Snap once the scroll gesture ends. Each mouse wheel tick is a complete gesture which
+                // advances to the next snap point in the direction of scrolling.
+                let snap_direction = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(..) => Some(-scroll_y),
+                    winit::event::MouseScrollDelta::PixelDelta(_) => (phase == TouchPhase::Ended).then_some(0.0),
+                };
+                if let Some(direction) = snap_direction {
+                    has_changed |= self.doc.snap_scroll_position(hover_node_id, direction);
+                }
+
                 if has_changed {
                     self.request_redraw();
                 }
@@ -9,6 +9,14 @@ pub struct DevtoolSettings {
     /// Render browser-style colored overlay showing the content-box,
     /// padding, border, and margin of the hovered element
     pub highlight_hover: bool,
+    /// Flash a translucent overlay over elements that were restyled or relaid out
+    /// (and thus repainted) since the previous frame
+    pub paint_flash: bool,
+    /// Shade the gaps (`row-gap` and `column-gap`) between the items of
+    /// flex and grid containers
+    pub show_gaps: bool,
+    /// Draw the lines (with line numbers) of grid containers
+    pub show_grid: bool,
 }
 
 impl DevtoolSettings {
@@ -21,4 +29,19 @@ impl DevtoolSettings {
     pub fn toggle_highlight_hover(&mut self) {
         self.highlight_hover = !self.highlight_hover
     }
+
+    /// Toggle the [`paint_flash`](Self::paint_flash) setting
+    pub fn toggle_paint_flash(&mut self) {
+        self.paint_flash = !self.paint_flash
+    }
+
+    /// Toggle the [`show_gaps`](Self::show_gaps) setting
+    pub fn toggle_show_gaps(&mut self) {
+        self.show_gaps = !self.show_gaps
+    }
+
+    /// Toggle the [`show_grid`](Self::show_grid) setting
+    pub fn toggle_show_grid(&mut self) {
+        self.show_grid = !self.show_grid
+    }
 }
@@ -93,6 +93,26 @@ impl DomEvent {
     }
 }
 
+/// The phase of an event's propagation through the DOM tree
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventPhase {
+    /// The event is being delivered to its target
+    AtTarget,
+    /// The event is bubbling up through the target's ancestors
+    Bubbling,
+}
+
+/// What caused an element to be focussed. This determines whether the element matches `:focus-visible`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FocusOrigin {
+    /// Keyboard navigation (e.g. pressing Tab)
+    Keyboard,
+    /// A mouse click or touch
+    Pointer,
+    /// A call to an API such as [`focus`](https://developer.mozilla.org/en-US/docs/Web/API/HTMLElement/focus)
+    Programmatic,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u8)]
 pub enum DomEventKind {
@@ -105,6 +125,10 @@ pub enum DomEventKind {
     KeyUp,
     Input,
     Ime,
+    Focus,
+    Blur,
+    Cancel,
+    Close,
 }
 impl DomEventKind {
     pub fn discriminant(self) -> u8 {
@@ -124,6 +148,10 @@ impl FromStr for DomEventKind {
             "keyup" => Ok(Self::KeyUp),
             "input" => Ok(Self::Input),
             "composition" => Ok(Self::Ime),
+            "focus" => Ok(Self::Focus),
+            "blur" => Ok(Self::Blur),
+            "cancel" => Ok(Self::Cancel),
+            "close" => Ok(Self::Close),
             _ => Err(()),
         }
     }
@@ -141,6 +169,12 @@ pub enum DomEventData {
     KeyUp(BlitzKeyEvent),
     Input(BlitzInputEvent),
     Ime(BlitzImeEvent),
+    Focus(BlitzFocusEvent),
+    Blur(BlitzFocusEvent),
+    /// Fired at a modal `<dialog>` when the user requests to close it (e.g. by pressing Escape)
+    Cancel,
+    /// Fired at a `<dialog>` after it has been closed
+    Close,
 }
 impl DomEventData {
     pub fn discriminant(&self) -> u8 {
@@ -164,6 +198,10 @@ impl DomEventData {
             Self::KeyUp { .. } => "keyup",
             Self::Input { .. } => "input",
             Self::Ime { .. } => "composition",
+            Self::Focus { .. } => "focus",
+            Self::Blur { .. } => "blur",
+            Self::Cancel => "cancel",
+            Self::Close => "close",
         }
     }
 
@@ -178,6 +216,10 @@ impl DomEventData {
             Self::KeyUp { .. } => DomEventKind::KeyUp,
             Self::Input { .. } => DomEventKind::Input,
             Self::Ime { .. } => DomEventKind::Ime,
+            Self::Focus { .. } => DomEventKind::Focus,
+            Self::Blur { .. } => DomEventKind::Blur,
+            Self::Cancel => DomEventKind::Cancel,
+            Self::Close => DomEventKind::Close,
         }
     }
 
@@ -192,6 +234,10 @@ impl DomEventData {
             Self::KeyPress { .. } => true,
             Self::Ime { .. } => true,
             Self::Input { .. } => false,
+            Self::Focus { .. } => false,
+            Self::Blur { .. } => false,
+            Self::Cancel => true,
+            Self::Close => false,
         }
     }
 
@@ -206,6 +252,10 @@ impl DomEventData {
             Self::KeyPress { .. } => true,
             Self::Ime { .. } => true,
             Self::Input { .. } => true,
+            Self::Focus { .. } => false,
+            Self::Blur { .. } => false,
+            Self::Cancel => false,
+            Self::Close => false,
         }
     }
 }
@@ -317,6 +367,12 @@ pub struct BlitzInputEvent {
     pub value: String,
 }
 
+#[derive(Clone, Debug)]
+pub struct BlitzFocusEvent {
+    /// The element losing focus (for `focus` events) or gaining focus (for `blur` events), if any
+    pub related_target: Option<usize>,
+}
+
 /// Copy of Winit IME event to avoid lower-level Blitz crates depending on winit
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BlitzImeEvent {
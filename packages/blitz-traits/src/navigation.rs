@@ -9,6 +9,13 @@ use crate::net::{Body, Request};
 /// or submitting a form.
 pub trait NavigationProvider: Send + Sync + 'static {
     fn navigate_to(&self, options: NavigationOptions);
+
+    /// Download the resource at `url` rather than navigating to it (e.g. when clicking a link with
+    /// a `download` attribute). `filename` is the suggested name for the downloaded file (if any).
+    fn download(&self, url: Url, filename: Option<String>) {
+        let _ = url;
+        let _ = filename;
+    }
 }
 
 pub struct DummyNavigationProvider;
@@ -25,6 +32,7 @@ pub struct NavigationOptions {
     /// The URL to navigate to
     pub url: Url,
 
+    /// The content type of the request body (empty if the navigation has no body)
     pub content_type: String,
 
     /// Source document for the navigation
@@ -33,6 +41,16 @@ pub struct NavigationOptions {
     pub method: Method,
 
     pub document_resource: Body,
+
+    /// The name of the browsing context targeted by the navigation (from a `target` attribute).
+    /// `None` if the navigation targets the source document's own browsing context.
+    pub target: Option<String>,
+
+    /// Whether the new browsing context should be opened without access to its opener (`rel="noopener"`)
+    pub noopener: bool,
+
+    /// Whether the `Referer` header should be omitted for the navigation (`rel="noreferrer"`)
+    pub noreferrer: bool,
 }
 
 impl NavigationOptions {
@@ -43,6 +61,9 @@ impl NavigationOptions {
             source_document,
             method: Method::GET,
             document_resource: Body::Empty,
+            target: None,
+            noopener: false,
+            noreferrer: false,
         }
     }
     pub fn set_document_resource(mut self, document_resource: Body) -> Self {
@@ -55,6 +76,25 @@ impl NavigationOptions {
         self
     }
 
+    pub fn set_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set the `noopener` and `noreferrer` flags from the value of a `rel` attribute.
+    /// Note that `noreferrer` implies `noopener`.
+    pub fn set_rel(mut self, rel: &str) -> Self {
+        for keyword in rel.split_ascii_whitespace() {
+            if keyword.eq_ignore_ascii_case("noopener") {
+                self.noopener = true;
+            } else if keyword.eq_ignore_ascii_case("noreferrer") {
+                self.noopener = true;
+                self.noreferrer = true;
+            }
+        }
+        self
+    }
+
     pub fn into_request(self) -> Request {
         Request {
             url: self.url,
@@ -1,6 +1,9 @@
 //! Abstraction over windowing / operating system ("shell") functionality
 
 use cursor_icon::CursorIcon;
+use std::sync::Arc;
+
+use crate::navigation::NavigationOptions;
 
 /// Type representing an error performing a clipboard operation
 // TODO: fill out with meaningful errors
@@ -13,6 +16,17 @@ pub trait ShellProvider {
     fn set_cursor(&self, icon: CursorIcon) {
         let _ = icon;
     }
+    /// Set the cursor to a custom image (from `cursor: url(...)`). Returns `false` if custom cursors
+    /// are not supported, in which case the document falls back to calling `set_cursor`.
+    fn set_custom_cursor(&self, cursor: CustomCursor) -> bool {
+        let _ = cursor;
+        false
+    }
+    /// Open a new window (or tab) and navigate it as described by `options` (e.g. when clicking a
+    /// link with `target="_blank"`). `options.target` contains the name of the requested browsing context.
+    fn open_new_window(&self, options: NavigationOptions) {
+        let _ = options;
+    }
     fn set_window_title(&self, title: String) {
         let _ = title;
     }
@@ -25,6 +39,13 @@ pub trait ShellProvider {
         let _ = width;
         let _ = height;
     }
+    /// Show a tooltip with the specified text at the pointer position (in CSS pixels relative to the viewport)
+    fn show_tooltip(&self, text: String, x: f32, y: f32) {
+        let _ = text;
+        let _ = x;
+        let _ = y;
+    }
+    fn hide_tooltip(&self) {}
     fn get_clipboard_text(&self) -> Result<String, ClipboardError> {
         Err(ClipboardError)
     }
@@ -43,20 +64,42 @@ pub trait ShellProvider {
     }
 }
 
+/// A decoded cursor image along with its hotspot
+#[derive(Debug, Clone)]
+pub struct CustomCursor {
+    /// The raw image data in RGBA8 format
+    pub rgba: Arc<Vec<u8>>,
+    pub width: u32,
+    pub height: u32,
+    /// The position within the image which is aligned with the pointer position
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+}
+
 pub struct DummyShellProvider;
 impl ShellProvider for DummyShellProvider {}
 
 /// The system color scheme (light and dark mode)
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorScheme {
     #[default]
     Light,
     Dark,
 }
 
+/// The media type that the document is rendered for (and which `@media` rules are evaluated against)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaType {
+    #[default]
+    Screen,
+    /// Paged output (e.g. printing or exporting to PDF). The window size is the size of a page.
+    Print,
+}
+
 #[derive(Debug, Clone)]
 pub struct Viewport {
     pub color_scheme: ColorScheme,
+    pub media_type: MediaType,
     pub window_size: (u32, u32),
     pub hidpi_scale: f32,
     pub zoom: f32,
@@ -69,6 +112,7 @@ impl Default for Viewport {
             hidpi_scale: 1.0,
             zoom: 1.0,
             color_scheme: ColorScheme::Light,
+            media_type: MediaType::Screen,
         }
     }
 }
@@ -85,9 +129,16 @@ impl Viewport {
             hidpi_scale: scale_factor,
             zoom: 1.0,
             color_scheme,
+            media_type: MediaType::Screen,
         }
     }
 
+    /// Render for the specified media type (e.g. [`MediaType::Print`] to paginate the document)
+    pub fn with_media_type(mut self, media_type: MediaType) -> Self {
+        self.media_type = media_type;
+        self
+    }
+
     /// Total scaling, computed as `hidpi_scale_factor * zoom`
     pub fn scale(&self) -> f32 {
         self.hidpi_scale * self.zoom
@@ -184,6 +184,12 @@ impl EventHandler for DioxusEventHandler<'_> {
 
             // TODO: Implement IME handling
             DomEventData::Ime(_) => None,
+
+            // TODO: Implement focus handling
+            DomEventData::Focus(_) | DomEventData::Blur(_) => None,
+
+            // TODO: Implement dialog event handling
+            DomEventData::Cancel | DomEventData::Close => None,
         };
 
         let Some(event_data) = event_data else {
@@ -136,6 +136,23 @@ pub fn inset(val: &stylo::InsetVal) -> taffy::LengthPercentageAuto {
     }
 }
 
+/// Convert the inset properties (`top`, `right`, `bottom` and `left`) of an element. They don't apply to
+/// `position: static` elements (which taffy lays out as `position: relative`), so they are always `auto`.
+#[inline]
+pub fn insets(style: &stylo::ComputedValues) -> taffy::Rect<taffy::LengthPercentageAuto> {
+    if style.clone_position() == stylo::Position::Static {
+        return taffy::Rect::auto();
+    }
+
+    let pos = style.get_position();
+    taffy::Rect {
+        left: self::inset(&pos.left),
+        right: self::inset(&pos.right),
+        top: self::inset(&pos.top),
+        bottom: self::inset(&pos.bottom),
+    }
+}
+
 #[inline]
 pub fn is_block(input: stylo::Display) -> bool {
     matches!(input.outside(), stylo::DisplayOutside::Block)
@@ -604,12 +621,7 @@ pub fn to_taffy_style(style: &stylo::ComputedValues) -> taffy::Style<Atom> {
         },
         aspect_ratio: self::aspect_ratio(pos.aspect_ratio),
 
-        inset: taffy::Rect {
-            left: self::inset(&pos.left),
-            right: self::inset(&pos.right),
-            top: self::inset(&pos.top),
-            bottom: self::inset(&pos.bottom),
-        },
+        inset: self::insets(style),
         margin: taffy::Rect {
             left: self::margin(&margin.margin_left),
             right: self::margin(&margin.margin_right),